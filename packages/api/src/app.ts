@@ -1,22 +1,31 @@
 import { Hono } from "hono";
 import { env } from "hono/adapter";
 import { cors } from "hono/cors";
+import { requestId, type RequestIdVariables } from "hono/request-id";
 import { type PinoLogger, pinoLogger } from "hono-pino";
+import { errorResponse } from "./lib/errors";
 import { logger } from "./lib/logger";
 import { Sentry } from "./lib/sentry";
 import { apiKeyAuth } from "./middleware/auth";
+import { requestBodyLimit } from "./middleware/body-limit";
 import { rateLimit } from "./middleware/rate-limit";
 import { downloadRouter } from "./routes/download";
 import { healthRouter } from "./routes/health";
 
-const app = new Hono<{ Variables: { logger: PinoLogger } }>();
+const app = new Hono<{ Variables: { logger: PinoLogger } & RequestIdVariables }>();
 
+// Honors a caller's `X-Request-Id` (or mints one) and echoes it back; the
+// access log, extraction summary and per-run yt-dlp spans all carry it.
+app.use("*", requestId());
 app.use(
 	"*",
 	pinoLogger({
 		pino: logger,
 		http: {
-			onReqBindings: (c) => ({ req: { method: c.req.method, url: c.req.path } }),
+			onReqBindings: (c) => ({
+				requestId: c.var.requestId,
+				req: { method: c.req.method, url: c.req.path },
+			}),
 			onResBindings: (c) => ({ res: { status: c.res.status } }),
 			onResLevel: (c) => {
 				if (c.res.status >= 500) return "error";
@@ -27,8 +36,26 @@ app.use(
 	}),
 );
 
-app.use(
-	"/api/*",
+/**
+ * The methods registered for `path` (plus `OPTIONS`), read off the app's own
+ * route table so a new route is advertised to preflights without touching
+ * the CORS config.
+ */
+function routeMethods(path: string): string[] {
+	const segments = path.split("/");
+	const methods = new Set<string>();
+	for (const route of app.routes) {
+		if (route.method === "ALL") continue;
+		const pattern = route.path.split("/");
+		const matches =
+			pattern.length === segments.length &&
+			pattern.every((part, i) => part.startsWith(":") || part === segments[i]);
+		if (matches) methods.add(route.method);
+	}
+	return [...methods, "OPTIONS"];
+}
+
+app.use("/api/*", (c, next) =>
 	cors({
 		origin: (origin, c) => {
 			const allowedOrigins = env(c).ALLOWED_ORIGINS as string | undefined;
@@ -36,20 +63,19 @@ app.use(
 				?.split(",")
 				.map((s) => s.trim())
 				.filter(Boolean);
-			if (!allowed?.length) {
-				return "";
-			}
-			return allowed.includes(origin) ? origin : allowed[0];
+			// Echo only allowlisted origins; anything else gets no ACAO header.
+			return allowed?.includes(origin) ? origin : "";
 		},
-		allowMethods: ["GET", "POST", "OPTIONS"],
+		allowMethods: c.req.method === "OPTIONS" ? routeMethods(c.req.path) : [],
 		allowHeaders: ["Authorization", "Content-Type"],
-	}),
+	})(c, next),
 );
 
 app.use("/api/*", rateLimit());
 // Mounted after rateLimit so unauthenticated probes still consume the
 // per-client abuse budget before being rejected.
 app.use("/api/*", apiKeyAuth());
+app.use("/api/*", requestBodyLimit());
 
 app.route("/", downloadRouter);
 app.route("/", healthRouter);
@@ -57,7 +83,7 @@ app.route("/", healthRouter);
 app.onError((err, c) => {
 	Sentry.captureException(err);
 	c.var.logger?.error({ err }, "unhandled");
-	return c.json({ success: false, error: "Internal server error" }, 500);
+	return errorResponse(c, "INTERNAL", "Internal server error");
 });
 
 export default app;
@@ -1,9 +1,33 @@
 import { serveStatic } from "hono/bun";
 import app from "./app";
+import { blockedPatterns, loadBlocklistFile } from "./lib/blocklist";
+import { maxBodyBytes, publicBaseUrl } from "./lib/env";
 import { logger } from "./lib/logger";
+import { platformDomains } from "./lib/platforms";
+import { retryPolicy } from "./lib/retry";
 import { initSentry } from "./lib/sentry";
+import { tlsConfig } from "./lib/tls";
+import { configArgs, defaultVideoQuality, hasFfmpeg, installedYtDlp } from "./lib/ytdlp";
 
 initSentry();
+// Validate env-driven settings before accepting traffic.
+configArgs();
+maxBodyBytes();
+publicBaseUrl();
+platformDomains();
+blockedPatterns();
+loadBlocklistFile();
+defaultVideoQuality();
+const retry = retryPolicy();
+
+// Re-read BLOCKLIST_FILE on SIGHUP so a takedown applies without a restart.
+process.on("SIGHUP", () => {
+	try {
+		loadBlocklistFile();
+	} catch (error) {
+		logger.error({ err: error }, "Blocklist reload failed; keeping the previous list");
+	}
+});
 
 // Serve the static client (packages/web/dist/client, copied to ./public in the
 // Docker image). Falls through to 404 when the dir is absent — e.g. local API
@@ -12,10 +36,25 @@ const staticRoot = process.env.STATIC_ROOT || "./public";
 app.use("*", serveStatic({ root: staticRoot }));
 
 const port = parseInt(process.env.PORT || "3001", 10);
+const tls = tlsConfig();
 
-logger.info({ port }, "Snatch running");
+logger.info({ port, tls: Boolean(tls) }, "Snatch running");
+logger.info({ retry }, "Probe retry policy");
+// Report the engine once, so a container built without yt-dlp shows up in the
+// startup logs rather than in the first failed extraction.
+void installedYtDlp().then((installed) => {
+	if (!installed) {
+		logger.warn("yt-dlp not found; it will be downloaded on first use");
+		return;
+	}
+	logger.info({ version: installed.version, binary: installed.binary }, "yt-dlp found");
+});
+void hasFfmpeg().then((found) => {
+	if (!found) logger.warn("ffmpeg not found; split video and audio streams will not be merged");
+});
 
 export default {
 	port,
 	fetch: app.fetch,
+	tls,
 };
@@ -0,0 +1,156 @@
+import { readFileSync } from "node:fs";
+import { canonicalUrl, detectPlatform, SERVICES, type SupportedPlatform } from "@snatch/shared";
+import { ERROR_STATUS } from "./errors";
+import { logger } from "./logger";
+
+/** A URL the operator's `BLOCKED_PATTERNS` refuses. */
+export class BlockedUrlError extends Error {
+	readonly code = "BLOCKED";
+	readonly status = ERROR_STATUS.BLOCKED;
+}
+
+/** A URL or account listed in the operator's `BLOCKLIST_FILE`, e.g. after a takedown. */
+export class BlockedContentError extends Error {
+	readonly code = "BLOCKED_CONTENT";
+	readonly status = ERROR_STATUS.BLOCKED_CONTENT;
+}
+
+let compiled: { raw: string; patterns: RegExp[] } | undefined;
+
+function escapeRegExp(text: string): string {
+	return text.replace(/[.*+?^${}()|[\]\\/]/g, "\\$&");
+}
+
+/**
+ * `BLOCKED_PATTERNS` compiled: comma-separated entries, each a plain
+ * substring or a `/regex/flags` literal, all matched case-insensitively.
+ * Compiled once per distinct value (startup calls this first); an invalid
+ * regex is logged and skipped rather than failing every request.
+ */
+export function blockedPatterns(): RegExp[] {
+	const raw = process.env.BLOCKED_PATTERNS ?? "";
+	if (compiled?.raw === raw) return compiled.patterns;
+
+	const patterns = raw.split(",").flatMap((entry) => {
+		const pattern = entry.trim();
+		if (!pattern) return [];
+		const literal = pattern.match(/^\/(.+)\/([a-z]*)$/);
+		if (!literal) return [new RegExp(escapeRegExp(pattern), "i")];
+		try {
+			// `g`/`y` would make `test()` stateful across URLs.
+			const flags = `${literal[2].replace(/[giy]/g, "")}i`;
+			return [new RegExp(literal[1], flags)];
+		} catch (error) {
+			logger.warn({ err: error, pattern }, "Ignoring invalid BLOCKED_PATTERNS entry");
+			return [];
+		}
+	});
+	compiled = { raw, patterns };
+	return patterns;
+}
+
+/** Whether the normalized form of `url` matches any blocked pattern. */
+export function isBlockedUrl(url: string): boolean {
+	const patterns = blockedPatterns();
+	if (patterns.length === 0) return false;
+	let normalized: string;
+	try {
+		normalized = new URL(url.trim()).href;
+	} catch {
+		return false;
+	}
+	return patterns.some((pattern) => pattern.test(normalized));
+}
+
+/**
+ * Where each platform's URLs name the posting account, as `host/path`: the
+ * username `platform:username` entries of `BLOCKLIST_FILE` are matched against.
+ */
+const ACCOUNT_PATTERNS: Partial<Record<SupportedPlatform, RegExp>> = {
+	// /USER/p/CODE, /USER/reel/CODE and /USER/tv/CODE.
+	instagram: /^[^/]+\/(?!(?:p|reels?|tv)\/)([\w.]+)\/(?:p|reels?|tv)\//,
+	reddit: /^[^/]+\/(?:u|user)\/([\w-]+)/,
+	tiktok: /^[^/]+\/@([\w.-]+)/,
+	twitch: /^[^/]+\/(\w+)\/clip\//,
+	twitter: /^[^/]+\/(\w+)\/status(?:es)?\//,
+};
+
+/** `platform:username`, the account written as `@user` or `user`. */
+const ACCOUNT_ENTRY_REGEX = /^([a-z]+):@?([\w.-]+)$/i;
+
+interface Blocklist {
+	/** Canonical URL prefixes: scheme and host lowercased, the path as written. */
+	prefixes: string[];
+	/** Lowercased `platform:username` keys. */
+	accounts: Set<string>;
+}
+
+let blocklist: Blocklist = { prefixes: [], accounts: new Set() };
+
+/** Parse `BLOCKLIST_FILE` text; unusable lines are logged and skipped. */
+function parseBlocklist(text: string): Blocklist {
+	const parsed: Blocklist = { prefixes: [], accounts: new Set() };
+	for (const line of text.split(/\r?\n/)) {
+		const entry = line.trim();
+		if (!entry || entry.startsWith("#")) continue;
+		if (/^https?:\/\//i.test(entry)) {
+			parsed.prefixes.push(canonicalUrl(entry));
+			continue;
+		}
+		const account = entry.match(ACCOUNT_ENTRY_REGEX);
+		const platform = account?.[1].toLowerCase();
+		if (account && SERVICES.some((service) => service.id === platform)) {
+			parsed.accounts.add(`${platform}:${account[2].toLowerCase()}`);
+			continue;
+		}
+		logger.warn({ entry }, "Ignoring invalid BLOCKLIST_FILE entry");
+	}
+	return parsed;
+}
+
+/**
+ * (Re)load `BLOCKLIST_FILE`: one entry per line, either a URL prefix
+ * (`https://x.com/someone/status/`) or `platform:username` (`tiktok:@someone`,
+ * with a `SERVICES` id). Usernames match case-insensitively; a prefix's path
+ * does not, since post IDs are case-sensitive. Blank lines and `#` comments
+ * are skipped. Called at startup, where an unreadable file fails
+ * the boot, and on SIGHUP, where it keeps the list already loaded.
+ */
+export function loadBlocklistFile(): void {
+	const file = process.env.BLOCKLIST_FILE?.trim();
+	if (!file) {
+		blocklist = { prefixes: [], accounts: new Set() };
+		return;
+	}
+	blocklist = parseBlocklist(readFileSync(file, "utf-8"));
+	const { prefixes, accounts } = blocklist;
+	logger.info({ file, prefixes: prefixes.length, accounts: accounts.size }, "Blocklist loaded");
+}
+
+/** Whether the canonical form of `url` is listed in the loaded `BLOCKLIST_FILE`. */
+export function isBlockedContent(url: string): boolean {
+	const { prefixes, accounts } = blocklist;
+	if (prefixes.length === 0 && accounts.size === 0) return false;
+	// `canonicalUrl` lowercases the scheme and host; paths and IDs keep their case.
+	const canonical = canonicalUrl(url);
+	if (prefixes.some((prefix) => canonical.startsWith(prefix))) return true;
+
+	let parsed: URL;
+	try {
+		parsed = new URL(canonical);
+	} catch {
+		return false;
+	}
+	const platform = detectPlatform(canonical);
+	const pattern = platform ? ACCOUNT_PATTERNS[platform] : undefined;
+	const username = pattern?.exec(parsed.hostname + parsed.pathname)?.[1]?.toLowerCase();
+	return username !== undefined && accounts.has(`${platform}:${username}`);
+}
+
+/** Throw the error of whichever operator blocklist refuses `url`, if one does. */
+export function assertNotBlocked(url: string): void {
+	if (isBlockedUrl(url)) throw new BlockedUrlError("This URL is blocked on this server");
+	if (isBlockedContent(url)) {
+		throw new BlockedContentError("This content is unavailable on this server for legal reasons");
+	}
+}
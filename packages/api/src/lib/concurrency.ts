@@ -0,0 +1,57 @@
+/**
+ * Map `items` through `fn` with at most `limit` calls in flight, resolving to
+ * results in input order regardless of completion order.
+ */
+export async function mapWithConcurrency<T, R>(
+	items: readonly T[],
+	limit: number,
+	fn: (item: T, index: number) => Promise<R>,
+): Promise<R[]> {
+	const results = new Array<R>(items.length);
+	let next = 0;
+	const worker = async () => {
+		while (next < items.length) {
+			const index = next++;
+			results[index] = await fn(items[index], index);
+		}
+	};
+	await Promise.all(Array.from({ length: Math.min(limit, items.length) }, worker));
+	return results;
+}
+
+/** Resolves once `signal` aborts; never rejects. */
+function aborted(signal: AbortSignal): Promise<void> {
+	return new Promise((resolve) => {
+		if (signal.aborted) resolve();
+		else signal.addEventListener("abort", () => resolve(), { once: true });
+	});
+}
+
+/**
+ * {@link mapWithConcurrency} under one shared deadline. Items still queued or
+ * running when `deadline` aborts resolve to `onTimeout(item)` instead, so a
+ * few slow calls cannot hold the whole result back. With `itemTimeoutMs`, each
+ * call also gets its own clock, started when it leaves the queue, so one
+ * hanging item times out alone and frees its slot for the rest. `fn` receives
+ * the signal covering both and should honour it so abandoned work is actually
+ * stopped.
+ */
+export async function mapWithDeadline<T, R>(
+	items: readonly T[],
+	limit: number,
+	deadline: AbortSignal,
+	fn: (item: T, index: number, signal: AbortSignal) => Promise<R>,
+	onTimeout: (item: T, index: number) => R,
+	itemTimeoutMs?: number,
+): Promise<R[]> {
+	return mapWithConcurrency(items, limit, async (item, index) => {
+		if (deadline.aborted) return onTimeout(item, index);
+		const signal = itemTimeoutMs
+			? AbortSignal.any([deadline, AbortSignal.timeout(itemTimeoutMs)])
+			: deadline;
+		return Promise.race([
+			fn(item, index, signal),
+			aborted(signal).then(() => onTimeout(item, index)),
+		]);
+	});
+}
@@ -0,0 +1,36 @@
+/** Last accepted run per key, in ms since the epoch. Per-process, like the rate limiter. */
+const lastRuns = new Map<string, number>();
+
+/** Sweep expired keys once the map grows past this, so abandoned URLs do not pile up. */
+const SWEEP_THRESHOLD = 1_000;
+
+/** Forget every recorded run. */
+export function clearCooldowns(): void {
+	lastRuns.clear();
+}
+
+/**
+ * Milliseconds until `key` may run again, or 0 after recording this run. At
+ * most one run per key is accepted every `intervalMs`, however many clients
+ * ask for it.
+ */
+export function takeCooldown(key: string, intervalMs: number, now = Date.now()): number {
+	const last = lastRuns.get(key);
+	if (last !== undefined && now - last < intervalMs) return intervalMs - (now - last);
+
+	if (lastRuns.size >= SWEEP_THRESHOLD) {
+		for (const [stale, at] of lastRuns) {
+			if (now - at >= intervalMs) lastRuns.delete(stale);
+		}
+	}
+	lastRuns.set(key, now);
+	return 0;
+}
+
+/**
+ * Undo the {@link takeCooldown} of `key` taken at `takenAt`, for a run that
+ * failed and should be retryable at once. A later run's record is kept.
+ */
+export function releaseCooldown(key: string, takenAt: number): void {
+	if (lastRuns.get(key) === takenAt) lastRuns.delete(key);
+}
@@ -0,0 +1,43 @@
+/** A positive integer from a raw env value, or `undefined` when unset or invalid. */
+export function positiveInt(value: unknown): number | undefined {
+	const parsed = Number.parseInt(typeof value === "string" ? value : "", 10);
+	return parsed > 0 ? parsed : undefined;
+}
+
+/** Request body cap when `MAX_BODY_BYTES` is unset; ample for a single resolve. */
+export const DEFAULT_MAX_BODY_BYTES = 10 * 1024;
+
+/**
+ * `MAX_BODY_BYTES`, or {@link DEFAULT_MAX_BODY_BYTES}. Throws on a malformed
+ * value; called once at startup so a typo fails the boot, not every request.
+ */
+export function maxBodyBytes(): number {
+	const raw = process.env.MAX_BODY_BYTES?.trim();
+	if (!raw) return DEFAULT_MAX_BODY_BYTES;
+	const bytes = positiveInt(raw);
+	if (!bytes || String(bytes) !== raw) {
+		throw new Error(`MAX_BODY_BYTES must be a positive integer, got "${raw}"`);
+	}
+	return bytes;
+}
+
+/**
+ * `PUBLIC_BASE_URL` without its trailing slash, or `undefined` when unset.
+ * Signed download links are built on it instead of the request's own origin,
+ * for deployments reached through a proxy on another host or under a path
+ * prefix. Throws on anything but a plain http(s) URL; checked at startup.
+ */
+export function publicBaseUrl(): string | undefined {
+	const raw = process.env.PUBLIC_BASE_URL?.trim();
+	if (!raw) return undefined;
+	let url: URL;
+	try {
+		url = new URL(raw);
+	} catch {
+		throw new Error(`PUBLIC_BASE_URL must be an absolute URL, got "${raw}"`);
+	}
+	if (!["http:", "https:"].includes(url.protocol) || url.search || url.hash) {
+		throw new Error(`PUBLIC_BASE_URL must be a plain http(s) URL, got "${raw}"`);
+	}
+	return `${url.origin}${url.pathname}`.replace(/\/+$/, "");
+}
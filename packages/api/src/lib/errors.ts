@@ -0,0 +1,100 @@
+import type { ErrorCode, ErrorResponse } from "@snatch/shared";
+import type { Context } from "hono";
+import { logger } from "./logger";
+
+/** The HTTP status each error code is served with; handlers never pick statuses themselves. */
+export const ERROR_STATUS = {
+	BAD_REQUEST: 400,
+	BAD_REQUEST_BODY: 422,
+	INVALID_URL: 400,
+	INVALID_CHAR: 400,
+	UNSUPPORTED_PLATFORM: 400,
+	INVALID_SIGNATURE: 403,
+	BLOCKED: 403,
+	PRIVATE_CONTENT: 403,
+	NOT_FOUND: 404,
+	FORMAT_UNAVAILABLE: 409,
+	LIVE_CONTENT: 409,
+	JOB_NOT_READY: 409,
+	PAYLOAD_TOO_LARGE: 413,
+	RANGE_NOT_SATISFIABLE: 416,
+	RATE_LIMITED: 429,
+	GEO_BLOCKED: 451,
+	BLOCKED_CONTENT: 451,
+	EXTRACTION_FAILED: 500,
+	INTERNAL: 500,
+	YTDLP_FAILED: 502,
+	NO_METADATA: 502,
+	YTDLP_UNAVAILABLE: 503,
+	YTDLP_OUTDATED: 503,
+	SERVER_BUSY: 503,
+	TIMEOUT: 504,
+} as const satisfies Record<ErrorCode, number>;
+
+/** Respond with the standard `{success:false, error, code}` body and the code's status. */
+export function errorResponse(
+	c: Context,
+	code: ErrorCode,
+	error: string,
+	{
+		field,
+		debug,
+		position,
+		character,
+		retryAfter,
+	}: Pick<ErrorResponse, "field" | "debug" | "position" | "character" | "retryAfter"> = {},
+) {
+	const body: ErrorResponse = { success: false, error, code };
+	if (field) body.field = field;
+	if (debug) body.debug = debug;
+	if (position !== undefined) body.position = position;
+	if (character !== undefined) body.character = character;
+	if (retryAfter !== undefined) body.retryAfter = retryAfter;
+	return c.json(body, ERROR_STATUS[code]);
+}
+
+/** An error that carries its own code, such as `YtDlpError` or `ShortLinkError`. */
+interface CodedError extends Error {
+	code: ErrorCode;
+	/** Raw process output; never sent to clients unless `DEBUG_ERRORS=1`. */
+	stderr?: string;
+}
+
+function isCodedError(error: unknown): error is CodedError {
+	const code = error instanceof Error ? (error as Partial<CodedError>).code : undefined;
+	return typeof code === "string" && code in ERROR_STATUS;
+}
+
+/** The code a failure will be reported under; uncoded errors are `EXTRACTION_FAILED`. */
+export function errorCodeOf(error: unknown): ErrorCode {
+	return isCodedError(error) ? error.code : "EXTRACTION_FAILED";
+}
+
+export interface Failure {
+	code: ErrorCode;
+	message: string;
+	debug?: string;
+}
+
+/**
+ * Code and client-safe message for a failed resolve or download. Raw yt-dlp
+ * stderr is logged and only echoed back as `debug` when `DEBUG_ERRORS=1`. A
+ * `YTDLP_OUTDATED` failure also gets an operator warning here, once per
+ * failure reported, rather than per attempt.
+ */
+export function describeFailure(error: unknown, fallbackMessage: string): Failure {
+	const message = error instanceof Error ? error.message : fallbackMessage;
+	if (!isCodedError(error)) return { code: "EXTRACTION_FAILED", message };
+
+	const { code, stderr } = error;
+	// Every extraction on the affected platforms fails until someone acts.
+	if (code === "YTDLP_OUTDATED") {
+		logger.error(
+			{ code, stderr },
+			"yt-dlp is outdated; update it (yt-dlp -U, or rebuild the image) to restore extraction",
+		);
+	}
+	if (!stderr) return { code, message };
+	logger.error({ code, stderr }, "yt-dlp failed");
+	return process.env.DEBUG_ERRORS === "1" ? { code, message, debug: stderr } : { code, message };
+}
@@ -0,0 +1,24 @@
+/** Kept by every filter so clients can always tell a picker from an error. */
+const ALWAYS_INCLUDED = ["status"];
+
+/** Split a comma-separated `fields` value; `undefined` means "no filtering". */
+export function parseFieldList(raw: string | undefined): string[] | undefined {
+	const fields = raw
+		?.split(",")
+		.map((field) => field.trim())
+		.filter(Boolean);
+	return fields?.length ? fields : undefined;
+}
+
+/**
+ * Keep only the requested top-level keys of a response. Unknown names are
+ * ignored rather than rejected so clients can ask for fields newer servers add.
+ */
+export function pickFields<T extends object>(
+	value: T,
+	fields: readonly string[] | undefined,
+): Partial<T> {
+	if (!fields) return value;
+	const keep = new Set([...ALWAYS_INCLUDED, ...fields]);
+	return Object.fromEntries(Object.entries(value).filter(([key]) => keep.has(key))) as Partial<T>;
+}
@@ -0,0 +1,63 @@
+import { logger } from "./logger";
+
+/** What a `FILENAME_TEMPLATE` placeholder can stand for. */
+export interface FilenameFields {
+	title: string;
+	uploader?: string;
+	id?: string;
+	ext: string;
+	platform?: string;
+}
+
+const PLACEHOLDERS: readonly string[] = ["title", "uploader", "id", "ext", "platform"];
+
+export const DEFAULT_FILENAME_TEMPLATE = "{title}.{ext}";
+
+/** Path separators, quotes and characters Windows refuses, plus control characters. */
+const UNSAFE_CHARS = /[\\/:*?"<>|\p{Cc}]/gu;
+
+let checked: { raw: string; template: string } | undefined;
+
+/**
+ * `FILENAME_TEMPLATE`, e.g. `{uploader} - {title}.{ext}`. Only the
+ * `{title}`, `{uploader}`, `{id}`, `{ext}` and `{platform}` placeholders
+ * exist; yt-dlp's own `-o` syntax is never exposed. A template with an
+ * unknown placeholder or a stray brace is logged once and replaced by
+ * {@link DEFAULT_FILENAME_TEMPLATE}.
+ */
+export function filenameTemplate(): string {
+	const raw = process.env.FILENAME_TEMPLATE?.trim() ?? "";
+	if (checked?.raw === raw) return checked.template;
+
+	let template = raw || DEFAULT_FILENAME_TEMPLATE;
+	const problem = templateProblem(template);
+	if (problem) {
+		logger.warn({ template, problem }, "Ignoring invalid FILENAME_TEMPLATE");
+		template = DEFAULT_FILENAME_TEMPLATE;
+	}
+	checked = { raw, template };
+	return template;
+}
+
+function templateProblem(template: string): string | undefined {
+	const unknown = [...template.matchAll(/\{([^{}]*)\}/g)].find(
+		([, name]) => !PLACEHOLDERS.includes(name),
+	);
+	if (unknown) return `unknown placeholder ${unknown[0]}`;
+	if (/[{}]/.test(template.replace(/\{[^{}]*\}/g, ""))) return "unbalanced brace";
+	return undefined;
+}
+
+/**
+ * A download filename from {@link filenameTemplate}. Every value is stripped
+ * of path separators and control characters before it is spliced in, and a
+ * missing one renders as empty; the result falls back to `media.<ext>`.
+ */
+export function renderFilename(fields: FilenameFields): string {
+	const values: Record<string, string | undefined> = { ...fields };
+	const name = filenameTemplate()
+		.replace(/\{(\w+)\}/g, (_, key: string) => (values[key] ?? "").replace(UNSAFE_CHARS, ""))
+		.replace(/\s+/g, " ")
+		.trim();
+	return name && !name.startsWith(".") ? name : `media.${fields.ext}`;
+}
@@ -0,0 +1,186 @@
+import { randomUUID } from "node:crypto";
+import fs from "node:fs/promises";
+import path from "node:path";
+import type { DownloadJobStatus } from "@snatch/shared";
+import { positiveInt } from "./env";
+import { describeFailure, ERROR_STATUS, type Failure } from "./errors";
+import { logger } from "./logger";
+
+/** A finished job's file; the caller owns it once {@link JobStore.take} hands it over. */
+export interface JobOutput {
+	filePath: string;
+	cleanup: () => Promise<void>;
+}
+
+export interface Job {
+	id: string;
+	status: DownloadJobStatus;
+	/** 0-100 while running, as last reported by yt-dlp. */
+	progress: number;
+	/** Requested name, else the file yt-dlp wrote once `done`; may be empty while running. */
+	filename: string;
+	contentType: string;
+	expiresAt: number;
+	/** Bytes written so far while running, as last reported by yt-dlp. */
+	written?: number;
+	/** Bytes of the finished file. */
+	size?: number;
+	error?: Failure;
+	output?: JobOutput;
+	controller: AbortController;
+}
+
+export type JobRunner = (
+	signal: AbortSignal,
+	onProgress: (percent: number, bytes?: number) => void,
+) => Promise<JobOutput>;
+
+/** Refused because the store is at its job or disk budget. */
+export class JobCapacityError extends Error {
+	readonly code = "SERVER_BUSY";
+	readonly status = ERROR_STATUS.SERVER_BUSY;
+}
+
+export interface JobStoreOptions {
+	/** Jobs allowed to run at once. */
+	maxRunning: number;
+	/**
+	 * Bytes of job files allowed on disk, unretrieved or still being written.
+	 * New jobs are refused at it, and a running job that pushes past it is stopped.
+	 */
+	maxBytes: number;
+	/** Lifetime of a job from creation; running jobs are cancelled and files removed after it. */
+	ttlMs: number;
+	now?: () => number;
+}
+
+/**
+ * In-memory registry of background downloads for clients that poll instead
+ * of holding a streaming connection. Jobs move `running` → `done` | `failed`
+ * and leave the store when their file is taken or their TTL passes; expiry is
+ * swept opportunistically on every call, so no timer keeps the process alive.
+ */
+export class JobStore {
+	private readonly jobs = new Map<string, Job>();
+	private readonly options: JobStoreOptions;
+	private readonly now: () => number;
+
+	constructor(options: JobStoreOptions) {
+		this.options = options;
+		this.now = options.now ?? Date.now;
+	}
+
+	/**
+	 * Start `run` in the background; throws {@link JobCapacityError} when over
+	 * budget. `onSettled` sees the job once it is `done` or `failed`, unless it
+	 * expired first.
+	 */
+	start(
+		meta: { filename: string; contentType: string },
+		run: JobRunner,
+		onSettled?: (job: Job) => void,
+	): Job {
+		this.sweep();
+		const running = [...this.jobs.values()].filter((job) => job.status === "running").length;
+		if (running >= this.options.maxRunning) {
+			throw new JobCapacityError("Too many downloads in progress; try again shortly");
+		}
+		if (this.bytesInUse() >= this.options.maxBytes) {
+			throw new JobCapacityError("Download storage is full; try again shortly");
+		}
+
+		const job: Job = {
+			id: randomUUID(),
+			status: "running",
+			progress: 0,
+			...meta,
+			expiresAt: this.now() + this.options.ttlMs,
+			controller: new AbortController(),
+		};
+		this.jobs.set(job.id, job);
+		void this.execute(job, run).then(() => {
+			if (job.status !== "running" && this.jobs.get(job.id) === job) onSettled?.(job);
+		});
+		return job;
+	}
+
+	get(id: string): Job | undefined {
+		this.sweep();
+		return this.jobs.get(id);
+	}
+
+	/** Remove a finished job and hand its file to the caller, who must clean it up. */
+	take(id: string): Job | undefined {
+		const job = this.get(id);
+		if (job?.status !== "done") return undefined;
+		this.jobs.delete(id);
+		return job;
+	}
+
+	/** Cancel and forget jobs past their TTL, deleting any files they left. */
+	sweep(): void {
+		const now = this.now();
+		for (const job of this.jobs.values()) {
+			if (job.expiresAt > now) continue;
+			this.jobs.delete(job.id);
+			job.controller.abort();
+			void job.output?.cleanup();
+		}
+	}
+
+	/** Finished files plus what running jobs have written so far. */
+	private bytesInUse(): number {
+		let total = 0;
+		for (const job of this.jobs.values()) total += job.size ?? job.written ?? 0;
+		return total;
+	}
+
+	private async execute(job: Job, run: JobRunner): Promise<void> {
+		try {
+			const output = await run(job.controller.signal, (percent, bytes) => {
+				job.progress = Math.max(job.progress, Math.min(percent, 100));
+				if (bytes === undefined) return;
+				job.written = bytes;
+				if (this.bytesInUse() > this.options.maxBytes) {
+					const full = "Download storage is full; the download was stopped";
+					job.controller.abort(new JobCapacityError(full));
+				}
+			});
+			// Expired while yt-dlp was finishing; nobody can retrieve the file.
+			if (this.jobs.get(job.id) !== job) {
+				await output.cleanup();
+				return;
+			}
+			job.size = (await fs.stat(output.filePath)).size;
+			job.output = output;
+			job.filename ||= path.basename(output.filePath);
+			job.progress = 100;
+			job.status = "done";
+		} catch (error) {
+			// A job stopped at the disk budget fails with that, not with the abort it caused.
+			const { aborted, reason } = job.controller.signal;
+			const failure = reason instanceof JobCapacityError ? reason : error;
+			job.error = describeFailure(failure, "Download execution failed");
+			job.status = "failed";
+			if (!aborted || failure !== error) {
+				logger.warn({ err: failure, jobId: job.id }, "background download failed");
+			}
+		}
+	}
+}
+
+const DEFAULT_MAX_RUNNING_JOBS = 4;
+const DEFAULT_MAX_JOB_BYTES = 2 * 1024 ** 3;
+const JOB_TTL_MS = 15 * 60 * 1000;
+
+let store: JobStore | undefined;
+
+/** The process-wide job store, sized by `ASYNC_JOBS_MAX` and `ASYNC_JOBS_MAX_BYTES`. */
+export function downloadJobs(): JobStore {
+	store ??= new JobStore({
+		maxRunning: positiveInt(process.env.ASYNC_JOBS_MAX) ?? DEFAULT_MAX_RUNNING_JOBS,
+		maxBytes: positiveInt(process.env.ASYNC_JOBS_MAX_BYTES) ?? DEFAULT_MAX_JOB_BYTES,
+		ttlMs: JOB_TTL_MS,
+	});
+	return store;
+}
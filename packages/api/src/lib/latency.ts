@@ -0,0 +1,56 @@
+/** Weight of the newest sample in the moving average. */
+const EMA_ALPHA = 0.2;
+/**
+ * A sample below this fraction of the current average counts as that
+ * fraction, so one fast outlier (a warm CDN, a tiny clip) cannot drag the
+ * timeout under what the platform usually needs.
+ */
+const MAX_DROP_RATIO = 0.5;
+/** Successful probes per platform before the average replaces the static default. */
+export const MIN_LATENCY_SAMPLES = 5;
+/** Headroom over the average a probe gets before it is timed out. */
+export const LATENCY_TIMEOUT_FACTOR = 4;
+
+interface Latency {
+	emaMs: number;
+	samples: number;
+}
+
+/** Per-platform probe latency. Per-process, like the rate limiter. */
+const latencies = new Map<string, Latency>();
+
+/** Forget every recorded latency. */
+export function clearLatencies(): void {
+	latencies.clear();
+}
+
+/** The moving average after `sampleMs`; the first sample seeds it. */
+export function nextEma(emaMs: number | undefined, sampleMs: number): number {
+	if (emaMs === undefined) return sampleMs;
+	const sample = Math.max(sampleMs, emaMs * MAX_DROP_RATIO);
+	return emaMs + EMA_ALPHA * (sample - emaMs);
+}
+
+/** Fold a successful probe's duration into `platform`'s average. */
+export function recordLatency(platform: string, durationMs: number): void {
+	const previous = latencies.get(platform);
+	latencies.set(platform, {
+		emaMs: nextEma(previous?.emaMs, durationMs),
+		samples: (previous?.samples ?? 0) + 1,
+	});
+}
+
+/**
+ * {@link LATENCY_TIMEOUT_FACTOR} times `platform`'s average probe latency in
+ * milliseconds, clamped to `minMs..maxMs`, or undefined until it has
+ * {@link MIN_LATENCY_SAMPLES} successful probes behind it.
+ */
+export function adaptiveTimeoutMs(
+	platform: string,
+	minMs: number,
+	maxMs: number,
+): number | undefined {
+	const latency = latencies.get(platform);
+	if (!latency || latency.samples < MIN_LATENCY_SAMPLES) return undefined;
+	return Math.min(Math.max(latency.emaMs * LATENCY_TIMEOUT_FACTOR, minMs), maxMs);
+}
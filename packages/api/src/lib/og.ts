@@ -0,0 +1,109 @@
+import { logger } from "./logger";
+import { safeFetch } from "./outbound";
+import { validatePlatformUrl } from "./platforms";
+import type { Fetcher } from "./shortlink";
+
+const PAGE_TIMEOUT_MS = 5_000;
+/** Open Graph tags sit in `<head>`; never scan more of a page than this. */
+const MAX_PAGE_BYTES = 512 * 1024;
+const DESKTOP_UA =
+	"Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36";
+
+export interface OgMedia {
+	videoUrl: string;
+	title?: string;
+	thumbnail?: string;
+}
+
+/** Whether the Open Graph fallback may run; on unless `OG_FALLBACK=false`. */
+export function ogFallbackEnabled(): boolean {
+	return process.env.OG_FALLBACK !== "false";
+}
+
+const META_TAG_REGEX = /<meta\s[^>]*>/gi;
+const ATTR_REGEX = /([\w:-]+)\s*=\s*(?:"([^"]*)"|'([^']*)')/g;
+
+function decodeEntities(value: string): string {
+	return value
+		.replace(/&quot;/g, '"')
+		.replace(/&#39;|&#x27;/g, "'")
+		.replace(/&lt;/g, "<")
+		.replace(/&gt;/g, ">")
+		.replace(/&amp;/g, "&");
+}
+
+/** Collect `og:*` meta tags, keeping the first value of each property. */
+export function parseOgTags(html: string): Record<string, string> {
+	const tags: Record<string, string> = {};
+	for (const [tag] of html.matchAll(META_TAG_REGEX)) {
+		const attrs: Record<string, string> = {};
+		for (const [, name, double, single] of tag.matchAll(ATTR_REGEX)) {
+			attrs[name.toLowerCase()] = decodeEntities(double ?? single ?? "");
+		}
+		const property = attrs.property ?? attrs.name;
+		if (property?.startsWith("og:") && attrs.content && !(property in tags)) {
+			tags[property] = attrs.content;
+		}
+	}
+	return tags;
+}
+
+/**
+ * The first `maxBytes` of `res`'s body as text. Reading stops there and the
+ * rest of the body is cancelled, so an oversized page is never buffered whole.
+ */
+async function readPrefix(res: Response, maxBytes: number): Promise<string> {
+	if (!res.body) return "";
+	const reader = res.body.getReader();
+	const chunks: Uint8Array[] = [];
+	let size = 0;
+	try {
+		while (size < maxBytes) {
+			const { done, value } = await reader.read();
+			if (done) break;
+			const chunk = value.subarray(0, maxBytes - size);
+			chunks.push(chunk);
+			size += chunk.byteLength;
+		}
+	} finally {
+		// A no-op once the body is drained; otherwise it drops the connection.
+		await reader.cancel().catch(() => {});
+	}
+	return new TextDecoder().decode(Buffer.concat(chunks));
+}
+
+interface OgFallbackOptions {
+	fetcher?: Fetcher;
+	signal?: AbortSignal;
+}
+
+/**
+ * Degraded extraction for when yt-dlp's extractor breaks after a platform
+ * change: read the page's Open Graph video tags directly. Only allowlisted
+ * pages on public addresses are fetched, redirects are not followed, and any
+ * failure yields `null` so the caller reports the original yt-dlp error instead.
+ */
+export async function ogFallback(
+	url: string,
+	{ fetcher = safeFetch, signal }: OgFallbackOptions = {},
+): Promise<OgMedia | null> {
+	if (!validatePlatformUrl(url).valid) return null;
+	try {
+		const timeout = AbortSignal.timeout(PAGE_TIMEOUT_MS);
+		const res = await fetcher(url, {
+			redirect: "manual",
+			headers: { "User-Agent": DESKTOP_UA, Accept: "text/html" },
+			signal: signal ? AbortSignal.any([signal, timeout]) : timeout,
+		});
+		if (!res.ok) return null;
+		const html = await readPrefix(res, MAX_PAGE_BYTES);
+		const tags = parseOgTags(html);
+		const videoUrl = tags["og:video:secure_url"] ?? tags["og:video"];
+		if (!videoUrl?.startsWith("https://")) return null;
+		return { videoUrl, title: tags["og:title"], thumbnail: tags["og:image"] };
+	} catch (error) {
+		if (signal?.aborted) throw error;
+		logger.warn({ err: error, url }, "Open Graph fallback failed");
+		return null;
+	}
+}
@@ -0,0 +1,55 @@
+import { lookup } from "node:dns/promises";
+import { isPrivateAddress, isSafeOutboundHost } from "@snatch/shared";
+import { ERROR_STATUS } from "./errors";
+import type { Fetcher } from "./shortlink";
+
+/** Every address `host` resolves to. */
+export type Resolver = (host: string) => Promise<string[]>;
+
+/** A request the server refused to make because its target is internal. */
+export class OutboundBlockedError extends Error {
+	readonly code = "INVALID_URL";
+	readonly status = ERROR_STATUS.INVALID_URL;
+}
+
+/** Whether `ALLOW_PRIVATE_OUTBOUND=true` lifts the checks, e.g. to test against a local server. */
+export function allowPrivateOutbound(): boolean {
+	return process.env.ALLOW_PRIVATE_OUTBOUND === "true";
+}
+
+async function resolveAddresses(host: string): Promise<string[]> {
+	const records = await lookup(host, { all: true });
+	return records.map((record) => record.address);
+}
+
+/**
+ * Throw {@link OutboundBlockedError} unless the server may fetch `url`: its
+ * host must pass `isSafeOutboundHost` and resolve to public addresses only.
+ * A name that does not resolve is let through; the fetch fails on its own.
+ */
+export async function assertSafeOutbound(
+	url: string,
+	resolve: Resolver = resolveAddresses,
+): Promise<void> {
+	if (allowPrivateOutbound()) return;
+	const host = new URL(url).hostname.replace(/^\[|\]$/g, "");
+	if (!isSafeOutboundHost(host)) {
+		throw new OutboundBlockedError(`Refusing to fetch internal host '${host}'`);
+	}
+
+	let addresses: string[];
+	try {
+		addresses = await resolve(host);
+	} catch {
+		return;
+	}
+	if (addresses.some(isPrivateAddress)) {
+		throw new OutboundBlockedError(`Refusing to fetch '${host}': it resolves to a private address`);
+	}
+}
+
+/** `fetch` behind {@link assertSafeOutbound}; the default for every user-driven request. */
+export const safeFetch: Fetcher = async (url, init) => {
+	await assertSafeOutbound(url);
+	return fetch(url, init);
+};
@@ -0,0 +1,82 @@
+import {
+	ALLOWED_PLATFORM_DOMAINS,
+	canonicalUrl,
+	extractVideoId,
+	PLATFORM_HOSTS,
+	type UrlValidation,
+	validateUrl,
+} from "@snatch/shared";
+import { logger } from "./logger";
+
+/** The `SUPPORTED_PLATFORMS` entry that stands for the built-in host list. */
+const DEFAULT_ENTRY = "default";
+
+/** Dot-separated LDH labels ending in an alphabetic (or punycode) TLD. */
+const DOMAIN_REGEX = /^(?:[a-z0-9](?:[a-z0-9-]{0,61}[a-z0-9])?\.)+[a-z][a-z0-9-]{1,62}$/;
+
+let compiled: { raw: string; domains: readonly string[] } | undefined;
+
+/** Whether `ENABLE_YOUTUBE_SHORTS=true` adds the YouTube hosts, for Shorts only. */
+export function youtubeShortsEnabled(): boolean {
+	return process.env.ENABLE_YOUTUBE_SHORTS === "true";
+}
+
+/**
+ * The host allowlist: the built-in `SERVICES` hosts, unless `SUPPORTED_PLATFORMS`
+ * lists comma-separated domains to use instead. Including `default` in the list
+ * extends the built-in hosts rather than replacing them. Subdomains match like
+ * the built-ins do. The YouTube hosts are added on top when
+ * {@link youtubeShortsEnabled}; `validateUrl` still admits Shorts only. Throws
+ * on an entry that is not a bare domain; startup calls this first so a typo
+ * fails the boot.
+ */
+export function platformDomains(): readonly string[] {
+	const shorts = youtubeShortsEnabled();
+	const raw = process.env.SUPPORTED_PLATFORMS?.trim() ?? "";
+	const key = `${shorts}|${raw}`;
+	if (compiled?.raw === key) return compiled.domains;
+	if (!raw && !shorts) return ALLOWED_PLATFORM_DOMAINS;
+
+	const domains = new Set<string>();
+	for (const entry of raw.split(",")) {
+		const domain = entry.trim().toLowerCase();
+		if (!domain) continue;
+		if (domain === DEFAULT_ENTRY) {
+			for (const host of ALLOWED_PLATFORM_DOMAINS) domains.add(host);
+		} else if (DOMAIN_REGEX.test(domain)) {
+			domains.add(domain);
+		} else {
+			throw new Error(`SUPPORTED_PLATFORMS entry "${entry.trim()}" is not a domain name`);
+		}
+	}
+	if (raw && domains.size === 0) throw new Error("SUPPORTED_PLATFORMS lists no domains");
+	if (!raw) for (const host of ALLOWED_PLATFORM_DOMAINS) domains.add(host);
+	if (shorts) for (const host of PLATFORM_HOSTS.youtube) domains.add(host);
+	compiled = { raw: key, domains: [...domains] };
+	return compiled.domains;
+}
+
+/** `validateUrl` against {@link platformDomains}; every host check in the API goes through it. */
+export function validatePlatformUrl(url: string): UrlValidation {
+	return validateUrl(url, platformDomains());
+}
+
+/**
+ * `canonicalUrl`, the spelling every probe, cache key and signature uses.
+ * Logs the original next to the cleaned URL at debug level when they differ.
+ */
+export function canonicalMediaUrl(url: string): string {
+	const canonical = canonicalUrl(url);
+	if (canonical !== url) logger.debug({ original: url, canonical }, "URL canonicalized");
+	return canonical;
+}
+
+/**
+ * Key for per-post state such as the refresh cooldown: `platform:id` when
+ * `extractVideoId` finds one, which outlasts every spelling of the URL, else
+ * the canonical URL itself.
+ */
+export function mediaKey(url: string): string {
+	const videoId = extractVideoId(url);
+	return videoId ? `${videoId.platform}:${videoId.id}` : url;
+}
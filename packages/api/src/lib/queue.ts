@@ -0,0 +1,126 @@
+import { positiveInt } from "./env";
+import { ERROR_STATUS } from "./errors";
+
+/** Refused because the queue is full, or the wait for a slot ran out. */
+export class QueueBusyError extends Error {
+	readonly code = "SERVER_BUSY";
+	readonly status = ERROR_STATUS.SERVER_BUSY;
+}
+
+export interface WorkQueueOptions {
+	/** Tasks allowed to run at once. */
+	concurrency: number;
+	/** Tasks allowed to wait for a slot; one more is refused straight away. */
+	maxDepth: number;
+	/** How long a task may wait for a slot before it is refused. */
+	maxWaitMs: number;
+}
+
+/**
+ * A bounded FIFO line in front of a fixed number of slots. Bursts wait their
+ * turn instead of failing outright, trading latency for success rate; only a
+ * full line or a wait past `maxWaitMs` is refused with {@link QueueBusyError}.
+ */
+export class WorkQueue {
+	private readonly options: WorkQueueOptions;
+	private readonly waiting: (() => void)[] = [];
+	private active = 0;
+
+	constructor(options: WorkQueueOptions) {
+		this.options = options;
+	}
+
+	/** Tasks holding a slot. */
+	get running(): number {
+		return this.active;
+	}
+
+	/** Tasks waiting for a slot. */
+	get length(): number {
+		return this.waiting.length;
+	}
+
+	/**
+	 * Run `task` once a slot is free, in arrival order. Aborting `signal` while
+	 * waiting leaves the line and rejects with the signal's reason.
+	 */
+	async run<T>(task: () => Promise<T>, signal?: AbortSignal): Promise<T> {
+		await this.acquire(signal);
+		try {
+			return await task();
+		} finally {
+			this.release();
+		}
+	}
+
+	private acquire(signal?: AbortSignal): Promise<void> {
+		if (signal?.aborted) return Promise.reject(signal.reason);
+		if (this.active < this.options.concurrency && this.waiting.length === 0) {
+			this.active++;
+			return Promise.resolve();
+		}
+		if (this.waiting.length >= this.options.maxDepth) {
+			return Promise.reject(new QueueBusyError("Too many extractions queued; try again shortly"));
+		}
+		return new Promise((resolve, reject) => {
+			const settle = () => {
+				clearTimeout(timer);
+				signal?.removeEventListener("abort", onAbort);
+			};
+			const grant = () => {
+				settle();
+				resolve();
+			};
+			const leave = (reason: unknown) => {
+				settle();
+				this.waiting.splice(this.waiting.indexOf(grant), 1);
+				reject(reason);
+			};
+			const onAbort = () => leave(signal?.reason);
+			const timer = setTimeout(
+				() => leave(new QueueBusyError("Server is busy; no extraction slot freed up in time")),
+				this.options.maxWaitMs,
+			);
+			signal?.addEventListener("abort", onAbort, { once: true });
+			this.waiting.push(grant);
+		});
+	}
+
+	/** Hand the slot straight to the next waiter, if any, so nobody can cut in. */
+	private release(): void {
+		const next = this.waiting.shift();
+		if (next) next();
+		else this.active--;
+	}
+}
+
+const DEFAULT_QUEUE_DEPTH = 50;
+const DEFAULT_QUEUE_MAX_WAIT_MS = 10_000;
+
+let queue: { concurrency: string | undefined; instance: WorkQueue | undefined } | undefined;
+
+/**
+ * The process-wide yt-dlp extraction queue, sized by `EXTRACT_CONCURRENCY`,
+ * `EXTRACT_QUEUE_DEPTH` and `EXTRACT_QUEUE_MAX_WAIT_MS`; undefined (no limit)
+ * while `EXTRACT_CONCURRENCY` is unset.
+ */
+export function extractionQueue(): WorkQueue | undefined {
+	const raw = process.env.EXTRACT_CONCURRENCY;
+	if (queue?.concurrency === raw) return queue.instance;
+	const concurrency = positiveInt(raw);
+	const instance = concurrency
+		? new WorkQueue({
+				concurrency,
+				maxDepth: positiveInt(process.env.EXTRACT_QUEUE_DEPTH) ?? DEFAULT_QUEUE_DEPTH,
+				maxWaitMs: positiveInt(process.env.EXTRACT_QUEUE_MAX_WAIT_MS) ?? DEFAULT_QUEUE_MAX_WAIT_MS,
+			})
+		: undefined;
+	queue = { concurrency: raw, instance };
+	return instance;
+}
+
+/** `task` through {@link extractionQueue}, or straight away when queueing is off. */
+export function runQueued<T>(task: () => Promise<T>, signal?: AbortSignal): Promise<T> {
+	const instance = extractionQueue();
+	return instance ? instance.run(task, signal) : task();
+}
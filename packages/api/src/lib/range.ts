@@ -0,0 +1,38 @@
+export interface ByteRange {
+	start: number;
+	/** Inclusive, as in `Content-Range`. */
+	end: number;
+}
+
+const RANGE_REGEX = /^bytes=(\d*)-(\d*)$/;
+
+/**
+ * Parse a single-range `Range` header against a file of `size` bytes.
+ *
+ * Returns `null` when the header should be ignored (absent, malformed, or a
+ * multi-range request — RFC 9110 lets us answer those with the full body) and
+ * `"unsatisfiable"` when it is well-formed but lies outside the file (→ 416).
+ */
+export function parseRange(
+	header: string | undefined,
+	size: number,
+): ByteRange | "unsatisfiable" | null {
+	const match = header ? RANGE_REGEX.exec(header.trim()) : null;
+	if (!match) return null;
+
+	const [, rawStart, rawEnd] = match;
+	if (!rawStart && !rawEnd) return null;
+
+	if (!rawStart) {
+		// Suffix range: the last N bytes.
+		const length = Number.parseInt(rawEnd, 10);
+		if (length === 0 || size === 0) return "unsatisfiable";
+		return { start: Math.max(0, size - length), end: size - 1 };
+	}
+
+	const start = Number.parseInt(rawStart, 10);
+	const end = rawEnd ? Number.parseInt(rawEnd, 10) : size - 1;
+	if (end < start) return null;
+	if (start >= size) return "unsatisfiable";
+	return { start, end: Math.min(end, size - 1) };
+}
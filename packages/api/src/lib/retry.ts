@@ -0,0 +1,135 @@
+import { positiveInt } from "./env";
+
+/** An attempt budget and the exponential delays between those attempts. */
+export interface Backoff {
+	/** Total attempts, including the first one. */
+	attempts: number;
+	/** Delay before the second attempt. */
+	initialDelayMs: number;
+	/** Factor each further delay grows by. Defaults to 2. */
+	multiplier?: number;
+	/** Upper bound on any single delay. Defaults to uncapped. */
+	maxDelayMs?: number;
+	/**
+	 * A fraction adds up to that much of the delay at random (0.5 → up to +50%);
+	 * `"full"` draws the whole delay from 0 up to it, so concurrent callers that
+	 * failed together spread out instead of retrying in bursts. Defaults to 0.
+	 */
+	jitter?: number | "full";
+}
+
+/**
+ * The schedule for retrying a flaky upstream: up to 500ms, 1s, then 2s, each
+ * fully jittered. Callers spread it and override what differs.
+ */
+export const DEFAULT_BACKOFF: Backoff = {
+	attempts: 3,
+	initialDelayMs: 500,
+	multiplier: 2,
+	jitter: "full",
+};
+
+/** A {@link Backoff} plus a cap on the time spent across all its attempts. */
+export interface RetryPolicy extends Backoff {
+	/**
+	 * Give up instead of retrying when the next attempt would start more than
+	 * this long after the first one. Defaults to no deadline.
+	 */
+	deadlineMs?: number;
+}
+
+/** Upper bound on `RETRY_MAX_ATTEMPTS`, so a typo cannot pin a request for minutes. */
+const MAX_RETRY_ATTEMPTS = 10;
+
+/** A positive integer from env var `name`, or `undefined` when unset. Throws on anything else. */
+function policyInt(name: string): number | undefined {
+	const raw = process.env[name]?.trim();
+	if (!raw) return undefined;
+	const value = positiveInt(raw);
+	if (!value || String(value) !== raw) {
+		throw new Error(`${name} must be a positive integer, got "${raw}"`);
+	}
+	return value;
+}
+
+/**
+ * The probe retry policy: {@link DEFAULT_BACKOFF} with `RETRY_MAX_ATTEMPTS`,
+ * `RETRY_INITIAL_MS`, `RETRY_MAX_DELAY_MS` and `RETRY_DEADLINE_MS` applied
+ * where set, so a platform's rate-limited spell can be ridden out without a
+ * redeploy. Throws on a malformed value; checked at startup.
+ */
+export function retryPolicy(): RetryPolicy {
+	const attempts = policyInt("RETRY_MAX_ATTEMPTS") ?? DEFAULT_BACKOFF.attempts;
+	if (attempts > MAX_RETRY_ATTEMPTS) {
+		throw new Error(`RETRY_MAX_ATTEMPTS must be at most ${MAX_RETRY_ATTEMPTS}, got ${attempts}`);
+	}
+	const initialDelayMs = policyInt("RETRY_INITIAL_MS") ?? DEFAULT_BACKOFF.initialDelayMs;
+	const maxDelayMs = policyInt("RETRY_MAX_DELAY_MS");
+	if (maxDelayMs !== undefined && maxDelayMs < initialDelayMs) {
+		throw new Error(`RETRY_MAX_DELAY_MS must be at least ${initialDelayMs}, got ${maxDelayMs}`);
+	}
+	const deadlineMs = policyInt("RETRY_DEADLINE_MS");
+	return {
+		...DEFAULT_BACKOFF,
+		attempts,
+		initialDelayMs,
+		...(maxDelayMs ? { maxDelayMs } : {}),
+		...(deadlineMs ? { deadlineMs } : {}),
+	};
+}
+
+export interface RetryOptions extends RetryPolicy {
+	/** Return false to rethrow immediately instead of retrying. Defaults to always retry. */
+	shouldRetry?: (error: unknown) => boolean;
+	/** A different schedule for particular errors; `undefined` keeps the default one. */
+	backoffFor?: (error: unknown) => Backoff | undefined;
+	onRetry?: (error: unknown, attempt: number, delayMs: number) => void;
+	/** What to throw when `deadlineMs` stops the retries. Defaults to the last error. */
+	onDeadline?: (error: unknown) => unknown;
+	/** Defaults to `Bun.sleep`; tests swap it to observe delays without waiting. */
+	sleep?: (ms: number) => Promise<unknown>;
+	/** Source of jitter in [0, 1). Defaults to `Math.random`; tests pin it. */
+	random?: () => number;
+	/** Clock `deadlineMs` is measured on. Defaults to `Date.now`; tests advance it from `sleep`. */
+	now?: () => number;
+}
+
+/** The delay after failed attempt `attempt` (1-based) under `backoff`. */
+export function backoffDelay(backoff: Backoff, attempt: number, random = Math.random): number {
+	const base = backoff.initialDelayMs * (backoff.multiplier ?? 2) ** (attempt - 1);
+	const { jitter = 0 } = backoff;
+	const jittered = jitter === "full" ? random() * base : base + random() * base * jitter;
+	return Math.min(jittered, backoff.maxDelayMs ?? Number.POSITIVE_INFINITY);
+}
+
+/**
+ * Run `fn` until it resolves or the attempt budget is spent, sleeping with
+ * exponential backoff between attempts. Each failure is checked against
+ * `backoffFor`, so one class of error can get a slower, shorter schedule than
+ * the rest. A retry that would start past `deadlineMs` is not made. The last
+ * error is rethrown as-is so callers keep their own error messages.
+ */
+export async function retryWithBackoff<T>(
+	fn: (attempt: number) => Promise<T>,
+	options: RetryOptions,
+): Promise<T> {
+	const sleep = options.sleep ?? Bun.sleep;
+	const now = options.now ?? Date.now;
+	const started = now();
+	for (let attempt = 1; ; attempt++) {
+		try {
+			return await fn(attempt);
+		} catch (error) {
+			const backoff = options.backoffFor?.(error) ?? options;
+			const retryable = options.shouldRetry?.(error) ?? true;
+			if (attempt >= backoff.attempts || !retryable) throw error;
+			const delayMs = backoffDelay(backoff, attempt, options.random);
+			const { deadlineMs } = options;
+			if (deadlineMs !== undefined && now() - started + delayMs > deadlineMs) {
+				throw options.onDeadline?.(error) ?? error;
+			}
+			options.onRetry?.(error, attempt, delayMs);
+			await sleep(delayMs);
+		}
+	}
+}
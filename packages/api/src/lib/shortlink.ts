@@ -0,0 +1,70 @@
+import { isShortLink, stripFragment } from "@snatch/shared";
+import { ERROR_STATUS } from "./errors";
+import { logger } from "./logger";
+import { OutboundBlockedError, safeFetch } from "./outbound";
+import { canonicalMediaUrl, validatePlatformUrl } from "./platforms";
+
+/** Redirects followed before a short link is rejected as a loop. */
+const MAX_REDIRECTS = 5;
+const HOP_TIMEOUT_MS = 5_000;
+
+export type Fetcher = (url: string, init: RequestInit) => Promise<Response>;
+
+/** A short link that redirects somewhere we refuse to follow. */
+export class ShortLinkError extends Error {
+	readonly code = "INVALID_URL";
+	readonly status = ERROR_STATUS.INVALID_URL;
+}
+
+interface ResolveShortLinkOptions {
+	fetcher?: Fetcher;
+	maxRedirects?: number;
+	signal?: AbortSignal;
+}
+
+/**
+ * Follow a share link's redirects by hand until it leaves the shortener, so
+ * the canonical URL is what gets probed and signed. Every hop must pass
+ * `validatePlatformUrl`: a redirect can never point the server at a host outside the
+ * platform allowlist, and `safeFetch` refuses hosts that resolve to private
+ * addresses. When the shortener is unreachable, an allowlisted link is handed
+ * to yt-dlp as-is; any other link is rejected.
+ */
+export async function resolveShortLink(
+	url: string,
+	{ fetcher = safeFetch, maxRedirects = MAX_REDIRECTS, signal }: ResolveShortLinkOptions = {},
+): Promise<string> {
+	let current = url;
+	for (let hops = 0; isShortLink(current); hops++) {
+		if (hops >= maxRedirects) {
+			throw new ShortLinkError(`Short link redirected more than ${maxRedirects} times`);
+		}
+
+		let res: Response;
+		try {
+			const timeout = AbortSignal.timeout(HOP_TIMEOUT_MS);
+			res = await fetcher(current, {
+				method: "HEAD",
+				redirect: "manual",
+				signal: signal ? AbortSignal.any([signal, timeout]) : timeout,
+			});
+		} catch (error) {
+			if (signal?.aborted || error instanceof OutboundBlockedError) throw error;
+			logger.warn({ err: error, url: current }, "Short link resolution failed");
+			break;
+		}
+
+		const location = res.headers.get("location");
+		if (res.status < 300 || res.status >= 400 || !location) break;
+
+		const next = stripFragment(new URL(location, current).toString());
+		if (!validatePlatformUrl(next).valid) {
+			throw new ShortLinkError("Short link redirects outside the supported platforms");
+		}
+		current = canonicalMediaUrl(next);
+	}
+	if (!validatePlatformUrl(current).valid) {
+		throw new ShortLinkError("Short link could not be resolved to a supported platform");
+	}
+	return current;
+}
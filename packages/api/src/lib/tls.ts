@@ -0,0 +1,31 @@
+import { accessSync, constants } from "node:fs";
+import type { BunFile } from "bun";
+
+/**
+ * Optional in-process TLS for operators without a reverse proxy. Both
+ * `TLS_CERT_PATH` (PEM, may hold the full chain) and `TLS_KEY_PATH` must be set
+ * together; neither means plain HTTP. Misconfiguration throws so the process
+ * refuses to start instead of silently serving HTTP.
+ */
+export function tlsConfig(
+	env: Record<string, string | undefined> = process.env,
+): { cert: BunFile; key: BunFile } | undefined {
+	const certPath = env.TLS_CERT_PATH?.trim();
+	const keyPath = env.TLS_KEY_PATH?.trim();
+	if (!certPath && !keyPath) return undefined;
+	if (!certPath || !keyPath) {
+		throw new Error("TLS_CERT_PATH and TLS_KEY_PATH must both be set to enable TLS");
+	}
+
+	for (const [name, file] of [
+		["TLS_CERT_PATH", certPath],
+		["TLS_KEY_PATH", keyPath],
+	]) {
+		try {
+			accessSync(file, constants.R_OK);
+		} catch {
+			throw new Error(`${name} is not a readable file: ${file}`);
+		}
+	}
+	return { cert: Bun.file(certPath), key: Bun.file(keyPath) };
+}
@@ -0,0 +1,107 @@
+import * as crypto from "node:crypto";
+import { isSafeOutboundHost } from "@snatch/shared";
+import { logger } from "./logger";
+import { OutboundBlockedError, safeFetch } from "./outbound";
+import { type Backoff, type RetryOptions, retryWithBackoff } from "./retry";
+import type { Fetcher } from "./shortlink";
+
+/** Header carrying `sha256=<hex HMAC-SHA256 of the body>` keyed with `WEBHOOK_SECRET`. */
+export const WEBHOOK_SIGNATURE_HEADER = "X-Snatch-Signature";
+
+const WEBHOOK_TIMEOUT_MS = 5_000;
+const WEBHOOK_BACKOFF: Backoff = { attempts: 4, initialDelayMs: 1_000, maxDelayMs: 10_000 };
+
+/** The secret webhook bodies are signed with; callbacks are refused while it is unset. */
+export function webhookSecret(): string | undefined {
+	return process.env.WEBHOOK_SECRET?.trim() || undefined;
+}
+
+/**
+ * Why `url` cannot be a job's callback, if it cannot: it must be https,
+ * carry no credentials and name a host the server may fetch. Its resolved
+ * addresses are checked again on every delivery.
+ */
+export function callbackUrlProblem(url: string): string | undefined {
+	let parsed: URL;
+	try {
+		parsed = new URL(url);
+	} catch {
+		return "callbackUrl must be an absolute https URL";
+	}
+	if (parsed.protocol !== "https:") return "callbackUrl must be an absolute https URL";
+	if (parsed.username || parsed.password) return "callbackUrl must not contain credentials";
+	if (!isSafeOutboundHost(parsed.hostname)) return "callbackUrl must point at a public host";
+	return undefined;
+}
+
+/** The {@link WEBHOOK_SIGNATURE_HEADER} value for `body`. */
+export function signWebhook(body: string, secret: string): string {
+	return `sha256=${crypto.createHmac("sha256", secret).update(body).digest("hex")}`;
+}
+
+/** A receiver answered with a non-2xx status. */
+class WebhookStatusError extends Error {
+	readonly status: number;
+
+	constructor(status: number) {
+		super(`Webhook answered ${status}`);
+		this.status = status;
+	}
+}
+
+/** Network failures and 408, 429 or 5xx answers; never a host {@link safeFetch} refused. */
+function isRetryableDelivery(error: unknown): boolean {
+	if (error instanceof OutboundBlockedError) return false;
+	if (!(error instanceof WebhookStatusError)) return true;
+	return error.status === 408 || error.status === 429 || error.status >= 500;
+}
+
+interface DeliverOptions {
+	fetcher?: Fetcher;
+	secret?: string;
+	sleep?: RetryOptions["sleep"];
+}
+
+/**
+ * POST `payload` as JSON to `url`, signed with {@link signWebhook}. Network
+ * failures, 408, 429 and 5xx answers are retried with backoff; a refused
+ * host or any other answer ends delivery. Never throws: resolves to whether
+ * the receiver accepted the payload with a 2xx.
+ */
+export async function deliverWebhook(
+	url: string,
+	payload: unknown,
+	{ fetcher = safeFetch, secret = webhookSecret(), sleep }: DeliverOptions = {},
+): Promise<boolean> {
+	if (!secret) return false;
+	const body = JSON.stringify(payload);
+	const headers = {
+		"Content-Type": "application/json",
+		[WEBHOOK_SIGNATURE_HEADER]: signWebhook(body, secret),
+	};
+	try {
+		await retryWithBackoff(
+			async () => {
+				const res = await fetcher(url, {
+					method: "POST",
+					headers,
+					body,
+					redirect: "manual",
+					signal: AbortSignal.timeout(WEBHOOK_TIMEOUT_MS),
+				});
+				if (!res.ok) throw new WebhookStatusError(res.status);
+			},
+			{
+				...WEBHOOK_BACKOFF,
+				shouldRetry: isRetryableDelivery,
+				onRetry: (error, attempt, delayMs) =>
+					logger.warn({ err: error, attempt, delayMs }, "Webhook delivery failed, retrying"),
+				sleep,
+			},
+		);
+		return true;
+	} catch (error) {
+		logger.warn({ err: error }, "Webhook delivery failed");
+		return false;
+	}
+}
@@ -1,14 +1,36 @@
 import type { ChildProcess } from "node:child_process";
 import { spawn } from "node:child_process";
+import { createHash } from "node:crypto";
 import { createWriteStream } from "node:fs";
 import fs from "node:fs/promises";
 import os from "node:os";
 import path from "node:path";
 import { Readable } from "node:stream";
 import { pipeline } from "node:stream/promises";
-import type { MediaOptions } from "@snatch/shared";
+import {
+	detectPlatform,
+	type ErrorCode,
+	type FormatDetails,
+	LANGUAGE_TAG_REGEX,
+	MAX_FORMATS_LIMIT,
+	MAX_PROFILE_ENTRIES,
+	type MediaMetadata,
+	type MediaOptions,
+	platformOf,
+	type ProfileEntry,
+	SERVICES,
+	type SupportedPlatform,
+	type Thumbnail,
+	VIDEO_QUALITIES,
+} from "@snatch/shared";
+import { positiveInt } from "./env";
+import { ERROR_STATUS } from "./errors";
+import { adaptiveTimeoutMs, recordLatency } from "./latency";
+import { logger } from "./logger";
+import { validatePlatformUrl } from "./platforms";
+import { runQueued } from "./queue";
+import { type Backoff, type RetryOptions, retryPolicy, retryWithBackoff } from "./retry";
 
-const SNATCH_DIR = process.env.YTDLP_DIR || path.join(os.homedir(), ".snatch", "bin");
 const RELEASE_BASE = "https://github.com/yt-dlp/yt-dlp/releases/latest/download";
 
 /** Prefix for the on-disk probe cache written by {@link probe}. */
@@ -50,30 +72,87 @@ function ytDlpAssetName(): string {
 	return process.arch === "arm64" ? "yt-dlp_linux_aarch64" : "yt-dlp_linux";
 }
 
-function commandWorks(cmd: string, args: string[]): Promise<boolean> {
-	const { promise, resolve } = Promise.withResolvers<boolean>();
+/** Read per call (not at import) so tests can point it at a scratch directory. */
+function ytDlpDir(): string {
+	return process.env.YTDLP_DIR || path.join(os.homedir(), ".snatch", "bin");
+}
+
+function localYtDlpPath(): string {
+	return path.join(ytDlpDir(), process.platform === "win32" ? "yt-dlp.exe" : "yt-dlp");
+}
+
+/** Trimmed stdout of a successful run, or `null` if the command fails or is missing. */
+function commandOutput(cmd: string, args: string[]): Promise<string | null> {
+	const { promise, resolve } = Promise.withResolvers<string | null>();
 	let child: ChildProcess;
 	try {
-		child = spawn(cmd, args, { stdio: "ignore", timeout: 10_000 });
+		child = spawn(cmd, args, { stdio: ["ignore", "pipe", "ignore"], timeout: 10_000 });
 	} catch {
-		return Promise.resolve(false);
+		return Promise.resolve(null);
 	}
-	child.on("error", () => resolve(false));
-	child.on("close", (code) => resolve(code === 0));
+	let out = "";
+	child.stdout?.on("data", (chunk) => {
+		out += chunk;
+	});
+	child.on("error", () => resolve(null));
+	child.on("close", (code) => resolve(code === 0 ? out.trim() : null));
 	return promise;
 }
 
 /**
- * Resolve a usable yt-dlp binary: system install first, then cached download,
- * then fetch standalone binary from GitHub releases.
+ * `YTDLP_BINARIES`: yt-dlp-compatible binaries to try in order, such as
+ * `yt-dlp,youtube-dl` on hosts that may only have the latter. `undefined`
+ * while unset, which keeps the usual lookup.
  */
-export async function ensureYtDlp(signal?: AbortSignal): Promise<string> {
-	if (await commandWorks("yt-dlp", ["--version"])) return "yt-dlp";
+export function ytDlpBinaries(): string[] | undefined {
+	const binaries = (process.env.YTDLP_BINARIES ?? "")
+		.split(",")
+		.map((binary) => binary.trim())
+		.filter(Boolean);
+	return binaries.length ? binaries : undefined;
+}
+
+/**
+ * The installed yt-dlp and its version, without downloading anything: only
+ * `YTDLP_PATH` when it is set, else the first of `YTDLP_BINARIES` that runs,
+ * else the system install, then the cached download. `null` when none runs.
+ */
+export async function installedYtDlp(): Promise<{ binary: string; version: string } | null> {
+	const configured = process.env.YTDLP_PATH;
+	const candidates = configured ? [configured] : (ytDlpBinaries() ?? ["yt-dlp", localYtDlpPath()]);
+	for (const binary of candidates) {
+		const version = await commandOutput(binary, ["--version"]);
+		if (version !== null) return { binary, version };
+	}
+	return null;
+}
+
+let ffmpegCheck: Promise<boolean> | undefined;
 
-	const local = path.join(SNATCH_DIR, process.platform === "win32" ? "yt-dlp.exe" : "yt-dlp");
-	if (await commandWorks(local, ["--version"])) return local;
+/** Whether `ffmpeg` runs, checked once per process. Merges and audio extraction need it. */
+export function hasFfmpeg(): Promise<boolean> {
+	ffmpegCheck ??= commandOutput("ffmpeg", ["-version"]).then((out) => out !== null);
+	return ffmpegCheck;
+}
+
+/**
+ * Resolve a usable yt-dlp binary: `YTDLP_PATH` if set, else the first of
+ * `YTDLP_BINARIES` that runs (neither is ever replaced by a download), else
+ * system install, then cached download, then fetch the standalone binary
+ * from GitHub releases.
+ */
+export async function ensureYtDlp(signal?: AbortSignal): Promise<string> {
+	const installed = await installedYtDlp();
+	if (installed) return installed.binary;
+	if (process.env.YTDLP_PATH) {
+		throw new YtDlpError("", "yt-dlp at YTDLP_PATH cannot be run", "YTDLP_UNAVAILABLE");
+	}
+	if (ytDlpBinaries()) {
+		throw new YtDlpError("", "None of YTDLP_BINARIES can be run", "YTDLP_UNAVAILABLE");
+	}
 
-	await fs.mkdir(SNATCH_DIR, { recursive: true });
+	const local = localYtDlpPath();
+	await fs.mkdir(ytDlpDir(), { recursive: true });
 
 	const url = `${RELEASE_BASE}/${ytDlpAssetName()}`;
 	const response = await fetch(url, { signal });
@@ -92,13 +171,329 @@ export async function ensureYtDlp(signal?: AbortSignal): Promise<string> {
 	return local;
 }
 
+/** Printable ASCII only: the value becomes an HTTP header, so no CR/LF or other controls. */
+const HEADER_VALUE_REGEX = /^[ -~]+$/;
+/** An RFC 9110 token, as header names must be. */
+const HEADER_NAME_REGEX = /^[!#$%&'*+.^_`|~0-9A-Za-z-]+$/;
+
+/** A trimmed env value checked for header safety; `undefined` when unset. */
+function headerSafeEnv(name: string): string | undefined {
+	const value = process.env[name]?.trim();
+	if (value && !HEADER_VALUE_REGEX.test(value)) {
+		throw new Error(`${name} must contain printable ASCII characters only`);
+	}
+	return value || undefined;
+}
+
+/** `YTDLP_EXTRA_HEADERS` (comma-separated `Name: value` pairs) as `--add-header` flags. */
+function extraHeaderArgs(): string[] {
+	const raw = process.env.YTDLP_EXTRA_HEADERS ?? "";
+	return raw.split(",").flatMap((entry) => {
+		const pair = entry.trim();
+		if (!pair) return [];
+		const sep = pair.indexOf(":");
+		const name = pair.slice(0, Math.max(sep, 0)).trim();
+		const value = pair.slice(sep + 1).trim();
+		if (!HEADER_NAME_REGEX.test(name) || !HEADER_VALUE_REGEX.test(value)) {
+			throw new Error(`YTDLP_EXTRA_HEADERS entry "${pair}" must be a printable "Name: value" pair`);
+		}
+		return ["--add-header", `${name}:${value}`];
+	});
+}
+
+/**
+ * Flags `YTDLP_EXTRA_FLAGS` may turn on. None takes a value, so an entry can
+ * never smuggle in an argument of its own.
+ */
+const EXTRA_FLAG_ALLOWLIST: readonly string[] = [
+	"--force-ipv4",
+	"--force-ipv6",
+	"--geo-bypass",
+	"--legacy-server-connect",
+	"--no-cache-dir",
+	"--no-mark-watched",
+];
+
+/** `name` (comma-separated), each flag checked against {@link EXTRA_FLAG_ALLOWLIST}. */
+function extraFlagArgs(name = "YTDLP_EXTRA_FLAGS"): string[] {
+	const raw = process.env[name] ?? "";
+	const flags = new Set<string>();
+	for (const entry of raw.split(",")) {
+		const flag = entry.trim();
+		if (!flag) continue;
+		if (!EXTRA_FLAG_ALLOWLIST.includes(flag)) {
+			const allowed = EXTRA_FLAG_ALLOWLIST.join(", ");
+			throw new Error(`${name} entry "${flag}" is not one of ${allowed}`);
+		}
+		flags.add(flag);
+	}
+	return [...flags];
+}
+
+/** One `--extractor-args` value: `extractor:key=value[;key=value]`, printable and unspaced. */
+const EXTRACTOR_ARGS_REGEX = /^[\w-]+:[!-~]+$/;
+
+/** A platform's extra yt-dlp arguments, on top of the global ones. */
+interface PlatformProfile {
+	flags: string[];
+	extractorArgs: string[];
+}
+
+/**
+ * `platform`'s argument profile: `YTDLP_EXTRA_FLAGS_<PLATFORM>`, checked like
+ * `YTDLP_EXTRA_FLAGS`, and `YTDLP_EXTRACTOR_ARGS_<PLATFORM>` as one
+ * `--extractor-args` value (e.g. `twitter:api=syndication`). Empty by default.
+ */
+function platformProfile(platform: SupportedPlatform): PlatformProfile {
+	const suffix = platform.toUpperCase();
+	const name = `YTDLP_EXTRACTOR_ARGS_${suffix}`;
+	const extractorArgs = process.env[name]?.trim();
+	if (extractorArgs && !EXTRACTOR_ARGS_REGEX.test(extractorArgs)) {
+		throw new Error(`${name} must be an "extractor:key=value" argument without spaces`);
+	}
+	return {
+		flags: extraFlagArgs(`YTDLP_EXTRA_FLAGS_${suffix}`),
+		extractorArgs: extractorArgs ? ["--extractor-args", extractorArgs] : [],
+	};
+}
+
+/**
+ * Operator-configured flags appended to every yt-dlp invocation. Values are
+ * passed as discrete argv entries (never through a shell), so the only thing
+ * to guard against is header injection via control characters.
+ *
+ * For a `url` on a known platform, `YTDLP_USER_AGENT_<PLATFORM>` (e.g.
+ * `YTDLP_USER_AGENT_TIKTOK`) overrides `YTDLP_USER_AGENT` and the platform's
+ * {@link platformProfile} is appended. Without a `url` it validates every
+ * override and profile, which is how startup calls it so bad config fails the
+ * boot rather than a request.
+ */
+export function configArgs(url?: string): string[] {
+	let userAgent = headerSafeEnv("YTDLP_USER_AGENT");
+	let profile: PlatformProfile = { flags: [], extractorArgs: [] };
+	if (url) {
+		const platform = detectPlatform(url);
+		if (platform) {
+			userAgent = headerSafeEnv(platformUserAgentVar(platform)) ?? userAgent;
+			profile = platformProfile(platform);
+		}
+	} else {
+		for (const service of SERVICES) {
+			headerSafeEnv(platformUserAgentVar(service.id));
+			platformProfile(service.id);
+		}
+	}
+	return [
+		...(userAgent ? ["--user-agent", userAgent] : []),
+		...extraHeaderArgs(),
+		...new Set([...extraFlagArgs(), ...profile.flags]),
+		...profile.extractorArgs,
+	];
+}
+
+/**
+ * Flags asking for titles and captions in `lang`: an `Accept-Language` header,
+ * which most extractors honour, plus YouTube's own `lang` extractor argument.
+ * Empty unless `lang` matches `LANGUAGE_TAG_REGEX`, so a request value can
+ * never smuggle in flags of its own.
+ */
+export function languageArgs(lang?: string): string[] {
+	if (!lang || !LANGUAGE_TAG_REGEX.test(lang)) return [];
+	return ["--add-header", `Accept-Language:${lang}`, "--extractor-args", `youtube:lang=${lang}`];
+}
+
+function platformUserAgentVar(platform: SupportedPlatform): string {
+	return `YTDLP_USER_AGENT_${platform.toUpperCase()}`;
+}
+
+/**
+ * Well-known yt-dlp stderr markers and the error code each one maps to.
+ * Checked in order; any other non-zero exit is a `YTDLP_FAILED` 502.
+ */
+const STDERR_CLASSES = [
+	{ code: "RATE_LIMITED", pattern: /HTTP Error 429|Too Many Requests/i },
+	{
+		code: "GEO_BLOCKED",
+		pattern: /geo.?restricted|not available (from|in) your (country|location)/i,
+	},
+	{
+		code: "PRIVATE_CONTENT",
+		pattern: /Private video|This video is private|This post may not be comfortable/i,
+	},
+	{
+		code: "NOT_FOUND",
+		pattern: /Video unavailable|Requested content is not available|HTTP Error 404/i,
+	},
+	// After the per-URL classes, so a private or missing post is still reported
+	// as such. yt-dlp's generic "Confirm you are on the latest version" bug-report
+	// footer is deliberately not matched: it follows almost every failure.
+	{
+		code: "YTDLP_OUTDATED",
+		pattern:
+			/please update yt-dlp|outdated version of yt-dlp|yt-dlp version \S+ is older than \d+ days|update to the latest version of yt-dlp/i,
+	},
+] as const satisfies readonly { code: ErrorCode; pattern: RegExp }[];
+
+export type YtDlpErrorCode =
+	| (typeof STDERR_CLASSES)[number]["code"]
+	| "TIMEOUT"
+	| "YTDLP_FAILED"
+	| "NO_METADATA"
+	| "YTDLP_UNAVAILABLE";
+
+/** Classify a run that started but exited non-zero, from its stderr. */
+export function classifyYtDlpError(stderr: string) {
+	const code: YtDlpErrorCode =
+		STDERR_CLASSES.find(({ pattern }) => pattern.test(stderr))?.code ?? "YTDLP_FAILED";
+	return { code, status: ERROR_STATUS[code] };
+}
+
+/** Longest stderr excerpt kept for logs and debug output; yt-dlp's useful lines come last. */
+const STDERR_EXCERPT_CHARS = 4_000;
+
+/**
+ * A failed yt-dlp run, carrying a client-safe message and its classification.
+ * The raw `stderr` excerpt is for logs only (see `describeFailure`).
+ */
+export class YtDlpError extends Error {
+	readonly code: YtDlpErrorCode;
+	readonly status: (typeof ERROR_STATUS)[YtDlpErrorCode];
+	readonly stderr: string;
+
+	constructor(
+		stderr: string,
+		fallbackMessage: string,
+		code: YtDlpErrorCode = classifyYtDlpError(stderr).code,
+	) {
+		super(cleanYtDlpError(stderr) || fallbackMessage);
+		this.code = code;
+		this.status = ERROR_STATUS[code];
+		const trimmed = stderr.trim();
+		this.stderr =
+			trimmed.length > STDERR_EXCERPT_CHARS
+				? `…${trimmed.slice(-STDERR_EXCERPT_CHARS)}`
+				: trimmed;
+	}
+}
+
+/**
+ * Map a child-process `error` event. A failed `spawn` (missing binary,
+ * permission denied) is an ops problem rather than a per-URL one, so it gets
+ * its own 503 code; the raw cause, which names server paths, is only logged.
+ * Aborts pass through untouched.
+ */
+export function spawnFailure(error: Error): Error {
+	if (error.name === "AbortError") return error;
+	logger.error({ err: error }, "Failed to start yt-dlp");
+	return new YtDlpError("", "yt-dlp is unavailable", "YTDLP_UNAVAILABLE");
+}
+
+/** Short, stable digest that correlates one URL across log lines without recording it. */
+export function urlHash(url: string): string {
+	return createHash("sha256").update(url).digest("hex").slice(0, 12);
+}
+
+/** Identifies one yt-dlp invocation in its log span. */
+interface RunSpan {
+	span: "yt_dlp.extract" | "yt_dlp.list" | "yt_dlp.download";
+	url: string;
+	/** The request the run serves, so its retries can be grepped together. */
+	requestId?: string;
+	attempt: number;
+}
+
+/**
+ * Debug-level span for a single yt-dlp invocation; retries log one each.
+ * `exitCode` is null when the process never exited on its own (spawn error,
+ * abort).
+ */
+function logRun(
+	{ span, url, requestId, attempt }: RunSpan,
+	started: number,
+	exitCode: number | null,
+	stderr: string,
+): void {
+	logger.debug(
+		{
+			span,
+			requestId,
+			platform: detectPlatform(url),
+			urlHash: urlHash(url),
+			attempt,
+			durationMs: Math.round(performance.now() - started),
+			exitCode,
+			stderrBytes: stderr.length,
+		},
+		"yt-dlp run finished",
+	);
+}
+
+/** Run `invoke` inside a {@link logRun} span. */
+async function spanned(run: RunSpan, invoke: () => Promise<CommandResult>): Promise<CommandResult> {
+	const started = performance.now();
+	let result: CommandResult | undefined;
+	try {
+		result = await invoke();
+		return result;
+	} finally {
+		logRun(run, started, result?.exitCode ?? null, result?.stderr ?? "");
+	}
+}
+
+/** Network-level hiccups worth another attempt; everything else is treated as permanent. */
+const TRANSIENT_ERROR_REGEX =
+	/timed? ?out|connection (reset|refused|aborted)|temporary failure|HTTP Error 5\d\d|Unable to download (webpage|JSON)|IncompleteRead/i;
+
+/**
+ * Whether a failed yt-dlp run could succeed on retry. Classified failures
+ * (private, removed, geo-blocked) and spawn errors never do, so retrying them
+ * only adds backoff latency to a guaranteed failure. A platform 429 might, but
+ * only on the slower {@link RATE_LIMIT_BACKOFF} schedule.
+ */
+export function isRetryableError(error: unknown): boolean {
+	if (!(error instanceof YtDlpError)) return false;
+	if (error.code === "RATE_LIMITED") return true;
+	return error.code === "YTDLP_FAILED" && TRANSIENT_ERROR_REGEX.test(error.message);
+}
+
+/**
+ * Backoff after a platform answers 429: hammering it on the usual sub-second
+ * schedule only extends the block, so wait at least 5s, jittered so parallel
+ * requests don't retry in lockstep, and give up after one retry.
+ */
+export const RATE_LIMIT_BACKOFF: Backoff = {
+	attempts: 2,
+	initialDelayMs: 5_000,
+	maxDelayMs: 15_000,
+	jitter: 0.5,
+};
+
+/** The retry schedule for `error` when it differs from the caller's default. */
+export function ytdlpBackoffFor(error: unknown): Backoff | undefined {
+	return error instanceof YtDlpError && error.code === "RATE_LIMITED"
+		? RATE_LIMIT_BACKOFF
+		: undefined;
+}
+
 interface RawFormat {
 	format_id: string;
+	/** The platform's direct media URL, often signed with an expiry. */
+	url?: string;
 	ext?: string;
+	/** e.g. `https`, `m3u8_native`; `mhtml` for storyboard thumbnails. */
+	protocol?: string;
 	vcodec?: string;
 	acodec?: string;
+	/** Free-form quality note, e.g. `1080p60` or `DASH video`. */
+	format_note?: string;
 	height?: number;
 	width?: number;
+	fps?: number;
+	/**
+	 * Set when `height`/`fps` were read off a quality label rather than
+	 * reported by yt-dlp, whose `[height=N]` filters then cannot see them.
+	 */
+	heightFromLabel?: boolean;
 	abr?: number;
 	tbr?: number;
 	filesize?: number;
@@ -109,51 +504,212 @@ export interface VideoInfo {
 	id: string;
 	title: string;
 	uploader?: string;
+	uploader_id?: string;
+	/** `YYYYMMDD`, as yt-dlp reports it. */
+	upload_date?: string;
+	description?: string;
+	/** Seconds; fractional for some extractors (e.g. Twitter). */
 	duration?: number;
+	view_count?: number;
+	like_count?: number;
 	thumbnail?: string;
+	/** yt-dlp's `thumbnails`, smallest first, entries without a URL dropped. */
+	thumbnails?: Thumbnail[];
 	webpage_url?: string;
 	extractor_key?: string;
+	ext?: string;
+	is_live?: boolean;
+	/** `is_live`, `is_upcoming`, `was_live`, `post_live` or `not_live`. */
+	live_status?: string;
 	formats?: RawFormat[];
+	/** Entries of yt-dlp's `formats` array dropped by the shape guard. */
+	skippedFormats?: number;
+	/** Items of a multi-item post (carousel, photo post), reported as a playlist. */
+	entries?: VideoInfo[];
+}
+
+function isObject(value: unknown): value is Record<string, unknown> {
+	return typeof value === "object" && value !== null;
 }
 
 function isRawFormat(value: unknown): value is RawFormat {
-	return (
-		typeof value === "object" &&
-		value !== null &&
-		typeof (value as RawFormat).format_id === "string"
-	);
+	return isObject(value) && typeof value.format_id === "string";
+}
+
+/** Quality labels such as `1080p60` or `720p`: height, then an optional frame rate. */
+const QUALITY_LABEL_REGEX = /^(\d{3,4})p(\d{2,3})?$/;
+
+/**
+ * Fill in `height` (and `fps`) from a quality label when an extractor reports
+ * one instead of a number: Twitch clips come with `"1080p60"`-style labels.
+ * A height that is neither a number nor such a label is dropped.
+ */
+function withNumericHeight(f: RawFormat): RawFormat {
+	const height: unknown = f.height;
+	if (optionalNumber(height) !== undefined) return f;
+	for (const label of [height, f.format_note, f.format_id]) {
+		const match = typeof label === "string" ? QUALITY_LABEL_REGEX.exec(label) : null;
+		if (!match) continue;
+		const fps = optionalNumber(f.fps) ?? (match[2] ? Number(match[2]) : undefined);
+		return { ...f, height: Number(match[1]), fps, heightFromLabel: true };
+	}
+	return height === undefined ? f : { ...f, height: undefined };
+}
+
+function optionalString(value: unknown): string | undefined {
+	return typeof value === "string" ? value : undefined;
 }
 
-/** Parse and shape-validate untrusted yt-dlp JSON into a VideoInfo. */
+function optionalNumber(value: unknown): number | undefined {
+	return typeof value === "number" && Number.isFinite(value) ? value : undefined;
+}
+
+function optionalBoolean(value: unknown): boolean | undefined {
+	return typeof value === "boolean" ? value : undefined;
+}
+
+function parseThumbnails(value: unknown): Thumbnail[] | undefined {
+	if (!Array.isArray(value)) return undefined;
+	return value.filter(isObject).flatMap((thumb) => {
+		const url = optionalString(thumb.url);
+		if (!url) return [];
+		const width = optionalNumber(thumb.width);
+		const height = optionalNumber(thumb.height);
+		return [{ url, ...(width && height ? { width, height } : {}) }];
+	});
+}
+
+/** Drop a leading UTF-8 byte order mark, which `JSON.parse` rejects. */
+function stripBom(text: string): string {
+	return text.charCodeAt(0) === 0xfeff ? text.slice(1) : text;
+}
+
+/**
+ * Parse and shape-validate untrusted yt-dlp JSON into a VideoInfo. A run that
+ * exits 0 with nothing on stdout (some age-gated pages) is `NO_METADATA`
+ * rather than a parse error.
+ */
 export function parseVideoInfo(raw: string): VideoInfo {
+	const text = stripBom(raw);
+	if (!text.trim()) throw new YtDlpError("", "No metadata returned", "NO_METADATA");
 	let data: unknown;
 	try {
-		data = JSON.parse(raw);
+		data = JSON.parse(text);
 	} catch {
 		throw new Error("Could not parse video metadata from yt-dlp.");
 	}
-	if (typeof data !== "object" || data === null) {
+	if (!isObject(data)) {
 		throw new Error("Unexpected video metadata shape from yt-dlp.");
 	}
-	const obj = data as Record<string, unknown>;
+	return parseInfoObject(data);
+}
+
+function parseInfoObject(obj: Record<string, unknown>): VideoInfo {
 	return {
-		id: typeof obj.id === "string" ? obj.id : "",
-		title: typeof obj.title === "string" ? obj.title : "",
-		uploader: typeof obj.uploader === "string" ? obj.uploader : undefined,
-		duration: typeof obj.duration === "number" ? obj.duration : undefined,
-		thumbnail: typeof obj.thumbnail === "string" ? obj.thumbnail : undefined,
-		webpage_url: typeof obj.webpage_url === "string" ? obj.webpage_url : undefined,
-		extractor_key: typeof obj.extractor_key === "string" ? obj.extractor_key : undefined,
-		formats: Array.isArray(obj.formats) ? obj.formats.filter(isRawFormat) : undefined,
+		id: optionalString(obj.id) ?? "",
+		title: optionalString(obj.title) ?? "",
+		uploader: optionalString(obj.uploader),
+		uploader_id: optionalString(obj.uploader_id),
+		upload_date: optionalString(obj.upload_date),
+		description: optionalString(obj.description),
+		duration: optionalNumber(obj.duration),
+		view_count: optionalNumber(obj.view_count),
+		like_count: optionalNumber(obj.like_count),
+		thumbnail: optionalString(obj.thumbnail),
+		thumbnails: parseThumbnails(obj.thumbnails),
+		webpage_url: optionalString(obj.webpage_url),
+		extractor_key: optionalString(obj.extractor_key),
+		ext: optionalString(obj.ext),
+		is_live: optionalBoolean(obj.is_live),
+		live_status: optionalString(obj.live_status),
+		formats: Array.isArray(obj.formats)
+			? obj.formats.filter(isRawFormat).map(withNumericHeight)
+			: undefined,
+		skippedFormats: Array.isArray(obj.formats)
+			? obj.formats.filter((f) => !isRawFormat(f)).length
+			: undefined,
+		entries: Array.isArray(obj.entries)
+			? obj.entries.filter(isObject).map(parseInfoObject)
+			: undefined,
 	};
 }
 
+/**
+ * `thumbnails` largest area first. Entries without dimensions keep their
+ * relative order at the end, since their size is anyone's guess.
+ */
+export function sortThumbnails(thumbnails: Thumbnail[]): Thumbnail[] {
+	const area = (thumb: Thumbnail) => (thumb.width ?? 0) * (thumb.height ?? 0);
+	return [...thumbnails].sort((a, b) => area(b) - area(a));
+}
+
+/** Map yt-dlp metadata onto the wire format; absent fields stay undefined and are omitted. */
+export function describeMedia(info: VideoInfo): MediaMetadata {
+	const thumbnails = sortThumbnails(info.thumbnails ?? []);
+	const largest = thumbnails[0]?.width ? thumbnails[0].url : undefined;
+	return {
+		title: info.title,
+		thumbnail: largest ?? info.thumbnail ?? thumbnails[0]?.url,
+		thumbnails: thumbnails.length ? thumbnails : undefined,
+		duration: info.duration,
+		uploader: info.uploader,
+		uploaderId: info.uploader_id,
+		uploadDate: info.upload_date,
+		description: info.description,
+		viewCount: info.view_count,
+		likeCount: info.like_count,
+		isLive: isLive(info),
+	};
+}
+
+/** Whether the media is still broadcasting; extractors report either field. */
+export function isLive(info: VideoInfo): boolean {
+	return info.is_live === true || info.live_status === "is_live";
+}
+
+const DEFAULT_LIVE_CAPTURE_SECS = 300;
+const MAX_LIVE_CAPTURE_SECS = 1800;
+
+/**
+ * How long an opted-in live capture may record: the request's `maxDuration`,
+ * else 5 minutes, never more than 30.
+ */
+export function liveCaptureSecs(requested?: number): number {
+	return Math.min(requested || DEFAULT_LIVE_CAPTURE_SECS, MAX_LIVE_CAPTURE_SECS);
+}
+
+/**
+ * Download flags that record a live stream from "now" for at most `secs`
+ * seconds. ffmpeg stops itself at the limit; callers still enforce a
+ * wall-clock deadline in case the stream stalls.
+ */
+export function liveCaptureArgs(secs: number): string[] {
+	return [
+		"--no-live-from-start",
+		"--downloader",
+		"ffmpeg",
+		"--downloader-args",
+		`ffmpeg_i:-t ${secs}`,
+	];
+}
+
 interface ProbeResult {
 	info: VideoInfo;
 	infoJsonPath: string;
 }
 
-interface DownloadChoice {
+/** What shapes the choices for a probed item: the user's media options plus host capabilities. */
+export interface ChoiceOptions
+	extends Pick<MediaOptions, "audioFormat" | "videoQuality" | "downloadMode"> {
+	/**
+	 * Whether ffmpeg is there to merge a video-only stream with a separate audio
+	 * one (see {@link hasFfmpeg}). Without it, such videos are offered video-only.
+	 * Defaults to true.
+	 */
+	canMerge?: boolean;
+}
+
+export interface DownloadChoice {
 	id: string;
 	label: string;
 	kind: "video" | "audio";
@@ -161,6 +717,7 @@ interface DownloadChoice {
 	ext: string;
 	args: string[];
 	sizeLabel?: string;
+	details?: FormatDetails;
 }
 
 function formatBytes(bytes: number): string {
@@ -171,32 +728,133 @@ function formatBytes(bytes: number): string {
 	return `${(bytes / k ** i).toFixed(1)} ${sizes[i]}`;
 }
 
+const MIN_PROBE_TIMEOUT_SECS = 5;
+const DEFAULT_PROBE_TIMEOUT_SECS = 30;
+const DEFAULT_PROBE_TIMEOUT_MAX_SECS = 120;
+
+/**
+ * Effective probe timeout in seconds: the request's `timeoutSecs`, else
+ * `platform`'s adaptive timeout (see {@link adaptiveTimeoutMs}), else
+ * `EXTRACT_TIMEOUT_SECS`, else 30, clamped to 5..`EXTRACT_TIMEOUT_MAX_SECS`
+ * (default 120) so a client can neither hang a worker nor fail instantly.
+ * A configured `EXTRACT_TIMEOUT_SECS` also caps the adaptive timeout, which
+ * can only tighten the operator's value, never extend it.
+ */
+export function probeTimeoutSecs(requested?: number, platform?: string | null): number {
+	const max = Math.max(
+		positiveInt(process.env.EXTRACT_TIMEOUT_MAX_SECS) ?? DEFAULT_PROBE_TIMEOUT_MAX_SECS,
+		MIN_PROBE_TIMEOUT_SECS,
+	);
+	const configured = positiveInt(process.env.EXTRACT_TIMEOUT_SECS);
+	const adaptiveMax = Math.min(configured ?? max, max);
+	const adaptiveMs = platform
+		? adaptiveTimeoutMs(platform, MIN_PROBE_TIMEOUT_SECS * 1000, adaptiveMax * 1000)
+		: undefined;
+	const secs =
+		requested ||
+		(adaptiveMs && Math.ceil(adaptiveMs / 1000)) ||
+		configured ||
+		DEFAULT_PROBE_TIMEOUT_SECS;
+	return Math.min(Math.max(secs, MIN_PROBE_TIMEOUT_SECS), max);
+}
+
+/** What an extraction cost, filled in by {@link probe} and {@link listProfile} for logging. */
+export interface ExtractionStats {
+	/** yt-dlp runs, retries included; 0 when answered from cache. */
+	attempts: number;
+	cacheHit: boolean;
+	/** Unix seconds the answer was fetched from the platform; a cache hit keeps the original. */
+	extractedAt?: number;
+}
+
+interface ProbeOptions {
+	signal?: AbortSignal;
+	/**
+	 * Budget for the whole probe, retries included. Defaults to
+	 * {@link probeTimeoutSecs} for the URL's platform.
+	 */
+	timeoutMs?: number;
+	run?: CommandRunner;
+	sleep?: RetryOptions["sleep"];
+	stats?: ExtractionStats;
+	/** The request this probe serves, for correlating its log lines. */
+	requestId?: string;
+	/** Preferred language for titles and captions; see {@link languageArgs}. */
+	lang?: string;
+}
+
 export async function probe(
 	ytdlp: string,
 	url: string,
-	signal?: AbortSignal,
+	{
+		signal,
+		timeoutMs = probeTimeoutSecs(undefined, platformOf(url)) * 1000,
+		run = withBinaryFallback(spawnCommand),
+		sleep,
+		stats,
+		requestId,
+		lang,
+	}: ProbeOptions = {},
 ): Promise<ProbeResult> {
-	const { promise, resolve, reject } = Promise.withResolvers<string>();
-	const child = spawn(ytdlp, ["-J", "--no-playlist", "--no-warnings", url], { signal });
-	let out = "";
-	let stderr = "";
-	child.stdout.on("data", (chunk) => {
-		out += chunk;
-	});
-	child.stderr.on("data", (chunk) => {
-		stderr += chunk;
-	});
-	child.on("error", reject);
-	child.on("close", (code) => {
-		if (code !== 0) {
-			reject(new Error(cleanYtDlpError(stderr) || `yt-dlp probe failed (exit code ${code})`));
-		} else {
-			resolve(out);
+	const args = [
+		"-J",
+		"--no-playlist",
+		"--no-warnings",
+		...configArgs(url),
+		...languageArgs(lang),
+		url,
+	];
+	const timeout = AbortSignal.timeout(timeoutMs);
+	const runSignal = signal ? AbortSignal.any([signal, timeout]) : timeout;
+	// How long the latest yt-dlp run took once it had a queue slot; queue wait,
+	// failed attempts and backoff stay out of the platform's latency average.
+	let runMs = 0;
+	const timedRun = async () => {
+		const started = performance.now();
+		const result = await run(ytdlp, args, runSignal);
+		runMs = performance.now() - started;
+		return result;
+	};
+	const attempt = async (n: number) => {
+		if (stats) stats.attempts = n;
+		const { stdout, stderr, exitCode } = await spanned(
+			{ span: "yt_dlp.extract", url, requestId, attempt: n },
+			() => runQueued(timedRun, runSignal),
+		);
+		if (exitCode !== 0) {
+			throw new YtDlpError(stderr, `yt-dlp probe failed (exit code ${exitCode})`);
 		}
-	});
+		return stripBom(stdout);
+	};
+
+	const timedOut = () => {
+		const secs = Math.round(timeoutMs / 100) / 10;
+		return new YtDlpError("", `Extraction timed out after ${secs}s`, "TIMEOUT");
+	};
 
-	const stdout = await promise;
+	// `timeoutMs` bounds every attempt and backoff together: each attempt runs
+	// on what is left of it, and a retry that could not start in time is not
+	// slept on, so the client gets TIMEOUT as soon as the budget is spent.
+	const policy = retryPolicy();
+	let stdout: string;
+	try {
+		stdout = await retryWithBackoff(attempt, {
+			...policy,
+			deadlineMs: Math.min(policy.deadlineMs ?? timeoutMs, timeoutMs),
+			shouldRetry: (error) => !runSignal.aborted && isRetryableError(error),
+			backoffFor: ytdlpBackoffFor,
+			onRetry: (error, attempt, delayMs) =>
+				logger.warn({ err: error, attempt, delayMs }, "yt-dlp probe failed, retrying"),
+			onDeadline: timedOut,
+			sleep,
+		});
+	} catch (error) {
+		if (timeout.aborted && !signal?.aborted) throw timedOut();
+		throw error;
+	}
 	const info = parseVideoInfo(stdout);
+	const platform = platformOf(url);
+	if (platform) recordLatency(platform, runMs);
 
 	const tmpDir = os.tmpdir();
 	await reapStaleInfoJson(tmpDir);
@@ -205,43 +863,377 @@ export async function probe(
 	return { info, infoJsonPath };
 }
 
-const MAX_VIDEO_CHOICES = 8;
+/** Listings are reused briefly so a client paging through a profile costs one yt-dlp run. */
+const PROFILE_CACHE_MS = 60_000;
+const PROFILE_CACHE_MAX = 100;
+interface CachedProfile {
+	expires: number;
+	entries: ProfileEntry[];
+	/** Unix seconds the listing was fetched. */
+	extractedAt: number;
+	bytes: number;
+}
+
+const profileCache = new Map<string, CachedProfile>();
+let profileCacheBytes = 0;
+
+/** Drop every cached profile listing. */
+export function clearProfileCache(): void {
+	profileCache.clear();
+	profileCacheBytes = 0;
+}
+
+/** Listings held by the profile cache and their approximate serialized size. */
+export function profileCacheUsage(): { entries: number; bytes: number } {
+	return { entries: profileCache.size, bytes: profileCacheBytes };
+}
 
+/**
+ * Cache a listing. By default the cache holds {@link PROFILE_CACHE_MAX}
+ * listings and starts over when full; with `PROFILE_CACHE_MAX_BYTES` set it
+ * instead evicts the oldest listings until the new one fits that budget of
+ * serialized JSON bytes, and never caches a listing larger than the budget.
+ */
+function cacheProfile(key: string, entries: ProfileEntry[], extractedAt: number): void {
+	const previous = profileCache.get(key);
+	if (previous) {
+		profileCache.delete(key);
+		profileCacheBytes -= previous.bytes;
+	}
+	const budget = positiveInt(process.env.PROFILE_CACHE_MAX_BYTES);
+	const bytes = Buffer.byteLength(JSON.stringify(entries));
+	if (budget === undefined) {
+		if (profileCache.size >= PROFILE_CACHE_MAX) clearProfileCache();
+	} else {
+		if (bytes > budget) return;
+		for (const [oldest, cached] of profileCache) {
+			if (profileCacheBytes + bytes <= budget) break;
+			profileCache.delete(oldest);
+			profileCacheBytes -= cached.bytes;
+		}
+	}
+	profileCache.set(key, { expires: Date.now() + PROFILE_CACHE_MS, entries, extractedAt, bytes });
+	profileCacheBytes += bytes;
+}
+
+interface ListProfileOptions extends ProbeOptions {
+	/** Entries to return; clamped to 1..{@link MAX_PROFILE_ENTRIES}. */
+	limit?: number;
+}
+
+/**
+ * List a creator's most recent uploads with `--flat-playlist`, which reads the
+ * profile page only and never touches the individual videos. Entries whose URL
+ * would not pass `validatePlatformUrl` are dropped, since clients resolve them next.
+ */
+export async function listProfile(
+	ytdlp: string,
+	url: string,
+	{
+		limit = MAX_PROFILE_ENTRIES,
+		signal,
+		timeoutMs = probeTimeoutSecs(undefined, platformOf(url)) * 1000,
+		run = withBinaryFallback(spawnCommand),
+		stats,
+		requestId,
+		lang,
+	}: ListProfileOptions = {},
+): Promise<ProfileEntry[]> {
+	const count = Math.min(Math.max(limit, 1), MAX_PROFILE_ENTRIES);
+	const key = `${count} ${url}`;
+	const cached = profileCache.get(key);
+	if (cached && cached.expires > Date.now()) {
+		if (stats) {
+			stats.cacheHit = true;
+			stats.extractedAt = cached.extractedAt;
+		}
+		return cached.entries;
+	}
+	if (stats) stats.attempts = 1;
+
+	const args = [
+		"--flat-playlist",
+		"--dump-json",
+		"--playlist-end",
+		String(count),
+		"--no-warnings",
+		...configArgs(url),
+		...languageArgs(lang),
+		url,
+	];
+	const timeout = AbortSignal.timeout(timeoutMs);
+	const runSignal = signal ? AbortSignal.any([signal, timeout]) : timeout;
+	let result: CommandResult;
+	try {
+		result = await spanned({ span: "yt_dlp.list", url, requestId, attempt: 1 }, () =>
+			runQueued(() => run(ytdlp, args, runSignal), runSignal),
+		);
+	} catch (error) {
+		if (timeout.aborted && !signal?.aborted) {
+			throw new YtDlpError("", "Profile listing timed out", "TIMEOUT");
+		}
+		throw error;
+	}
+	if (result.exitCode !== 0) {
+		throw new YtDlpError(result.stderr, `yt-dlp listing failed (exit code ${result.exitCode})`);
+	}
+
+	const entries = result.stdout
+		.split("\n")
+		.flatMap((line) => parseProfileEntry(line) ?? [])
+		.slice(0, count);
+	const extractedAt = Math.floor(Date.now() / 1000);
+	if (stats) stats.extractedAt = extractedAt;
+	cacheProfile(key, entries, extractedAt);
+	return entries;
+}
+
+function parseProfileEntry(line: string): ProfileEntry | null {
+	let data: unknown;
+	try {
+		data = JSON.parse(line);
+	} catch {
+		return null;
+	}
+	if (!isObject(data)) return null;
+	const url = optionalString(data.webpage_url) ?? optionalString(data.url);
+	if (!url || !validatePlatformUrl(url).valid) return null;
+
+	// Flat entries usually carry a `thumbnails` list (smallest first) instead of `thumbnail`.
+	const thumbnails = Array.isArray(data.thumbnails) ? data.thumbnails.filter(isObject) : [];
+	return {
+		id: optionalString(data.id) ?? url,
+		url,
+		title: optionalString(data.title),
+		thumbnail: optionalString(data.thumbnail) ?? optionalString(thumbnails.at(-1)?.url),
+	};
+}
+
+/** Captured output of one finished process. */
+export interface CommandResult {
+	stdout: string;
+	stderr: string;
+	/** `null` when the process was killed by a signal. */
+	exitCode: number | null;
+}
+
+/**
+ * Runs a command to completion. Rejects only when the process cannot be
+ * started or is aborted; a non-zero exit resolves so the caller classifies it.
+ * {@link probe} takes one so its pipeline can be tested without a real yt-dlp.
+ */
+export type CommandRunner = (
+	command: string,
+	args: string[],
+	signal?: AbortSignal,
+) => Promise<CommandResult>;
+
+/**
+ * `run`, falling back through `binaries` in order when the command fails to
+ * spawn (see {@link spawnFailure}), so a binary that went missing after
+ * startup costs a fallback rather than the request. Logs the binary that
+ * served a run in the primary's place.
+ */
+export function withBinaryFallback(
+	run: CommandRunner,
+	binaries = ytDlpBinaries() ?? [],
+): CommandRunner {
+	return async (command, args, signal) => {
+		let failure: unknown;
+		for (const binary of [command, ...binaries.filter((b) => b !== command)]) {
+			try {
+				const result = await run(binary, args, signal);
+				if (binary !== command) {
+					logger.warn({ binary, primary: command }, "yt-dlp run served by a fallback binary");
+				}
+				return result;
+			} catch (error) {
+				if (!(error instanceof YtDlpError && error.code === "YTDLP_UNAVAILABLE")) throw error;
+				failure = error;
+			}
+		}
+		throw failure;
+	};
+}
+
+/** How long an aborted child gets to exit on SIGTERM before it is SIGKILLed. */
+const KILL_GRACE_MS = 2_000;
+
+/**
+ * Kill `child` once `signal` aborts: SIGTERM, then SIGKILL if it is still
+ * running {@link KILL_GRACE_MS} later. Node's own `spawn({ signal })` only
+ * sends the SIGTERM, so a yt-dlp stuck on a dead socket outlived its timeout.
+ */
+function killOnAbort(child: ChildProcess, signal?: AbortSignal): void {
+	if (!signal) return;
+	let escalation: ReturnType<typeof setTimeout> | undefined;
+	const onAbort = () => {
+		child.kill("SIGTERM");
+		escalation = setTimeout(() => child.kill("SIGKILL"), KILL_GRACE_MS);
+	};
+	const done = () => {
+		clearTimeout(escalation);
+		signal.removeEventListener("abort", onAbort);
+	};
+	child.once("exit", done);
+	child.once("error", done);
+	if (signal.aborted) onAbort();
+	else signal.addEventListener("abort", onAbort, { once: true });
+}
+
+/** The rejection for a run cut short by `signal`, shaped like Node's own `AbortError`. */
+function abortError(signal: AbortSignal): Error {
+	const error = new Error("The operation was aborted", { cause: signal.reason });
+	error.name = "AbortError";
+	return error;
+}
+
+/**
+ * {@link CommandRunner} over `child_process.spawn`. An abort settles only once
+ * the child has exited, so a timed-out probe never leaves yt-dlp running.
+ */
+export function spawnCommand(
+	command: string,
+	args: string[],
+	signal?: AbortSignal,
+): Promise<CommandResult> {
+	const { promise, resolve, reject } = Promise.withResolvers<CommandResult>();
+	const child = spawn(command, args);
+	killOnAbort(child, signal);
+	let stdout = "";
+	let stderr = "";
+	child.stdout.on("data", (chunk) => {
+		stdout += chunk;
+	});
+	child.stderr.on("data", (chunk) => {
+		stderr += chunk;
+	});
+	child.on("error", (error) => reject(spawnFailure(error)));
+	// A grandchild may hold the pipes open after a kill, so don't wait for `close`.
+	child.on("exit", () => {
+		if (signal?.aborted) reject(abortError(signal));
+	});
+	child.on("close", (exitCode) => resolve({ stdout, stderr, exitCode }));
+	return promise;
+}
+
+/** Video choices per picker when neither the request nor `FORMATS_LIMIT` says otherwise. */
+const DEFAULT_FORMATS_LIMIT = 8;
+
+/**
+ * How many video choices a resolve should offer: the request's `formatsLimit`
+ * when set (0 means "server default"), else `FORMATS_LIMIT`, else 8. Always
+ * within 1..{@link MAX_FORMATS_LIMIT}.
+ */
+export function formatsLimit(requested?: number): number {
+	const limit = requested || positiveInt(process.env.FORMATS_LIMIT) || DEFAULT_FORMATS_LIMIT;
+	return Math.min(Math.max(limit, 1), MAX_FORMATS_LIMIT);
+}
+
+/** Shorter renditions are preview variants, not something anyone wants to download. */
+const DEFAULT_MIN_VIDEO_HEIGHT = 144;
+
+/** Lowest height offered as a video choice: `MIN_VIDEO_HEIGHT`, else 144. */
+export function minVideoHeight(): number {
+	return positiveInt(process.env.MIN_VIDEO_HEIGHT) ?? DEFAULT_MIN_VIDEO_HEIGHT;
+}
+
+/**
+ * `DEFAULT_QUALITY`: the `videoQuality` of a request that sends none, so the
+ * picker, `POST /api/download` and `POST /api/best` top out there instead of
+ * at the tallest rendition. Takes the values `videoQuality` does (`720`,
+ * `max`), also spelled `720p` or `best`. Throws on anything else; checked at
+ * startup.
+ */
+export function defaultVideoQuality(): MediaOptions["videoQuality"] {
+	const raw = process.env.DEFAULT_QUALITY?.trim().toLowerCase();
+	if (!raw) return undefined;
+	const quality = raw === "best" ? "max" : raw.replace(/^(\d+)p$/, "$1");
+	const allowed = VIDEO_QUALITIES.find((q) => q === quality);
+	if (!allowed) {
+		const accepted = ["best", ...VIDEO_QUALITIES].join(", ");
+		throw new Error(`DEFAULT_QUALITY must be one of ${accepted}, got "${raw}"`);
+	}
+	return allowed;
+}
+
+/**
+ * Storyboards (sprite sheets of preview frames, delivered as `mhtml`) sit in
+ * yt-dlp's `formats` next to real media and, when their codecs are missing,
+ * look like an unknown-quality video.
+ */
+function isStoryboard(f: RawFormat): boolean {
+	return f.ext === "mhtml" || f.protocol === "mhtml" || f.protocol === "storyboard";
+}
+
+/**
+ * Keep the `limit` highest heights, giving the last slot to the lowest one so
+ * the "data saver" rendition survives truncation. A single slot always goes to
+ * the highest: a one-choice picker should be the best one.
+ */
+function limitHeights(heights: number[], limit: number): number[] {
+	if (heights.length <= limit) return heights;
+	if (limit <= 1) return heights.slice(0, 1);
+	return [...heights.slice(0, limit - 1), heights[heights.length - 1]];
+}
+
+/**
+ * Derive the picker's choices. `videoLimit` caps the per-height video choices
+ * (see {@link formatsLimit}); the download route omits it so every choice a
+ * resolve could have signed is rebuilt and found again.
+ */
 export function buildChoices(
 	info: VideoInfo,
-	options?: Pick<MediaOptions, "audioFormat" | "videoQuality" | "downloadMode">,
+	options?: ChoiceOptions,
+	videoLimit = Number.POSITIVE_INFINITY,
 ): DownloadChoice[] {
-	const formats = info.formats ?? [];
+	const primary = primaryEntry(info);
+	if (primary) {
+		const choices = buildChoices(primary.entry, options, videoLimit);
+		return choices.map((c) => pinToEntry(c, primary.index));
+	}
+
+	const formats = (info.formats ?? []).filter((f) => !isStoryboard(f));
 	const choices: DownloadChoice[] = [];
 	const requestedAudioFmt = options?.audioFormat ?? "mp3";
 	const audioOnly = options?.downloadMode === "audio";
+	const videoQuality = options?.videoQuality ?? defaultVideoQuality();
 	const maxHeight =
-		options?.videoQuality && options.videoQuality !== "max"
-			? Number.parseInt(options.videoQuality, 10)
-			: undefined;
+		videoQuality && videoQuality !== "max" ? Number.parseInt(videoQuality, 10) : undefined;
 
-	const audioFormats = formats.filter(
-		(f) => f.acodec && f.acodec !== "none" && (!f.vcodec || f.vcodec === "none"),
-	);
+	const canMerge = options?.canMerge ?? true;
+	const audioFormats = formats.filter(isAudioOnly);
 	const bestAudio = [...audioFormats].sort(
 		(a, b) => (b.abr ?? b.tbr ?? 0) - (a.abr ?? a.tbr ?? 0),
 	)[0];
 	const audioSize = bestAudio?.filesize ?? bestAudio?.filesize_approx;
 
 	if (!audioOnly) {
-		const videos = formats.filter((f) => f.vcodec && f.vcodec !== "none" && f.height);
+		const minHeight = minVideoHeight();
+		const videos = formats.filter(
+			(f) => f.vcodec && f.vcodec !== "none" && (f.height ?? 0) >= minHeight,
+		);
 		let heights = [...new Set(videos.map((f) => f.height as number))].sort((a, b) => b - a);
 		if (maxHeight !== undefined && Number.isFinite(maxHeight)) {
 			heights = heights.filter((h) => h <= maxHeight);
 		}
 
-		for (const height of heights.slice(0, MAX_VIDEO_CHOICES)) {
+		for (const height of limitHeights(heights, videoLimit)) {
 			const candidates = videos.filter((f) => f.height === height);
-			const best = [...candidates].sort((a, b) => scoreVideo(b) - scoreVideo(a))[0];
-			const muxed = best.acodec && best.acodec !== "none";
-			const size = (best.filesize ?? best.filesize_approx ?? 0) + (muxed ? 0 : (audioSize ?? 0));
+			const best = [...candidates].sort(compareVideo)[0];
+			const muxed = Boolean(best.acodec && best.acodec !== "none");
+			const merged = !muxed && canMerge && bestAudio !== undefined;
+			const size = (best.filesize ?? best.filesize_approx ?? 0) + (merged ? (audioSize ?? 0) : 0);
 			const sizeLabel = size > 0 ? formatBytes(size) : undefined;
 			const ext = "mp4";
+			const details = formatDetails(best);
+			if (merged) {
+				details.formatId = `${best.format_id}+${bestAudio.format_id}`;
+				details.acodec = bestAudio.acodec;
+				details.expiresAt = earliest(details.expiresAt, formatExpiry(bestAudio.url));
+			}
+			details.hasAudio = muxed || merged;
+			if (size > 0) details.filesize = size;
 
 			choices.push({
 				id: `v-${height}p`,
@@ -250,12 +1242,8 @@ export function buildChoices(
 				ext,
 				label: `${height}p (${ext})${sizeLabel ? ` · ~${sizeLabel}` : ""}`,
 				sizeLabel,
-				args: [
-					"-f",
-					`bv*[height=${height}]+ba/b[height=${height}]/bv*[height<=${height}]+ba/b`,
-					"--merge-output-format",
-					"mp4",
-				],
+				details,
+				args: best.heightFromLabel ? pinnedVideoArgs(best, merged) : heightArgs(height, canMerge),
 			});
 		}
 
@@ -267,12 +1255,14 @@ export function buildChoices(
 				quality: "best",
 				ext: "mp4",
 				label: cap ? `Best up to ${cap}p (mp4)` : "Best Quality (mp4)",
-				args: [
-					"-f",
-					cap ? `bv*[height<=${cap}]+ba/b[height<=${cap}]/bv*+ba/b` : "bv*+ba/b",
-					"--merge-output-format",
-					"mp4",
-				],
+				args: canMerge
+					? [
+							"-f",
+							cap ? `bv*[height<=${cap}]+ba/b[height<=${cap}]/bv*+ba/b` : "bv*+ba/b",
+							"--merge-output-format",
+							"mp4",
+						]
+					: ["-f", cap ? `b[height<=${cap}]/bv*[height<=${cap}]/b` : "b/bv*"],
 			});
 		}
 	}
@@ -285,11 +1275,170 @@ export function buildChoices(
 		ext: requestedAudioFmt,
 		label: `Audio Only (${requestedAudioFmt})${audioSizeLabel ? ` · ~${audioSizeLabel}` : ""}`,
 		sizeLabel: audioSizeLabel,
+		details: bestAudio ? { ...formatDetails(bestAudio), hasAudio: true } : undefined,
 		args: ["-f", "ba/b", "-x", "--audio-format", requestedAudioFmt, "--audio-quality", "0"],
 	});
 
 	return choices;
 }
+
+const IMAGE_EXTS = new Set(["jpg", "jpeg", "png", "webp", "heic"]);
+
+function mediaTypeOf(entry: VideoInfo): "video" | "image" {
+	const formats = entry.formats ?? [];
+	const imageOnly = formats.length > 0 && formats.every((f) => IMAGE_EXTS.has(f.ext ?? ""));
+	return IMAGE_EXTS.has(entry.ext ?? "") || imageOnly ? "image" : "video";
+}
+
+/** The entry a multi-item post's top-level picker stands for: its first video. */
+function primaryEntry(info: VideoInfo): { entry: VideoInfo; index: number } | undefined {
+	if (info.formats?.length || !info.entries) return undefined;
+	const i = info.entries.findIndex((entry) => mediaTypeOf(entry) === "video");
+	return i >= 0 ? { entry: info.entries[i], index: i + 1 } : undefined;
+}
+
+/** Restrict a choice to one entry of a playlist-shaped info-json. */
+function pinToEntry(choice: DownloadChoice, index: number, idPrefix = ""): DownloadChoice {
+	return {
+		...choice,
+		id: `${idPrefix}${choice.id}`,
+		args: [...choice.args, "--playlist-items", String(index)],
+	};
+}
+
+export interface PostItem {
+	/** 1-based, as `--playlist-items` counts. */
+	index: number;
+	mediaType: "video" | "image";
+	info: VideoInfo;
+	/** Empty for images. */
+	choices: DownloadChoice[];
+}
+
+/**
+ * Entries of a multi-item post (Instagram carousel, TikTok photo post).
+ * Carousel extractors report a playlist even under `--no-playlist`, which only
+ * collapses video-in-playlist URLs. Each video entry gets its own choices,
+ * with ids prefixed `i<index>-` so they never collide with the top level.
+ */
+export function buildPostItems(
+	info: VideoInfo,
+	options?: ChoiceOptions,
+	videoLimit?: number,
+): PostItem[] {
+	return (info.entries ?? []).map((entry, i) => {
+		const index = i + 1;
+		const mediaType = mediaTypeOf(entry);
+		const choices =
+			mediaType === "image"
+				? []
+				: buildChoices(entry, options, videoLimit).map((c) => pinToEntry(c, index, `i${index}-`));
+		return { index, mediaType, info: entry, choices };
+	});
+}
+
+/** Every choice a resolve may have signed for `info`, top-level and per item. */
+export function signableChoices(
+	info: VideoInfo,
+	options?: ChoiceOptions,
+): DownloadChoice[] {
+	const itemChoices = buildPostItems(info, options).flatMap((item) => item.choices);
+	return [...buildChoices(info, options), ...itemChoices];
+}
+
+/**
+ * Human-readable notes explaining why a picker may offer fewer qualities than
+ * the source has. Empty when nothing was dropped or approximated.
+ */
+export function choiceWarnings(info: VideoInfo, choices: DownloadChoice[]): string[] {
+	const warnings: string[] = [];
+	if (info.skippedFormats) {
+		warnings.push(`Skipped ${info.skippedFormats} malformed format(s) reported by yt-dlp`);
+	}
+	if (choices.some((c) => c.id === "v-best")) {
+		warnings.push("No per-resolution formats found; offering the best available quality only");
+	}
+	// With a separate audio track on offer, a silent video choice means it could not be merged.
+	const audioTrack = info.formats?.some(isAudioOnly);
+	if (audioTrack && choices.some((c) => c.details?.hasAudio === false)) {
+		warnings.push("ffmpeg is unavailable, so videos with a separate audio track are video-only");
+	}
+	return warnings;
+}
+
+/** `-f` selection for one height, merging in the best audio when ffmpeg allows. */
+function heightArgs(height: number, canMerge: boolean): string[] {
+	if (!canMerge) {
+		return ["-f", `b[height=${height}]/bv*[height=${height}]/b[height<=${height}]/bv*/b`];
+	}
+	return [
+		"-f",
+		`bv*[height=${height}]+ba/b[height=${height}]/bv*[height<=${height}]+ba/b`,
+		"--merge-output-format",
+		"mp4",
+	];
+}
+
+/** Select `f` by id, for a height yt-dlp itself does not know (see `heightFromLabel`). */
+function pinnedVideoArgs(f: RawFormat, merged: boolean): string[] {
+	if (!merged) return ["-f", f.format_id];
+	return ["-f", `${f.format_id}+ba/${f.format_id}`, "--merge-output-format", "mp4"];
+}
+
+function isAudioOnly(f: RawFormat): boolean {
+	return Boolean(f.acodec && f.acodec !== "none" && (!f.vcodec || f.vcodec === "none"));
+}
+
+/** Source-format fields a client needs to tell two same-height variants apart. */
+function formatDetails(f: RawFormat): FormatDetails {
+	return {
+		formatId: f.format_id,
+		width: f.width,
+		height: f.height,
+		fps: f.fps,
+		vcodec: f.vcodec,
+		acodec: f.acodec,
+		tbr: f.tbr,
+		expiresAt: formatExpiry(f.url),
+	};
+}
+
+/**
+ * Signed-expiry query params on the platforms' media CDNs: decimal Unix
+ * seconds on googlevideo (`expire`), TikTok (`x-expires`) and token-signed
+ * CDNs such as Twitch's and Vimeo's (`exp`), hex on the Meta CDNs behind
+ * Instagram and Facebook (`oe`).
+ */
+const EXPIRY_PARAMS = [
+	{ name: "expire", radix: 10 },
+	{ name: "x-expires", radix: 10 },
+	{ name: "exp", radix: 10 },
+	{ name: "oe", radix: 16 },
+] as const;
+
+/** When a direct media URL's signature runs out, in Unix seconds, if it says. */
+export function formatExpiry(url: string | undefined): number | undefined {
+	// Formats are untrusted yt-dlp JSON; `url` is only checked for presence.
+	if (typeof url !== "string") return undefined;
+	let params: URLSearchParams;
+	try {
+		params = new URL(url).searchParams;
+	} catch {
+		return undefined;
+	}
+	for (const { name, radix } of EXPIRY_PARAMS) {
+		const raw = params.get(name);
+		const digits = radix === 16 ? /^[0-9a-f]{1,8}$/i : /^\d{1,12}$/;
+		if (raw && digits.test(raw)) return Number.parseInt(raw, radix);
+	}
+	return undefined;
+}
+
+function earliest(a: number | undefined, b: number | undefined): number | undefined {
+	if (a === undefined) return b;
+	return b === undefined ? a : Math.min(a, b);
+}
+
 function scoreVideo(f: RawFormat): number {
 	let score = f.tbr ?? 0;
 	if (f.ext === "mp4") score += 10_000;
@@ -297,85 +1446,197 @@ function scoreVideo(f: RawFormat): number {
 	return score;
 }
 
-interface ExecuteDownloadOptions {
+/**
+ * Order same-height variants best-first: by {@link scoreVideo}, then by size
+ * when the platform reports no bitrate (Twitter often lists several 720p
+ * variants that only differ in filesize).
+ */
+function compareVideo(a: RawFormat, b: RawFormat): number {
+	const sizeOf = (f: RawFormat) => f.filesize ?? f.filesize_approx ?? 0;
+	return scoreVideo(b) - scoreVideo(a) || sizeOf(b) - sizeOf(a);
+}
+
+export interface ExecuteDownloadOptions {
 	ytdlp: string;
 	url: string;
 	infoJsonPath?: string;
 	args: string[];
+	/**
+	 * Called with 0-100 as yt-dlp reports progress; each merged stream restarts
+	 * at 0. `bytes` counts everything this attempt has written, streams summed.
+	 */
+	onProgress?: (percent: number, bytes: number) => void;
+	/** The request this download serves, for correlating its log lines. */
+	requestId?: string;
 }
 
+/** Machine-readable progress line, printed only when a caller asks for progress. */
+const PROGRESS_TEMPLATE =
+	"download:snatch-progress %(progress.downloaded_bytes)s/%(progress.total_bytes,progress.total_bytes_estimate)s";
+const PROGRESS_REGEX = /snatch-progress (\d+)\/(\d+(?:\.\d+)?|NA)/g;
+
+/** Attempts per download; later attempts resume the `.part` file left by the previous one. */
+const DOWNLOAD_ATTEMPTS = 3;
+const RESUME_DELAY_MS = 1_000;
+
+/** yt-dlp exited non-zero after writing some output, so a `--continue` retry can resume it. */
+class PartialDownloadError extends YtDlpError {}
+
 export async function executeDownload(
 	opts: ExecuteDownloadOptions,
 	signal?: AbortSignal,
 ): Promise<{ filePath: string; cleanup: () => Promise<void> }> {
-	const outPattern = path.join(os.tmpdir(), `snatch-${Date.now()}-%(title).60s.%(ext)s`);
+	// Every file this download writes (parts, fragments, the merged result)
+	// shares this prefix, so retries resume the same temp files and cleanup
+	// can sweep them without tracking yt-dlp's intermediate names.
+	const prefix = `snatch-${process.pid}-${Date.now()}-`;
+	const outPattern = path.join(os.tmpdir(), `${prefix}%(title).60s.%(ext)s`);
 	const args = [
 		...(opts.infoJsonPath ? ["--load-info-json", opts.infoJsonPath] : [opts.url]),
 		...opts.args,
+		...configArgs(opts.url),
 		"--no-playlist",
 		"--no-warnings",
+		"--continue",
 		"--print",
 		"after_move:filepath",
 		"--no-simulate",
+		...(opts.onProgress
+			? ["--progress", "--newline", "--progress-template", PROGRESS_TEMPLATE]
+			: []),
 		"-o",
 		outPattern,
 	];
 
-	const destinations: string[] = [];
-	const { promise, resolve, reject } = Promise.withResolvers<{
-		filePath: string;
-		cleanup: () => Promise<void>;
-	}>();
-	const child = spawn(opts.ytdlp, args, { signal });
-	const stdoutLines: string[] = [];
+	const removeOutputs = async () => {
+		await removeTempFiles(prefix);
+		if (opts.infoJsonPath) await fs.rm(opts.infoJsonPath, { force: true });
+	};
+
+	try {
+		const { url, requestId, onProgress } = opts;
+		const attempt = (n: number) => {
+			const started = performance.now();
+			const span: RunSpan = { span: "yt_dlp.download", url, requestId, attempt: n };
+			const onExit = (exitCode: number | null, stderr: string) =>
+				logRun(span, started, exitCode, stderr);
+			return runDownload(opts.ytdlp, args, prefix, { signal, onProgress, onExit });
+		};
+		const filePath = await retryWithBackoff(attempt, {
+			attempts: DOWNLOAD_ATTEMPTS,
+			initialDelayMs: RESUME_DELAY_MS,
+			shouldRetry: (error) => !signal?.aborted && error instanceof PartialDownloadError,
+			onRetry: (error, attempt) =>
+				logger.warn({ err: error, attempt }, "yt-dlp download interrupted, resuming"),
+		});
+		return { filePath, cleanup: removeOutputs };
+	} catch (error) {
+		await removeOutputs();
+		throw error;
+	}
+}
+
+interface RunDownloadHooks {
+	signal?: AbortSignal;
+	onProgress?: (percent: number, bytes: number) => void;
+	/** Called once the process is gone; the code is null if it never started or was killed. */
+	onExit?: (exitCode: number | null, stderr: string) => void;
+}
+
+function runDownload(
+	ytdlp: string,
+	args: string[],
+	prefix: string,
+	{ signal, onProgress, onExit }: RunDownloadHooks,
+): Promise<string> {
+	const { promise, resolve, reject } = Promise.withResolvers<string>();
+	const child = spawn(ytdlp, args);
+	killOnAbort(child, signal);
+	let filePath: string | undefined;
 	let stderr = "";
 
+	// yt-dlp writes progress to whichever stream it treats as the screen. A
+	// count that drops means the next stream of a merge has started.
+	let earlierStreams = 0;
+	let streamBytes = 0;
+	let percent = 0;
+	const reportProgress = (text: string) => {
+		for (const match of text.matchAll(PROGRESS_REGEX)) {
+			const downloaded = Number(match[1]);
+			if (downloaded < streamBytes) earlierStreams += streamBytes;
+			streamBytes = downloaded;
+			const total = Number(match[2]);
+			if (total > 0) percent = (downloaded / total) * 100;
+			onProgress?.(percent, earlierStreams + downloaded);
+		}
+	};
+
 	child.stdout.on("data", (chunk: Buffer) => {
-		const text = chunk.toString().trim();
+		const text = chunk.toString();
+		if (onProgress) reportProgress(text);
 		for (const line of text.split("\n")) {
 			const trimmed = line.trim();
-			if (!trimmed) continue;
-			stdoutLines.push(trimmed);
-			if (path.isAbsolute(trimmed)) {
-				destinations.push(trimmed);
-			}
+			if (path.isAbsolute(trimmed)) filePath = trimmed;
 		}
 	});
 
 	child.stderr.on("data", (chunk) => {
+		if (onProgress) reportProgress(chunk.toString());
 		stderr += chunk;
 	});
 
-	child.on("error", reject);
-	child.on("close", (code) => {
-		const filepath = stdoutLines.filter((l) => path.isAbsolute(l)).pop();
-
+	// `error` may be followed by `close`; report whichever comes first.
+	let exited = false;
+	const exit = (code: number | null) => {
+		if (!exited) onExit?.(code, stderr);
+		exited = true;
+	};
+	child.on("error", (error) => {
+		exit(null);
+		reject(spawnFailure(error));
+	});
+	child.on("close", async (code) => {
+		exit(code);
 		if (signal?.aborted) {
-			void removeFiles(destinations);
 			reject(new Error("Download cancelled."));
 			return;
 		}
-		if (code === 0 && filepath) {
-			const cleanup = async () => {
-				const filesToRemove = [filepath, ...destinations];
-				if (opts.infoJsonPath) filesToRemove.push(opts.infoJsonPath);
-				await removeFiles(filesToRemove);
-			};
-			resolve({ filePath: filepath, cleanup });
-		} else {
-			const filesToRemove = [...destinations];
-			if (opts.infoJsonPath) filesToRemove.push(opts.infoJsonPath);
-			void removeFiles(filesToRemove);
-			reject(new Error(cleanYtDlpError(stderr) || `Download failed (exit code ${code})`));
+		if (code === 0 && filePath) {
+			resolve(filePath);
+			return;
 		}
+		const ErrorClass = (await hasPartialOutput(prefix)) ? PartialDownloadError : YtDlpError;
+		reject(new ErrorClass(stderr, `Download failed (exit code ${code})`));
 	});
 
 	return promise;
 }
 
-function removeFiles(files: string[]): Promise<unknown> {
-	const set = new Set(files.flatMap((f) => [f, `${f}.part`, `${f}.ytdl`]));
-	return Promise.allSettled(Array.from(set).map((file) => fs.rm(file, { force: true })));
+async function tempFilesWithPrefix(prefix: string): Promise<string[]> {
+	const dir = os.tmpdir();
+	try {
+		const entries = await fs.readdir(dir);
+		return entries.filter((name) => name.startsWith(prefix)).map((name) => path.join(dir, name));
+	} catch {
+		return [];
+	}
+}
+
+async function hasPartialOutput(prefix: string): Promise<boolean> {
+	const sizes = await Promise.all(
+		(await tempFilesWithPrefix(prefix)).map((file) =>
+			fs.stat(file).then(
+				(st) => st.size,
+				() => 0,
+			),
+		),
+	);
+	return sizes.some((size) => size > 0);
+}
+
+async function removeTempFiles(prefix: string): Promise<void> {
+	const files = await tempFilesWithPrefix(prefix);
+	await Promise.allSettled(files.map((file) => fs.rm(file, { force: true })));
 }
 
 function cleanYtDlpError(stderr: string): string {
@@ -384,5 +1645,5 @@ function cleanYtDlpError(stderr: string): string {
 		.map((l) => l.trim())
 		.filter((l) => l.startsWith("ERROR:"));
 	const last = lines.at(-1);
-	return last ? last.replace(/^ERROR:\s*(\[[^\]]+\]\s*)?/, "") : stderr.trim();
+	return last ? last.replace(/^ERROR:\s*(\[[^\]]+\]\s*)?/, "") : "";
 }
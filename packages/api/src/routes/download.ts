@@ -1,19 +1,80 @@
 import { createReadStream } from "node:fs";
 import fs from "node:fs/promises";
 import path from "node:path";
-import { type ResolveResponse, validateUrl } from "@snatch/shared";
+import {
+	type BatchResolveItem,
+	type BatchResolveResponse,
+	type BestFormatResponse,
+	type DownloadJobResponse,
+	type ErrorCode,
+	extractVideoId,
+	isAllowedHost,
+	isProfileUrl,
+	isShortLink,
+	type MediaChoiceItem,
+	type MediaItem,
+	PLATFORM_EXAMPLES,
+	type PlatformInfo,
+	platformOf,
+	type ResolveResponse,
+	SERVICES,
+	stripFragment,
+	supportsProfiles,
+	type UrlValidation,
+	withDefaultScheme,
+} from "@snatch/shared";
 import { type Context, Hono } from "hono";
+import { env } from "hono/adapter";
 import { stream } from "hono/streaming";
+import type { z } from "zod";
+import { assertNotBlocked } from "../lib/blocklist";
+import { mapWithDeadline } from "../lib/concurrency";
+import { releaseCooldown, takeCooldown } from "../lib/cooldown";
+import { positiveInt, publicBaseUrl } from "../lib/env";
+import { describeFailure, ERROR_STATUS, errorCodeOf, errorResponse } from "../lib/errors";
+import { parseFieldList, pickFields } from "../lib/fields";
+import { type FilenameFields, renderFilename } from "../lib/filename";
+import { downloadJobs, type Job, type JobRunner } from "../lib/jobs";
+import { logger } from "../lib/logger";
+import { ogFallback, ogFallbackEnabled } from "../lib/og";
+import { canonicalMediaUrl, mediaKey, platformDomains } from "../lib/platforms";
+import { parseRange } from "../lib/range";
 import { sanitizeFilename, signUrl, verifyUrl } from "../lib/security";
+import { resolveShortLink } from "../lib/shortlink";
+import { callbackUrlProblem, deliverWebhook, webhookSecret } from "../lib/webhook";
 import {
 	buildChoices,
+	buildPostItems,
+	choiceWarnings,
+	describeMedia,
+	type DownloadChoice,
 	ensureYtDlp,
 	executeDownload,
+	type ExecuteDownloadOptions,
+	type ExtractionStats,
+	formatExpiry,
+	formatsLimit,
+	hasFfmpeg,
+	isLive,
+	listProfile,
+	liveCaptureArgs,
+	liveCaptureSecs,
 	parseVideoInfo,
 	probe,
+	probeTimeoutSecs,
+	signableChoices,
 	type VideoInfo,
+	YtDlpError,
 } from "../lib/ytdlp";
-import { mediaOptionsSchema, resolveInputSchema } from "../schemas/media";
+import {
+	asyncDownloadInputSchema,
+	batchResolveInputSchema,
+	directDownloadInputSchema,
+	mediaOptionsSchema,
+	resolveInputSchema,
+	type ResolveOptionsInput,
+	validateMediaUrl,
+} from "../schemas/media";
 
 const downloadRouter = new Hono();
 
@@ -41,7 +102,7 @@ function downloadPayload(p: DownloadParams): string {
 function generateDownloadUrl(
 	params: DownloadParams,
 	filename: string,
-	origin: string,
+	baseUrl: string,
 	c: Context,
 ): string {
 	const sig = signUrl(downloadPayload(params), c);
@@ -55,39 +116,177 @@ function generateDownloadUrl(
 		downloadMode: params.downloadMode ?? "",
 		sig,
 	});
-	return `${origin}/api/download?${query.toString()}`;
+	return `${baseUrl}/api/download?${query.toString()}`;
 }
 
 /**
- * POST /api/resolve
- * Resolve media URL formats using yt-dlp.
+ * Reject a body that failed schema validation. A failed URL check keeps its
+ * own code and status, plus the position of an `INVALID_CHAR`; anything else
+ * is `422 BAD_REQUEST_BODY` naming the offending field. Unknown keys are
+ * reported first: `{"uri": ...}` is a typo for `url`, not a missing URL.
  */
-downloadRouter.post("/api/resolve", async (c) => {
+function invalidBody(c: Context, error: z.ZodError) {
+	const issue =
+		error.issues.find((candidate) => candidate.code === "unrecognized_keys") ?? error.issues[0];
+	const params = issue?.code === "custom" ? issue.params : undefined;
+	const code = params?.code as ErrorCode | undefined;
+	if (code) {
+		return errorResponse(c, code, issue?.message ?? "Invalid URL", {
+			position: params?.position,
+			character: params?.character,
+		});
+	}
+	const path = issue?.code === "unrecognized_keys" ? [...issue.path, issue.keys[0]] : issue?.path;
+	return errorResponse(c, "BAD_REQUEST_BODY", issue?.message ?? "Invalid request body", {
+		field: path?.join("."),
+	});
+}
+
+/** Parse a JSON body against `schema`, or answer with the error response to send instead. */
+async function readBody<T extends z.ZodType>(
+	c: Context,
+	schema: T,
+): Promise<z.output<T> | Response> {
 	let raw: unknown;
 	try {
 		raw = await c.req.json();
 	} catch {
-		return c.json({ success: false, error: "Invalid JSON in request body" }, 400);
+		return errorResponse(c, "BAD_REQUEST_BODY", "Request body must be valid JSON");
 	}
+	const parsed = schema.safeParse(raw);
+	return parsed.success ? parsed.data : invalidBody(c, parsed.error);
+}
+
+/** Whether resolves carry `extractedAt`; `EXTRACTED_AT=false` drops it for minimal payloads. */
+function stampExtraction(): boolean {
+	return process.env.EXTRACTED_AT !== "false";
+}
 
-	const parsed = resolveInputSchema.safeParse(raw);
-	if (!parsed.success) {
-		return c.json(
-			{ success: false, error: parsed.error.issues[0]?.message ?? "Invalid request" },
-			400,
+/**
+ * Resolve one validated URL, logging a single `extraction finished` event
+ * with its platform, cost and outcome. The timing spans short-link expansion,
+ * every probe attempt and any fallback, i.e. everything the client waited for.
+ */
+async function resolveMedia(
+	c: Context,
+	sharedUrl: string,
+	options: ResolveOptionsInput,
+	signal = c.req.raw.signal,
+): Promise<ResolveResponse> {
+	const started = performance.now();
+	const stats: ExtractionStats = { attempts: 0, cacheHit: false };
+	let url = sharedUrl;
+	const logOutcome = (errorCode?: ErrorCode) =>
+		logger.info(
+			{
+				requestId: c.var.requestId,
+				platform: platformOf(url),
+				attempts: stats.attempts,
+				cacheHit: stats.cacheHit,
+				durationMs: Math.round(performance.now() - started),
+				outcome: errorCode ? "error" : "success",
+				errorCode,
+			},
+			"extraction finished",
 		);
+	try {
+		if (isShortLink(url)) url = await resolveShortLink(url, { signal });
+		// Short links are only checked once expanded.
+		assertNotBlocked(url);
+		const result = await extractMedia(c, url, options, signal, stats);
+		logOutcome();
+		const extractedAt = stats.extractedAt ?? Math.floor(Date.now() / 1000);
+		return {
+			...result,
+			platform: platformOf(url) ?? undefined,
+			videoId: extractVideoId(url)?.id,
+			...(stampExtraction() ? { extractedAt } : {}),
+		};
+	} catch (error) {
+		logOutcome(errorCodeOf(error));
+		throw error;
 	}
+}
 
-	const { url, ...options } = parsed.data;
-
+/** Probe one canonical URL and build its signed picker, or list a profile's uploads. */
+async function extractMedia(
+	c: Context,
+	url: string,
+	options: ResolveOptionsInput,
+	signal: AbortSignal,
+	stats: ExtractionStats,
+): Promise<ResolveResponse> {
+	const ytdlp = await ensureYtDlp(signal);
+	const timeoutMs = probeTimeoutSecs(options.timeoutSecs, platformOf(url)) * 1000;
+	if (isProfileUrl(url)) {
+		const entries = await listProfile(ytdlp, url, {
+			limit: options.entriesLimit,
+			signal,
+			timeoutMs,
+			stats,
+			requestId: c.var.requestId,
+			lang: options.lang,
+		});
+		return { status: "list", entries };
+	}
+	let probed: Awaited<ReturnType<typeof probe>>;
 	try {
-		const ytdlp = await ensureYtDlp(c.req.raw.signal);
-		const { info, infoJsonPath } = await probe(ytdlp, url, c.req.raw.signal);
-		const choices = buildChoices(info, options);
-		const origin = new URL(c.req.url).origin;
-		const titleBase = (info.title || "media").slice(0, 50);
+		probed = await probe(ytdlp, url, {
+			signal,
+			timeoutMs,
+			stats,
+			requestId: c.var.requestId,
+			lang: options.lang,
+		});
+	} catch (error) {
+		const fallback = await openGraphFallback(url, error, signal);
+		if (fallback) return fallback;
+		throw error;
+	}
+	const { info, infoJsonPath } = probed;
+	const limit = formatsLimit(options.formatsLimit);
+	const choiceOptions = { ...options, canMerge: await hasFfmpeg() };
+	const choices = buildChoices(info, choiceOptions, limit);
+	const fields = filenameFields(info, url);
+	const titleBase = fields.title;
+	const toPicker = signedPicker(c, url, infoJsonPath, options, fields);
+
+	const items: MediaItem[] = buildPostItems(info, choiceOptions, limit).map((item) => ({
+		index: item.index,
+		mediaType: item.mediaType,
+		title: item.info.title || undefined,
+		thumbnail: item.info.thumbnail,
+		...(item.choices.length
+			? { picker: toPicker(item.choices, `${titleBase}-${item.index}`, item.info.thumbnail) }
+			: {}),
+	}));
 
-		const picker = choices.map((choice) => ({
+	const warnings = choiceWarnings(info, choices);
+	return {
+		status: "picker",
+		...describeMedia(info),
+		filename: renderFilename({ ...fields, ext: "mp4" }),
+		picker: toPicker(choices, titleBase, info.thumbnail),
+		...(items.length ? { items } : {}),
+		...(warnings.length ? { warnings } : {}),
+	};
+}
+
+/**
+ * Turns a probe's choices into picker entries whose `url` is a signed
+ * `/api/download` link to the probe cached at `infoJsonPath`.
+ */
+function signedPicker(
+	c: Context,
+	url: string,
+	infoJsonPath: string,
+	options: ResolveOptionsInput,
+	fields: Omit<FilenameFields, "ext">,
+) {
+	const baseUrl = publicBaseUrl() ?? new URL(c.req.url).origin;
+	return (list: DownloadChoice[], title: string, thumb?: string): MediaChoiceItem[] =>
+		list.map((choice) => ({
+			...choice.details,
 			id: choice.id,
 			type: choice.kind,
 			quality: choice.quality,
@@ -102,56 +301,320 @@ downloadRouter.post("/api/resolve", async (c) => {
 					videoQuality: options.videoQuality,
 					downloadMode: options.downloadMode,
 				},
-				`${titleBase}.${choice.ext}`,
-				origin,
+				renderFilename({ ...fields, title, ext: choice.ext }),
+				baseUrl,
 				c,
 			),
-			thumb: info.thumbnail,
+			thumb,
 		}));
+}
 
-		const response: ResolveResponse = {
-			status: "picker",
-			title: info.title,
-			thumbnail: info.thumbnail,
-			duration: info.duration,
-			filename: `${titleBase}.mp4`,
-			picker,
-		};
+/** {@link renderFilename} fields for a probed `url`, all but the per-choice `ext`. */
+function filenameFields(info: VideoInfo, url: string): Omit<FilenameFields, "ext"> {
+	return {
+		title: (info.title || "media").slice(0, 50),
+		uploader: info.uploader,
+		id: info.id || undefined,
+		platform: platformOf(url) ?? undefined,
+	};
+}
 
-		return c.json(response);
-	} catch (error) {
-		const msg = error instanceof Error ? error.message : "Resolution failed";
-		return c.json(
+/**
+ * Degraded resolve for an extractor that yt-dlp cannot handle right now: a
+ * single choice linking to the page's `og:video`. Only generic extractor
+ * failures qualify; private, removed or geo-blocked media stays an error.
+ */
+async function openGraphFallback(
+	url: string,
+	error: unknown,
+	signal: AbortSignal,
+): Promise<ResolveResponse | null> {
+	if (!ogFallbackEnabled() || !(error instanceof YtDlpError) || error.code !== "YTDLP_FAILED") {
+		return null;
+	}
+	const og = await ogFallback(url, { signal });
+	if (!og) return null;
+	logger.warn({ url, err: error }, "yt-dlp failed, serving Open Graph fallback");
+	return {
+		status: "picker",
+		source: "og-fallback",
+		title: og.title,
+		thumbnail: og.thumbnail,
+		picker: [
 			{
-				status: "error",
-				error: { code: "api.resolve_failed", message: msg },
+				id: "og",
+				type: "video",
+				ext: "mp4",
+				label: "Original",
+				url: og.videoUrl,
+				thumb: og.thumbnail,
+				expiresAt: formatExpiry(og.videoUrl),
 			},
-			200,
+		],
+	};
+}
+
+/**
+ * POST /api/resolve
+ * Resolve media URL formats using yt-dlp. An optional `fields` list (body or
+ * query) trims the success payload to those keys; `status` is always kept.
+ */
+downloadRouter.post("/api/resolve", async (c) => {
+	const body = await readBody(c, resolveInputSchema);
+	if (body instanceof Response) return body;
+
+	const { url, fields, ...options } = body;
+	const fieldList = parseFieldList(fields ?? c.req.query("fields"));
+	try {
+		return c.json(pickFields(await resolveMedia(c, url, options), fieldList), 200);
+	} catch (error) {
+		const failure = describeFailure(error, "Resolution failed");
+		return c.json({ status: "error", error: failure }, ERROR_STATUS[failure.code]);
+	}
+});
+
+/** Parallel probes per batch while `EXTRACT_CONCURRENCY` is unset; each is a yt-dlp process. */
+const DEFAULT_BATCH_CONCURRENCY = 3;
+/** Default wall-clock budget for a whole batch; unfinished items become `TIMEOUT` errors. */
+const DEFAULT_BATCH_TIMEOUT_MS = 60_000;
+/** Default budget for one batch URL, so a single hanging link cannot eat the whole batch's. */
+const DEFAULT_BATCH_ITEM_TIMEOUT_MS = 45_000;
+
+/**
+ * POST /api/resolve/batch
+ * Resolve several URLs in one round trip. Each URL is validated and probed
+ * independently, so one bad link only fails its own entry in `results`.
+ */
+downloadRouter.post("/api/resolve/batch", async (c) => {
+	const body = await readBody(c, batchResolveInputSchema);
+	if (body instanceof Response) return body;
+
+	const envVars = env(c);
+	// A batch fans out as wide as the process-wide extraction slots, no wider.
+	const concurrency = positiveInt(process.env.EXTRACT_CONCURRENCY) ?? DEFAULT_BATCH_CONCURRENCY;
+	const timeoutMs = positiveInt(envVars.BATCH_TIMEOUT_MS) ?? DEFAULT_BATCH_TIMEOUT_MS;
+	const itemTimeoutMs =
+		positiveInt(envVars.BATCH_ITEM_TIMEOUT_MS) ?? DEFAULT_BATCH_ITEM_TIMEOUT_MS;
+	const deadline = AbortSignal.any([c.req.raw.signal, AbortSignal.timeout(timeoutMs)]);
+
+	const { urls, ...options } = body;
+	const results = await mapWithDeadline(
+		urls.map((rawUrl) => withDefaultScheme(stripFragment(rawUrl), platformDomains())),
+		concurrency,
+		deadline,
+		async (url, _index, signal): Promise<BatchResolveItem> => {
+			const validation: UrlValidation = isShortLink(url) ? { valid: true } : validateMediaUrl(url);
+			if (!validation.valid) {
+				return {
+					url,
+					status: "error",
+					error: {
+						code: validation.code ?? "INVALID_URL",
+						message: validation.error ?? "Invalid URL",
+						...(validation.position !== undefined
+							? { context: { position: validation.position, character: validation.character } }
+							: {}),
+					},
+				};
+			}
+			try {
+				return { url, ...(await resolveMedia(c, canonicalMediaUrl(url), options, signal)) };
+			} catch (error) {
+				return { url, status: "error", error: describeFailure(error, "Resolution failed") };
+			}
+		},
+		(url) => ({
+			url,
+			status: "error",
+			error: {
+				code: "TIMEOUT",
+				message: deadline.aborted
+					? `Batch did not finish within ${timeoutMs} ms`
+					: `URL did not resolve within ${itemTimeoutMs} ms`,
+			},
+		}),
+		itemTimeoutMs,
+	);
+
+	const response: BatchResolveResponse = { results };
+	return c.json(response, 200);
+});
+
+/** Default minimum gap between two refreshes of the same URL. */
+const DEFAULT_REFRESH_INTERVAL_MS = 10_000;
+
+/** What a refresh returns: the renewed links, nothing that cannot have changed. */
+type RefreshResponse = Pick<ResolveResponse, "status" | "picker" | "items" | "warnings">;
+
+/**
+ * The fast path behind `POST /api/refresh`: probe `sharedUrl` again and sign
+ * fresh choices for it and its carousel entries. Unlike {@link resolveMedia}
+ * it builds no metadata or thumbnails and never falls back to Open Graph,
+ * whose unsigned link is not something a refresh can renew.
+ */
+async function refreshChoices(
+	c: Context,
+	sharedUrl: string,
+	options: ResolveOptionsInput,
+): Promise<RefreshResponse> {
+	const signal = c.req.raw.signal;
+	const url = isShortLink(sharedUrl) ? await resolveShortLink(sharedUrl, { signal }) : sharedUrl;
+	assertNotBlocked(url);
+	const ytdlp = await ensureYtDlp(signal);
+	const { info, infoJsonPath } = await probe(ytdlp, url, {
+		signal,
+		timeoutMs: probeTimeoutSecs(options.timeoutSecs, platformOf(url)) * 1000,
+		requestId: c.var.requestId,
+		lang: options.lang,
+	});
+	const limit = formatsLimit(options.formatsLimit);
+	const choiceOptions = { ...options, canMerge: await hasFfmpeg() };
+	const choices = buildChoices(info, choiceOptions, limit);
+	const fields = filenameFields(info, url);
+	const toPicker = signedPicker(c, url, infoJsonPath, options, fields);
+	const items: MediaItem[] = buildPostItems(info, choiceOptions, limit).map((item) => ({
+		index: item.index,
+		mediaType: item.mediaType,
+		...(item.choices.length
+			? { picker: toPicker(item.choices, `${fields.title}-${item.index}`) }
+			: {}),
+	}));
+	const warnings = choiceWarnings(info, choices);
+	return {
+		status: "picker",
+		picker: toPicker(choices, fields.title),
+		...(items.length ? { items } : {}),
+		...(warnings.length ? { warnings } : {}),
+	};
+}
+
+/**
+ * POST /api/refresh
+ * Re-probe a post whose signed links expired (see `expiresAt`) and return
+ * only its fresh `picker`/`items` (see {@link refreshChoices}). Each URL can be refreshed once per
+ * `REFRESH_INTERVAL_MS` across all clients; sooner is a 429 with `Retry-After`
+ * and the same number of seconds as `retryAfter` in the body.
+ * A failed refresh does not count, so the client can retry it straight away.
+ */
+downloadRouter.post("/api/refresh", async (c) => {
+	const body = await readBody(c, resolveInputSchema);
+	if (body instanceof Response) return body;
+
+	const { url, ...options } = body;
+	if (isProfileUrl(url)) {
+		return errorResponse(c, "BAD_REQUEST", "Profile listings have no links to refresh");
+	}
+
+	const intervalMs = positiveInt(env(c).REFRESH_INTERVAL_MS) ?? DEFAULT_REFRESH_INTERVAL_MS;
+	const key = mediaKey(url);
+	const takenAt = Date.now();
+	const waitMs = takeCooldown(key, intervalMs, takenAt);
+	if (waitMs > 0) {
+		const retryAfter = Math.ceil(waitMs / 1000);
+		c.header("Retry-After", retryAfter.toString());
+		return errorResponse(
+			c,
+			"RATE_LIMITED",
+			`This URL was just refreshed. Please try again in ${retryAfter} seconds.`,
+			{ retryAfter },
 		);
 	}
+
+	try {
+		return c.json(await refreshChoices(c, url, options), 200);
+	} catch (error) {
+		releaseCooldown(key, takenAt);
+		const failure = describeFailure(error, "Refresh failed");
+		return c.json({ status: "error", error: failure }, ERROR_STATUS[failure.code]);
+	}
 });
 
 /**
- * GET /api/download
- * Execute yt-dlp download for selected format choice and stream file to client.
+ * The best video choice of a resolved picker: the first one, as `buildChoices`
+ * sorts by height, that stands for a probed format or an Open Graph video
+ * rather than a blind `v-best` guess.
  */
-downloadRouter.get("/api/download", async (c) => {
-	const url = c.req.query("url");
-	const choiceId = c.req.query("choiceId");
-	const infoJsonPath = c.req.query("infoJson");
-	const signature = c.req.query("sig");
-	const requestedFilename = c.req.query("filename");
-	const audioFormat = c.req.query("audioFormat") ?? "";
-	const videoQuality = c.req.query("videoQuality") ?? "";
-	const downloadMode = c.req.query("downloadMode") ?? "";
+function bestVideoChoice(resolved: ResolveResponse): MediaChoiceItem | undefined {
+	return resolved.picker?.find(
+		(choice) => choice.type === "video" && (choice.formatId || resolved.source === "og-fallback"),
+	);
+}
+
+/**
+ * POST /api/best
+ * Resolve like `/api/resolve` (same body) but answer only the best video
+ * choice as `{url, ext, quality, filesize}`, for clients that just play it.
+ * Media without a video format is a 404.
+ */
+downloadRouter.post("/api/best", async (c) => {
+	const body = await readBody(c, resolveInputSchema);
+	if (body instanceof Response) return body;
+
+	const { url, ...options } = body;
+	if (isProfileUrl(url)) {
+		return errorResponse(c, "BAD_REQUEST", "Profile pages list uploads; resolve them instead");
+	}
+
+	try {
+		const best = bestVideoChoice(await resolveMedia(c, url, options));
+		if (!best) return errorResponse(c, "NOT_FOUND", "No video format is available for this URL");
+		const response: BestFormatResponse = {
+			url: best.url,
+			ext: best.ext,
+			quality: best.quality,
+			filesize: best.filesize,
+		};
+		return c.json(response, 200);
+	} catch (error) {
+		const failure = describeFailure(error, "Resolution failed");
+		return c.json({ status: "error", error: failure }, ERROR_STATUS[failure.code]);
+	}
+});
+
+/** Headroom on top of a live capture's duration for yt-dlp startup and muxing. */
+const LIVE_CAPTURE_GRACE_SECS = 30;
+
+/** Download params as they arrive in the signed query string or an async-job body. */
+type RawDownloadParams = Record<string, string | undefined>;
+
+/** A verified download, ready for {@link executeDownload}. */
+interface PreparedDownload {
+	run: ExecuteDownloadOptions;
+	choice: DownloadChoice;
+	/** Client-requested filename; empty means "use the file yt-dlp wrote". */
+	filename: string;
+	/** Wall-clock stop for an opted-in live capture. */
+	deadline?: AbortSignal;
+}
+
+/**
+ * Verify a signed download request and work out what to run. Rejections come
+ * back as a ready error response; engine failures are thrown.
+ */
+async function prepareDownload(
+	c: Context,
+	params: RawDownloadParams,
+	signal: AbortSignal,
+): Promise<PreparedDownload | Response> {
+	const { url, choiceId, infoJson: infoJsonPath, sig: signature } = params;
+	const audioFormat = params.audioFormat ?? "";
+	const videoQuality = params.videoQuality ?? "";
+	const downloadMode = params.downloadMode ?? "";
+	// Live capture is an explicit, unsigned opt-in: it only changes how long
+	// the server records, never which file or format is fetched.
+	const allowLive = params.allowLive === "true";
+	const maxDuration = positiveInt(params.maxDuration);
 
 	if (!url || !choiceId || !infoJsonPath || !signature) {
-		return c.json({ success: false, error: "Missing required download parameters" }, 400);
+		return errorResponse(c, "BAD_REQUEST", "Missing required download parameters");
 	}
 
-	const validation = validateUrl(url);
+	const validation = validateMediaUrl(url);
 	if (!validation.valid) {
-		return c.json({ success: false, error: validation.error }, 400);
+		return errorResponse(c, validation.code ?? "INVALID_URL", validation.error ?? "Invalid URL", {
+			position: validation.position,
+			character: validation.character,
+		});
 	}
 
 	// Signature is mandatory: it covers the info-json filesystem path and the
@@ -168,69 +631,319 @@ downloadRouter.get("/api/download", async (c) => {
 		downloadMode,
 	});
 	if (!verifyUrl(payload, signature, c)) {
-		return c.json({ success: false, error: "Invalid download signature" }, 403);
+		return errorResponse(c, "INVALID_SIGNATURE", "Invalid download signature");
 	}
 
 	// Signature is verified; still validate the carried values at this boundary.
 	const parsedOptions = mediaOptionsSchema.safeParse({ audioFormat, videoQuality, downloadMode });
 	if (!parsedOptions.success) {
-		return c.json({ success: false, error: "Invalid download options" }, 400);
+		return errorResponse(c, "BAD_REQUEST", "Invalid download options");
 	}
 	const options = parsedOptions.data;
+	if (params.maxDuration !== undefined && maxDuration === undefined) {
+		return errorResponse(c, "BAD_REQUEST", "maxDuration must be a positive number of seconds");
+	}
+
+	const ytdlp = await ensureYtDlp(signal);
 
+	// The signed URL always carries an info-json path; reuse it, falling
+	// back to a fresh probe only if the cached file is gone or unreadable.
+	let info: VideoInfo | undefined;
+	let infoJsonToUse = infoJsonPath;
 	try {
-		const ytdlp = await ensureYtDlp(c.req.raw.signal);
-
-		// The signed URL always carries an info-json path; reuse it, falling
-		// back to a fresh probe only if the cached file is gone or unreadable.
-		let info: VideoInfo | undefined;
-		let infoJsonToUse = infoJsonPath;
-		try {
-			info = parseVideoInfo(await fs.readFile(infoJsonPath, "utf-8"));
-		} catch {
-			const probed = await probe(ytdlp, url, c.req.raw.signal);
-			info = probed.info;
-			infoJsonToUse = probed.infoJsonPath;
-		}
+		info = parseVideoInfo(await fs.readFile(infoJsonPath, "utf-8"));
+	} catch {
+		const probed = await probe(ytdlp, url, { signal, requestId: c.var.requestId });
+		info = probed.info;
+		infoJsonToUse = probed.infoJsonPath;
+	}
 
-		const choices = buildChoices(info, options);
-		const selectedChoice = choices.find((ch) => ch.id === choiceId);
-		if (!selectedChoice) {
-			return c.json({ success: false, error: "Requested format is no longer available" }, 409);
-		}
+	const choices = signableChoices(info, { ...options, canMerge: await hasFfmpeg() });
+	const choice = choices.find((ch) => ch.id === choiceId);
+	if (!choice) {
+		return errorResponse(c, "FORMAT_UNAVAILABLE", "Requested format is no longer available");
+	}
 
-		const { filePath, cleanup } = await executeDownload(
-			{
-				ytdlp,
-				url,
-				infoJsonPath: infoJsonToUse,
-				args: selectedChoice.args,
-			},
-			c.req.raw.signal,
+	const run: ExecuteDownloadOptions = {
+		ytdlp,
+		url,
+		infoJsonPath: infoJsonToUse,
+		args: choice.args,
+		requestId: c.var.requestId,
+	};
+	const filename = params.filename ?? "";
+	if (!isLive(info)) return { run, choice, filename };
+	if (!allowLive) {
+		return errorResponse(
+			c,
+			"LIVE_CONTENT",
+			"This is a live stream; pass allowLive=true to record a capped capture",
 		);
+	}
+	// ffmpeg stops itself after `secs`; the deadline is our own backstop
+	// for streams that stall instead of producing frames.
+	const secs = liveCaptureSecs(maxDuration);
+	return {
+		run: { ...run, args: [...run.args, ...liveCaptureArgs(secs)] },
+		choice,
+		filename,
+		deadline: AbortSignal.timeout((secs + LIVE_CAPTURE_GRACE_SECS) * 1000),
+	};
+}
 
-		const stat = await fs.stat(filePath);
-		const filename = sanitizeFilename(
-			requestedFilename || path.basename(filePath) || "download.mp4",
-		);
+interface SpooledFile {
+	filePath: string;
+	cleanup: () => Promise<void>;
+	filename: string;
+	contentType: string;
+}
+
+/** Stream a file yt-dlp has spooled to disk, honouring `Range`, and delete it afterwards. */
+async function sendFile(c: Context, file: SpooledFile): Promise<Response> {
+	const { filePath, cleanup } = file;
+	const stat = await fs.stat(filePath);
+	const filename = sanitizeFilename(file.filename || path.basename(filePath) || "download.mp4");
+
+	c.header("Content-Type", file.contentType);
+	c.header("Content-Disposition", `attachment; filename="${filename}"`);
+	c.header("Accept-Ranges", "bytes");
 
-		c.header("Content-Type", contentTypeFor(selectedChoice.kind, selectedChoice.ext));
-		c.header("Content-Disposition", `attachment; filename="${filename}"`);
-		c.header("Content-Length", String(stat.size));
+	// yt-dlp has already spooled the whole file, so a resumed or seeking
+	// client is served the requested slice of it.
+	const range = parseRange(c.req.header("Range"), stat.size);
+	if (range === "unsatisfiable") {
+		await cleanup();
+		c.header("Content-Range", `bytes */${stat.size}`);
+		return errorResponse(c, "RANGE_NOT_SATISFIABLE", "Requested range not satisfiable");
+	}
+	const expected = range ? range.end - range.start + 1 : stat.size;
+	c.header("Content-Length", String(expected));
+	if (range) {
+		c.status(206);
+		c.header("Content-Range", `bytes ${range.start}-${range.end}/${stat.size}`);
+	}
 
-		const readStream = createReadStream(filePath);
-		return stream(c, async (s) => {
+	const readStream = createReadStream(filePath, range ?? {});
+	return stream(
+		c,
+		async (s) => {
+			let sent = 0;
 			try {
 				for await (const chunk of readStream) {
 					await s.write(chunk as Uint8Array);
+					sent += (chunk as Uint8Array).byteLength;
 				}
 			} finally {
 				await cleanup();
 			}
+			// Throwing leaves the body shorter than the declared Content-Length,
+			// so the client sees a failed transfer instead of a complete file.
+			if (sent !== expected) {
+				throw new Error(`Short write: sent ${sent} of ${expected} bytes`);
+			}
+		},
+		async (err) => {
+			logger.error({ err, filename }, "download stream ended early");
+		},
+	);
+}
+
+/**
+ * GET /api/download
+ * Execute yt-dlp download for selected format choice and stream file to client.
+ */
+downloadRouter.get("/api/download", async (c) => {
+	let deadline: AbortSignal | undefined;
+	try {
+		const prepared = await prepareDownload(c, c.req.query(), c.req.raw.signal);
+		if (prepared instanceof Response) return prepared;
+		deadline = prepared.deadline;
+
+		const signal = deadline ? AbortSignal.any([c.req.raw.signal, deadline]) : c.req.raw.signal;
+		const { filePath, cleanup } = await executeDownload(prepared.run, signal);
+		return await sendFile(c, {
+			filePath,
+			cleanup,
+			filename: prepared.filename,
+			contentType: contentTypeFor(prepared.choice.kind, prepared.choice.ext),
+		});
+	} catch (error) {
+		if (deadline?.aborted) {
+			return errorResponse(c, "TIMEOUT", "Live capture did not finish in time");
+		}
+		const { code, message, debug } = describeFailure(error, "Download execution failed");
+		return errorResponse(c, code, message, { debug });
+	}
+});
+
+/** The `format` choice by id; by default the audio choice or else the best video. */
+function directChoice(
+	choices: DownloadChoice[],
+	format?: string,
+	audioOnly?: boolean,
+): DownloadChoice | undefined {
+	if (format) return choices.find((ch) => ch.id === format);
+	return audioOnly ? choices.find((ch) => ch.kind === "audio") : choices[0];
+}
+
+/**
+ * POST /api/download
+ * One-shot download for simple clients: probe the JSON body's `url`, pick the
+ * `format` choice from the same `buildChoices()` list the picker is signed
+ * from, and stream it like `GET /api/download`. Nothing is signed because the
+ * server makes every choice itself. Live streams and profiles need a resolve.
+ */
+downloadRouter.post("/api/download", async (c) => {
+	const body = await readBody(c, directDownloadInputSchema);
+	if (body instanceof Response) return body;
+
+	const { url: sharedUrl, format, audioOnly, filename, ...options } = body;
+	const signal = c.req.raw.signal;
+	try {
+		const url = isShortLink(sharedUrl) ? await resolveShortLink(sharedUrl, { signal }) : sharedUrl;
+		assertNotBlocked(url);
+		if (isProfileUrl(url)) {
+			return errorResponse(c, "BAD_REQUEST", "Profile pages list uploads; resolve them instead");
+		}
+
+		const ytdlp = await ensureYtDlp(signal);
+		const { info, infoJsonPath } = await probe(ytdlp, url, { signal, requestId: c.var.requestId });
+		if (isLive(info)) {
+			return errorResponse(
+				c,
+				"LIVE_CONTENT",
+				"This is a live stream; resolve it and download with allowLive=true",
+			);
+		}
+
+		const choices = buildChoices(info, { ...options, canMerge: await hasFfmpeg() });
+		const choice = directChoice(choices, format, audioOnly);
+		if (!choice) {
+			return errorResponse(c, "FORMAT_UNAVAILABLE", `Format "${format}" is not available`);
+		}
+		if (audioOnly && choice.kind !== "audio") {
+			return errorResponse(c, "BAD_REQUEST", `audioOnly conflicts with video format "${format}"`);
+		}
+
+		const { filePath, cleanup } = await executeDownload(
+			{ ytdlp, url, infoJsonPath, args: choice.args, requestId: c.var.requestId },
+			signal,
+		);
+		return await sendFile(c, {
+			filePath,
+			cleanup,
+			filename: filename ?? renderFilename({ ...filenameFields(info, url), ext: choice.ext }),
+			contentType: contentTypeFor(choice.kind, choice.ext),
+		});
+	} catch (error) {
+		const { code, message, debug } = describeFailure(error, "Download execution failed");
+		return errorResponse(c, code, message, { debug });
+	}
+});
+
+function jobResponse(job: Job): DownloadJobResponse {
+	return {
+		jobId: job.id,
+		status: job.status,
+		progress: Math.round(job.progress),
+		...(job.filename ? { filename: job.filename } : {}),
+		...(job.size !== undefined ? { size: job.size } : {}),
+		...(job.error ? { error: job.error } : {}),
+		expiresAt: job.expiresAt,
+	};
+}
+
+/**
+ * POST /api/download/async
+ * Start a download in the background and answer `202` with a job to poll.
+ * The JSON body carries the same signed params as `GET /api/download`, plus
+ * an optional `callbackUrl` that is POSTed the job once it settles.
+ */
+downloadRouter.post("/api/download/async", async (c) => {
+	const body = await readBody(c, asyncDownloadInputSchema);
+	if (body instanceof Response) return body;
+
+	const { callbackUrl, ...params } = body;
+	if (callbackUrl !== undefined) {
+		const problem = webhookSecret()
+			? callbackUrlProblem(callbackUrl)
+			: "Webhook callbacks are not enabled on this server";
+		if (problem) return errorResponse(c, "BAD_REQUEST", problem, { field: "callbackUrl" });
+	}
+
+	try {
+		const prepared = await prepareDownload(c, params, c.req.raw.signal);
+		if (prepared instanceof Response) return prepared;
+
+		const { run, deadline } = prepared;
+		const contentType = contentTypeFor(prepared.choice.kind, prepared.choice.ext);
+		const runner: JobRunner = async (signal, onProgress) => {
+			try {
+				const jobSignal = deadline ? AbortSignal.any([signal, deadline]) : signal;
+				return await executeDownload({ ...run, onProgress }, jobSignal);
+			} catch (error) {
+				if (deadline?.aborted) {
+					throw new YtDlpError("", "Live capture did not finish in time", "TIMEOUT");
+				}
+				throw error;
+			}
+		};
+		const onSettled = callbackUrl
+			? (settled: Job) => void deliverWebhook(callbackUrl, jobResponse(settled))
+			: undefined;
+		const job = downloadJobs().start(
+			{ filename: prepared.filename, contentType },
+			runner,
+			onSettled,
+		);
+		return c.json(jobResponse(job), 202);
+	} catch (error) {
+		const { code, message, debug } = describeFailure(error, "Download execution failed");
+		return errorResponse(c, code, message, { debug });
+	}
+});
+
+/**
+ * GET /api/download/status/:jobId
+ * Poll a background download.
+ */
+downloadRouter.get("/api/download/status/:jobId", (c) => {
+	const job = downloadJobs().get(c.req.param("jobId"));
+	if (!job) {
+		return errorResponse(c, "NOT_FOUND", "Unknown or expired download job");
+	}
+	return c.json(jobResponse(job), 200);
+});
+
+/**
+ * GET /api/download/result/:jobId
+ * Stream a finished background download. Retrieval is single-use: the job
+ * and its file are gone once the response ends.
+ */
+downloadRouter.get("/api/download/result/:jobId", async (c) => {
+	const jobs = downloadJobs();
+	const jobId = c.req.param("jobId");
+	const job = jobs.get(jobId);
+	if (!job) {
+		return errorResponse(c, "NOT_FOUND", "Unknown or expired download job");
+	}
+	if (job.status === "failed" && job.error) {
+		return errorResponse(c, job.error.code, job.error.message, { debug: job.error.debug });
+	}
+	const finished = jobs.take(jobId);
+	if (!finished?.output) {
+		return errorResponse(c, "JOB_NOT_READY", "Download is still in progress");
+	}
+	try {
+		return await sendFile(c, {
+			...finished.output,
+			filename: finished.filename,
+			contentType: finished.contentType,
 		});
 	} catch (error) {
-		const msg = error instanceof Error ? error.message : "Download execution failed";
-		return c.json({ success: false, error: msg }, 500);
+		await finished.output.cleanup();
+		const { code, message, debug } = describeFailure(error, "Download execution failed");
+		return errorResponse(c, code, message, { debug });
 	}
 });
 
@@ -246,6 +959,31 @@ function contentTypeFor(kind: "video" | "audio", ext: string): string {
 	return "video/mp4";
 }
 
+/**
+ * GET /api/platforms
+ * The services this deployment accepts links from: `SERVICES` narrowed to the
+ * hosts `platformDomains()` admits, so clients never drift from `SUPPORTED_PLATFORMS`.
+ */
+downloadRouter.get("/api/platforms", async (c) => {
+	const domains = platformDomains();
+	const supportsAudioOnly = await hasFfmpeg();
+	const platforms = SERVICES.flatMap((service): PlatformInfo[] => {
+		const hosts = service.hosts.filter((host) => isAllowedHost(host, domains));
+		if (hosts.length === 0) return [];
+		return [
+			{
+				id: service.id,
+				name: service.label,
+				domains: hosts,
+				exampleUrl: PLATFORM_EXAMPLES[service.id],
+				supportsAudioOnly,
+				supportsProfiles: supportsProfiles(service.id),
+			},
+		];
+	});
+	return c.json(platforms);
+});
+
 /**
  * GET /api/info
  * Query engine status.
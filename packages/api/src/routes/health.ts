@@ -1,7 +1,62 @@
-import { Hono } from "hono";
+import { type Context, Hono } from "hono";
+import { extractionQueue } from "../lib/queue";
+import { installedYtDlp } from "../lib/ytdlp";
 
 const healthRouter = new Hono();
 
-healthRouter.get("/health", (c) => c.text("OK"));
+/** How long a readiness result is reused, so probes don't spawn yt-dlp every time. */
+const READY_CACHE_MS = 3 * 60_000;
+
+let ytDlpCheck: { at: number; result: ReturnType<typeof installedYtDlp> } | undefined;
+
+/** Forget the cached yt-dlp check (for tests). */
+export function clearReadinessCache(): void {
+	ytDlpCheck = undefined;
+}
+
+function checkYtDlp(): ReturnType<typeof installedYtDlp> {
+	if (!ytDlpCheck || Date.now() - ytDlpCheck.at > READY_CACHE_MS) {
+		ytDlpCheck = { at: Date.now(), result: installedYtDlp() };
+	}
+	return ytDlpCheck.result;
+}
+
+/** The `ytdlp` field both health endpoints report: whether it runs, and its version. */
+async function ytDlpStatus(): Promise<{ available: boolean; version?: string }> {
+	const installed = await checkYtDlp();
+	return installed ? { available: true, version: installed.version } : { available: false };
+}
+
+/**
+ * Liveness: always 200 while the process is up, so a missing yt-dlp never
+ * gets the container restarted. The body reports uptime, whether yt-dlp
+ * runs and, when `EXTRACT_CONCURRENCY` is set, the extraction queue's load,
+ * for humans and dashboards.
+ */
+healthRouter.get("/health", async (c) => {
+	const queue = extractionQueue();
+	return c.json({
+		status: "ok",
+		uptimeSecs: Math.floor(process.uptime()),
+		ytdlp: await ytDlpStatus(),
+		...(queue ? { queue: { running: queue.running, waiting: queue.length } } : {}),
+	});
+});
+
+/**
+ * Readiness: 200 only when yt-dlp can actually be invoked, 503 otherwise, so
+ * an orchestrator stops routing to an instance that cannot resolve anything.
+ * Served at `/ready` too, for probes configured with the short path.
+ */
+async function readiness(c: Context) {
+	const ytdlp = await ytDlpStatus();
+	return c.json(
+		{ status: ytdlp.available ? "ready" : "unavailable", ytdlp },
+		ytdlp.available ? 200 : 503,
+	);
+}
+
+healthRouter.get("/health/ready", readiness);
+healthRouter.get("/ready", readiness);
 
 export { healthRouter };
@@ -1,12 +1,59 @@
-import { AUDIO_FORMATS, DOWNLOAD_MODES, VIDEO_QUALITIES, validateUrl } from "@snatch/shared";
+import {
+	AUDIO_FORMATS,
+	detectCdnPlatform,
+	DOWNLOAD_MODES,
+	isShortLink,
+	LANGUAGE_TAG_REGEX,
+	MAX_BATCH_URLS,
+	MAX_FORMATS_LIMIT,
+	MAX_PROFILE_ENTRIES,
+	stripFragment,
+	type UrlValidation,
+	VIDEO_QUALITIES,
+	withDefaultScheme,
+} from "@snatch/shared";
 import { z } from "zod";
+import { isBlockedContent, isBlockedUrl } from "../lib/blocklist";
+import { canonicalMediaUrl, platformDomains, validatePlatformUrl } from "../lib/platforms";
 
 /**
  * Zod schemas for the untrusted request boundary. Kept here (not in
  * `@snatch/shared`, which stays dependency-free) and layered over the pure
- * `validateUrl` host allowlist so validation logic lives in one place.
+ * `validateUrl` host allowlist (as configured by `SUPPORTED_PLATFORMS`, see
+ * `validatePlatformUrl`) so validation logic lives in one place. JSON
+ * bodies are strict, so a typo'd key fails loudly instead of being ignored.
  */
 
+/**
+ * `validatePlatformUrl`, plus direct media links on a platform's trusted CDN (see
+ * `TRUSTED_CDN_HOSTS`) when `DIRECT_CDN_URLS=true`. Off by default: yt-dlp's
+ * generic extractor will fetch any URL it is given, so the CDN list is the
+ * only thing keeping this from being an open proxy. An allowed URL matching
+ * `BLOCKED_PATTERNS` is then refused with `BLOCKED`, and one listed in
+ * `BLOCKLIST_FILE` with `BLOCKED_CONTENT`.
+ */
+export function validateMediaUrl(url: string): UrlValidation {
+	const result = allowedMediaUrl(url);
+	if (!result.valid) return result;
+	if (isBlockedUrl(url)) {
+		return { valid: false, error: "This URL is blocked on this server", code: "BLOCKED" };
+	}
+	if (isBlockedContent(url)) {
+		return {
+			valid: false,
+			error: "This content is unavailable on this server for legal reasons",
+			code: "BLOCKED_CONTENT",
+		};
+	}
+	return result;
+}
+
+function allowedMediaUrl(url: string): UrlValidation {
+	const result = validatePlatformUrl(url);
+	if (result.valid || process.env.DIRECT_CDN_URLS !== "true") return result;
+	return detectCdnPlatform(url) ? { valid: true } : result;
+}
+
 /** Query params arrive as "" when absent; treat that as unset. */
 const emptyToUndefined = (value: unknown) => (value === "" || value == null ? undefined : value);
 
@@ -16,16 +63,101 @@ export const mediaOptionsSchema = z.object({
 	downloadMode: z.preprocess(emptyToUndefined, z.enum(DOWNLOAD_MODES).optional()),
 });
 
-export type MediaOptionsInput = z.infer<typeof mediaOptionsSchema>;
-
-export const resolveInputSchema = mediaOptionsSchema
-	.extend({ url: z.string({ error: "URL is required" }) })
-	.transform((data, ctx) => {
-		const url = data.url.trim();
-		const result = validateUrl(url);
-		if (!result.valid) {
-			ctx.addIssue({ code: "custom", message: result.error ?? "Invalid URL" });
-			return z.NEVER;
-		}
-		return { ...data, url };
-	});
+/**
+ * Options that only shape the resolve itself (picker size, listing size, probe
+ * timeout, language), so they stay out of the signed download params: the
+ * download route rebuilds choices without a limit and replays the probed titles.
+ */
+export const resolveOptionsSchema = mediaOptionsSchema.extend({
+	/** Video choices to return; 0 or absent means the `FORMATS_LIMIT` default. */
+	formatsLimit: z
+		.number({ error: "formatsLimit must be a number" })
+		.int("formatsLimit must be an integer")
+		.min(0, "formatsLimit must not be negative")
+		.max(MAX_FORMATS_LIMIT, `formatsLimit must be at most ${MAX_FORMATS_LIMIT}`)
+		.optional(),
+	/** Uploads to list when the URL is a profile page; defaults to the maximum. */
+	entriesLimit: z
+		.number({ error: "entriesLimit must be a number" })
+		.int("entriesLimit must be an integer")
+		.min(1, "entriesLimit must be at least 1")
+		.max(MAX_PROFILE_ENTRIES, `entriesLimit must be at most ${MAX_PROFILE_ENTRIES}`)
+		.optional(),
+	/** Preferred language for titles and captions, e.g. `en` or `pt-BR`. */
+	lang: z
+		.string({ error: "lang must be a string" })
+		.regex(LANGUAGE_TAG_REGEX, "lang must be a language tag such as en or pt-BR")
+		.optional(),
+	/** Probe timeout; clamped server-side to 5..`EXTRACT_TIMEOUT_MAX_SECS`. */
+	timeoutSecs: z
+		.number({ error: "timeoutSecs must be a number" })
+		.positive("timeoutSecs must be positive")
+		.optional(),
+});
+
+export type ResolveOptionsInput = z.infer<typeof resolveOptionsSchema>;
+
+/**
+ * A body's `url`, trimmed, stripped of its fragment, given `https://` if it
+ * was pasted without a scheme, run through {@link validateMediaUrl} and then
+ * put in canonical form. Short links skip validation: they are checked hop
+ * by hop when they are resolved.
+ */
+const mediaUrlSchema = z.string({ error: "URL is required" }).transform((raw, ctx) => {
+	const url = withDefaultScheme(stripFragment(raw), platformDomains());
+	if (isShortLink(url)) return canonicalMediaUrl(url);
+	const result = validateMediaUrl(url);
+	if (!result.valid) {
+		ctx.addIssue({
+			code: "custom",
+			message: result.error ?? "Invalid URL",
+			params: { code: result.code, position: result.position, character: result.character },
+		});
+		return z.NEVER;
+	}
+	return canonicalMediaUrl(url);
+});
+
+export const resolveInputSchema = resolveOptionsSchema
+	.extend({
+		url: mediaUrlSchema,
+		/** Comma-separated top-level response keys to return, e.g. `title,picker`. */
+		fields: z.string({ error: "fields must be a comma-separated string" }).optional(),
+	})
+	.strict();
+
+/**
+ * Batch resolve body. URLs are only checked structurally here; each one is run
+ * through `validateMediaUrl` individually so a bad link fails its own result entry
+ * instead of the whole request.
+ */
+export const batchResolveInputSchema = resolveOptionsSchema
+	.extend({
+		urls: z
+			.array(z.string(), { error: "urls must be an array of strings" })
+			.min(1, "At least one URL is required")
+			.max(MAX_BATCH_URLS, `At most ${MAX_BATCH_URLS} URLs per batch`),
+	})
+	.strict();
+
+/**
+ * `POST /api/download/async` body: the signed `GET /api/download` query params
+ * as a flat JSON object. The route verifies them exactly like the query string.
+ */
+export const asyncDownloadInputSchema = z.record(z.string(), z.string(), {
+	error: "Download parameters must be strings",
+});
+
+/**
+ * `POST /api/download` body: a one-shot download without the resolve round
+ * trip. `format` is a picker `id` (e.g. `v-720p`, `a-mp3`); without it the
+ * best video is fetched, or the audio choice when `audioOnly` is set.
+ */
+export const directDownloadInputSchema = mediaOptionsSchema
+	.extend({
+		url: mediaUrlSchema,
+		format: z.string({ error: "format must be a choice id such as v-720p" }).optional(),
+		audioOnly: z.boolean({ error: "audioOnly must be a boolean" }).optional(),
+		filename: z.string({ error: "filename must be a string" }).optional(),
+	})
+	.strict();
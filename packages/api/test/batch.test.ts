@@ -0,0 +1,168 @@
+import { afterEach, beforeEach, describe, expect, it } from "bun:test";
+import type { BatchResolveResponse } from "@snatch/shared";
+import app from "../src/app";
+import { mapWithConcurrency, mapWithDeadline } from "../src/lib/concurrency";
+import { clearClients } from "../src/middleware/rate-limit";
+import { useStubYtDlp } from "./stub-ytdlp";
+
+function postBatch(body: unknown) {
+	return app.fetch(
+		new Request("http://localhost:3001/api/resolve/batch", {
+			method: "POST",
+			headers: { "Content-Type": "application/json" },
+			body: JSON.stringify(body),
+		}),
+	);
+}
+
+describe("POST /api/resolve/batch", () => {
+	beforeEach(() => {
+		clearClients();
+	});
+
+	it("reports a per-item error for each invalid URL without failing the batch", async () => {
+		const res = await postBatch({
+			urls: ["https://unknown-host-12345.com/video", "https://x.com/a; rm -rf /", "not a url"],
+		});
+		expect(res.status).toBe(200);
+		const data = (await res.json()) as BatchResolveResponse;
+		expect(data.results.map((item) => [item.status, item.error?.code])).toEqual([
+			["error", "UNSUPPORTED_PLATFORM"],
+			["error", "INVALID_CHAR"],
+			["error", "INVALID_URL"],
+		]);
+		expect(data.results[1].error?.context).toEqual({ position: 16, character: " " });
+		expect(data.results[0].url).toBe("https://unknown-host-12345.com/video");
+		expect(data.results[0].error?.message).toContain("Unsupported platform");
+	});
+
+	it("rejects an empty URL list with 422", async () => {
+		const res = await postBatch({ urls: [] });
+		expect(res.status).toBe(422);
+	});
+
+	it("rejects more URLs than the batch cap with 422", async () => {
+		const urls = Array.from({ length: 11 }, (_, i) => `https://x.com/user/status/${i}`);
+		const res = await postBatch({ urls });
+		expect(res.status).toBe(422);
+		const data = (await res.json()) as { success: boolean; error: string };
+		expect(data.success).toBe(false);
+		expect(data.error).toContain("At most 10");
+	});
+});
+
+describe("POST /api/resolve/batch with a stub yt-dlp", () => {
+	const stub = useStubYtDlp();
+	const original = process.env.BATCH_ITEM_TIMEOUT_MS;
+
+	beforeEach(() => {
+		clearClients();
+	});
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.BATCH_ITEM_TIMEOUT_MS;
+		else process.env.BATCH_ITEM_TIMEOUT_MS = original;
+	});
+
+	it("times out a hanging URL on its own clock without holding back the rest", async () => {
+		process.env.BATCH_ITEM_TIMEOUT_MS = "300";
+		await stub.script(`case "$*" in *status/2*) sleep 5 ;; esac\necho '{"id":"1","title":"Clip"}'`);
+
+		const urls = [1, 2, 3].map((id) => `https://x.com/user/status/${id}`);
+		const started = performance.now();
+		const res = await postBatch({ urls });
+		expect(performance.now() - started).toBeLessThan(3_000);
+		const data = (await res.json()) as BatchResolveResponse;
+		expect(data.results.map((item) => [item.url, item.error?.code])).toEqual([
+			[urls[0], undefined],
+			[urls[1], "TIMEOUT"],
+			[urls[2], undefined],
+		]);
+		expect(data.results[1].error?.message).toBe("URL did not resolve within 300 ms");
+	});
+});
+
+describe("mapWithConcurrency", () => {
+	it("preserves input order and never exceeds the limit", async () => {
+		let inFlight = 0;
+		let peak = 0;
+		const results = await mapWithConcurrency([30, 10, 20, 5, 15], 2, async (ms, i) => {
+			inFlight++;
+			peak = Math.max(peak, inFlight);
+			await Bun.sleep(ms);
+			inFlight--;
+			return i;
+		});
+		expect(results).toEqual([0, 1, 2, 3, 4]);
+		expect(peak).toBe(2);
+	});
+});
+
+describe("mapWithDeadline", () => {
+	it("returns finished results and times out the rest", async () => {
+		const results = await mapWithDeadline(
+			[5, 500, 10, 500],
+			2,
+			AbortSignal.timeout(100),
+			async (ms) => {
+				await Bun.sleep(ms);
+				return `done ${ms}`;
+			},
+			(ms) => `timeout ${ms}`,
+		);
+		expect(results).toEqual(["done 5", "timeout 500", "done 10", "timeout 500"]);
+	});
+
+	it("never exceeds the concurrency limit", async () => {
+		let inFlight = 0;
+		let peak = 0;
+		await mapWithDeadline(
+			[10, 10, 10, 10, 10],
+			3,
+			AbortSignal.timeout(1_000),
+			async (ms) => {
+				inFlight++;
+				peak = Math.max(peak, inFlight);
+				await Bun.sleep(ms);
+				inFlight--;
+			},
+			() => undefined,
+		);
+		expect(peak).toBe(3);
+	});
+
+	it("gives each item its own timeout and passes the signal to it", async () => {
+		const signals: AbortSignal[] = [];
+		const results = await mapWithDeadline(
+			[5, 500, 10],
+			3,
+			AbortSignal.timeout(1_000),
+			async (ms, _index, signal) => {
+				signals.push(signal);
+				await Bun.sleep(ms);
+				return `done ${ms}`;
+			},
+			(ms) => `timeout ${ms}`,
+			50,
+		);
+		expect(results).toEqual(["done 5", "timeout 500", "done 10"]);
+		expect(signals[1].aborted).toBe(true);
+	});
+
+	it("skips every item once the deadline has already passed", async () => {
+		const deadline = AbortSignal.abort();
+		let calls = 0;
+		const results = await mapWithDeadline(
+			[1, 2],
+			2,
+			deadline,
+			async () => {
+				calls++;
+				return "ran";
+			},
+			() => "timeout",
+		);
+		expect(results).toEqual(["timeout", "timeout"]);
+		expect(calls).toBe(0);
+	});
+});
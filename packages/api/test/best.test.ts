@@ -0,0 +1,85 @@
+import { beforeEach, describe, expect, it } from "bun:test";
+import app from "../src/app";
+import { clearClients } from "../src/middleware/rate-limit";
+import { useStubYtDlp } from "./stub-ytdlp";
+
+function best(url: string, options: Record<string, unknown> = {}) {
+	return app.fetch(
+		new Request("http://localhost:3001/api/best", {
+			method: "POST",
+			headers: { "Content-Type": "application/json" },
+			body: JSON.stringify({ url, ...options }),
+		}),
+	);
+}
+
+describe("POST /api/best", () => {
+	const stub = useStubYtDlp();
+
+	beforeEach(() => {
+		clearClients();
+	});
+
+	it("answers only the tallest video choice", async () => {
+		await stub.probeJson({
+			id: "1",
+			title: "Clip",
+			formats: [
+				{ format_id: "360", vcodec: "avc1", acodec: "mp4a", height: 360, filesize: 1_000 },
+				{ format_id: "1080", vcodec: "avc1", acodec: "mp4a", height: 1080, filesize: 9_000 },
+				{ format_id: "720", vcodec: "avc1", acodec: "mp4a", height: 720, filesize: 4_000 },
+				{ format_id: "audio", vcodec: "none", acodec: "mp4a", abr: 128, filesize: 500 },
+			],
+		});
+
+		const res = await best("https://x.com/user/status/1");
+		expect(res.status).toBe(200);
+		expect(await res.json()).toEqual({
+			url: expect.stringMatching(/^http:\/\/localhost:3001\/api\/download\?.*choiceId=v-1080p/),
+			ext: "mp4",
+			quality: "1080p",
+			filesize: 9_000,
+		});
+	});
+
+	it("tops out at DEFAULT_QUALITY unless the request sets videoQuality", async () => {
+		const prev = process.env.DEFAULT_QUALITY;
+		process.env.DEFAULT_QUALITY = "720p";
+		try {
+			await stub.probeJson({
+				id: "3",
+				title: "Clip",
+				formats: [
+					{ format_id: "720", vcodec: "avc1", acodec: "mp4a", height: 720 },
+					{ format_id: "1080", vcodec: "avc1", acodec: "mp4a", height: 1080 },
+				],
+			});
+			const capped = await best("https://x.com/user/status/3");
+			expect(((await capped.json()) as { quality: string }).quality).toBe("720p");
+
+			const full = await best("https://x.com/user/status/3", { videoQuality: "max" });
+			expect(((await full.json()) as { quality: string }).quality).toBe("1080p");
+		} finally {
+			if (prev === undefined) delete process.env.DEFAULT_QUALITY;
+			else process.env.DEFAULT_QUALITY = prev;
+		}
+	});
+
+	it("is a 404 when the media has no video format", async () => {
+		await stub.probeJson({
+			id: "2",
+			title: "Podcast",
+			formats: [{ format_id: "audio", vcodec: "none", acodec: "mp4a", abr: 128 }],
+		});
+
+		const res = await best("https://x.com/user/status/2");
+		expect(res.status).toBe(404);
+		expect(((await res.json()) as { code: string }).code).toBe("NOT_FOUND");
+	});
+
+	it("refuses profile pages", async () => {
+		const res = await best("https://www.tiktok.com/@user");
+		expect(res.status).toBe(400);
+		expect(((await res.json()) as { code: string }).code).toBe("BAD_REQUEST");
+	});
+});
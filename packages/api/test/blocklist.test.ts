@@ -0,0 +1,136 @@
+import { afterEach, beforeEach, describe, expect, it, spyOn } from "bun:test";
+import { mkdtemp, rm, writeFile } from "node:fs/promises";
+import os from "node:os";
+import path from "node:path";
+import app from "../src/app";
+import {
+	blockedPatterns,
+	isBlockedContent,
+	isBlockedUrl,
+	loadBlocklistFile,
+} from "../src/lib/blocklist";
+import { logger } from "../src/lib/logger";
+import { clearClients } from "../src/middleware/rate-limit";
+
+function resolve(url: string) {
+	return app.fetch(
+		new Request("http://localhost:3001/api/resolve", {
+			method: "POST",
+			headers: { "Content-Type": "application/json" },
+			body: JSON.stringify({ url }),
+		}),
+	);
+}
+
+describe("BLOCKED_PATTERNS", () => {
+	const original = process.env.BLOCKED_PATTERNS;
+
+	beforeEach(() => {
+		clearClients();
+	});
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.BLOCKED_PATTERNS;
+		else process.env.BLOCKED_PATTERNS = original;
+	});
+
+	it("refuses a URL containing a blocked substring with 403 BLOCKED", async () => {
+		process.env.BLOCKED_PATTERNS = "x.com/spammer, example.org";
+		const res = await resolve("https://X.com/Spammer/status/1");
+		expect(res.status).toBe(403);
+		expect(await res.json()).toEqual({
+			success: false,
+			error: "This URL is blocked on this server",
+			code: "BLOCKED",
+		});
+	});
+
+	it("lets URLs that match no pattern through", () => {
+		process.env.BLOCKED_PATTERNS = "x.com/spammer";
+		expect(isBlockedUrl("https://x.com/user/status/1")).toBe(false);
+		delete process.env.BLOCKED_PATTERNS;
+		expect(isBlockedUrl("https://x.com/spammer/status/1")).toBe(false);
+	});
+
+	it("treats /slash-wrapped/ entries as regexes", () => {
+		process.env.BLOCKED_PATTERNS = String.raw`/tiktok\.com\/@bot\d+/`;
+		expect(isBlockedUrl("https://www.tiktok.com/@bot42/video/1")).toBe(true);
+		expect(isBlockedUrl("https://www.tiktok.com/@robot/video/1")).toBe(false);
+	});
+
+	it("logs and skips an invalid regex while keeping the other entries", () => {
+		const warn = spyOn(logger, "warn");
+		try {
+			process.env.BLOCKED_PATTERNS = "/([a-z/,x.com/spammer";
+			expect(blockedPatterns()).toHaveLength(1);
+			expect(warn).toHaveBeenCalledTimes(1);
+			expect(isBlockedUrl("https://x.com/spammer/status/1")).toBe(true);
+		} finally {
+			warn.mockRestore();
+		}
+	});
+});
+
+describe("BLOCKLIST_FILE", () => {
+	const original = process.env.BLOCKLIST_FILE;
+	let dir: string;
+
+	/** Point `BLOCKLIST_FILE` at a fresh file holding `lines` and load it. */
+	async function useBlocklist(...lines: string[]) {
+		const file = path.join(dir, "blocklist.txt");
+		await writeFile(file, lines.join("\n"));
+		process.env.BLOCKLIST_FILE = file;
+		loadBlocklistFile();
+	}
+
+	beforeEach(async () => {
+		clearClients();
+		dir = await mkdtemp(path.join(os.tmpdir(), "snatch-blocklist-"));
+	});
+
+	afterEach(async () => {
+		if (original === undefined) delete process.env.BLOCKLIST_FILE;
+		else process.env.BLOCKLIST_FILE = original;
+		loadBlocklistFile();
+		await rm(dir, { recursive: true, force: true });
+	});
+
+	it("refuses a listed URL prefix with 451 BLOCKED_CONTENT", async () => {
+		await useBlocklist("# takedown 2026-10", "", "https://x.com/someone/status/");
+		const res = await resolve("https://twitter.com/someone/status/1");
+		expect(res.status).toBe(451);
+		expect(await res.json()).toEqual({
+			success: false,
+			error: "This content is unavailable on this server for legal reasons",
+			code: "BLOCKED_CONTENT",
+		});
+		expect(isBlockedContent("https://x.com/someone_else/status/1")).toBe(false);
+	});
+
+	it("matches prefix paths case-sensitively, as post IDs are", async () => {
+		await useBlocklist("https://www.instagram.com/p/AbC123/");
+		expect(isBlockedContent("https://instagram.com/p/AbC123/")).toBe(true);
+		expect(isBlockedContent("https://WWW.Instagram.com/p/AbC123/")).toBe(true);
+		expect(isBlockedContent("https://www.instagram.com/p/abc123/")).toBe(false);
+	});
+
+	it("refuses every post of a listed account, whatever the username's case", async () => {
+		await useBlocklist("tiktok:@SomeOne", "twitter:Spammer");
+		expect(isBlockedContent("https://www.tiktok.com/@someone/video/1")).toBe(true);
+		expect(isBlockedContent("https://m.tiktok.com/@SOMEONE/video/2")).toBe(true);
+		expect(isBlockedContent("https://x.com/spammer/status/1")).toBe(true);
+		expect(isBlockedContent("https://x.com/someone/status/1")).toBe(false);
+		expect(isBlockedContent("https://www.tiktok.com/@someone2/video/1")).toBe(false);
+	});
+
+	it("logs and skips unusable lines", async () => {
+		const warn = spyOn(logger, "warn");
+		try {
+			await useBlocklist("myspace:someone", "not an entry", "tiktok:someone");
+			expect(warn).toHaveBeenCalledTimes(2);
+			expect(isBlockedContent("https://www.tiktok.com/@someone/video/1")).toBe(true);
+		} finally {
+			warn.mockRestore();
+		}
+	});
+});
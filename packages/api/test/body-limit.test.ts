@@ -0,0 +1,61 @@
+import { afterEach, beforeEach, describe, expect, it } from "bun:test";
+import app from "../src/app";
+import { maxBodyBytes } from "../src/lib/env";
+import { clearClients } from "../src/middleware/rate-limit";
+
+function post(path: string, body: string) {
+	return app.fetch(
+		new Request(`http://localhost:3001${path}`, {
+			method: "POST",
+			headers: { "Content-Type": "application/json" },
+			body,
+		}),
+	);
+}
+
+describe("request body limit", () => {
+	const original = process.env.MAX_BODY_BYTES;
+
+	beforeEach(() => {
+		clearClients();
+	});
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.MAX_BODY_BYTES;
+		else process.env.MAX_BODY_BYTES = original;
+	});
+
+	it("rejects a body over MAX_BODY_BYTES with 413", async () => {
+		process.env.MAX_BODY_BYTES = "100";
+		const urls = Array.from({ length: 5 }, (_, i) => `https://x.com/user/status/${i}`);
+		const res = await post("/api/resolve/batch", JSON.stringify({ urls }));
+		expect(res.status).toBe(413);
+		expect(((await res.json()) as { code: string }).code).toBe("PAYLOAD_TOO_LARGE");
+	});
+
+	it("keeps every single-URL route at the default cap when the limit is raised", async () => {
+		process.env.MAX_BODY_BYTES = String(1024 * 1024);
+		const url = `https://x.com/user/status/1?pad=${"a".repeat(11 * 1024)}`;
+		for (const path of [
+			"/api/resolve",
+			"/api/best",
+			"/api/refresh",
+			"/api/download",
+			"/api/download/async",
+		]) {
+			expect((await post(path, JSON.stringify({ url }))).status).toBe(413);
+		}
+		// Padding a batch body with whitespace gets it past the cap to body validation.
+		const batch = `{"urls":[]}${" ".repeat(11 * 1024)}`;
+		expect((await post("/api/resolve/batch", batch)).status).not.toBe(413);
+	});
+
+	it("validates MAX_BODY_BYTES", () => {
+		delete process.env.MAX_BODY_BYTES;
+		expect(maxBodyBytes()).toBe(10 * 1024);
+		process.env.MAX_BODY_BYTES = "65536";
+		expect(maxBodyBytes()).toBe(65536);
+		process.env.MAX_BODY_BYTES = "64kb";
+		expect(() => maxBodyBytes()).toThrow("MAX_BODY_BYTES");
+	});
+});
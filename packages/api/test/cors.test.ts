@@ -33,4 +33,59 @@ describe("CORS configuration", () => {
 
 		if (orig !== undefined) process.env.ALLOWED_ORIGINS = orig;
 	});
+
+	it("should not grant a disallowed origin access to /api/download", async () => {
+		const orig = process.env.ALLOWED_ORIGINS;
+		process.env.ALLOWED_ORIGINS = "https://snatch.example";
+
+		const res = await app.fetch(
+			new Request("http://localhost:3001/api/download?url=https://x.com/user/status/1", {
+				headers: { Origin: "http://evil.com" },
+			}),
+		);
+
+		const acao = res.headers.get("Access-Control-Allow-Origin");
+		expect(acao).not.toBe("*");
+		expect(acao).not.toBe("http://evil.com");
+
+		if (orig !== undefined) process.env.ALLOWED_ORIGINS = orig;
+		else delete process.env.ALLOWED_ORIGINS;
+	});
+
+	it("should echo an allowlisted origin on /api/download", async () => {
+		const orig = process.env.ALLOWED_ORIGINS;
+		process.env.ALLOWED_ORIGINS = "https://snatch.example";
+
+		const res = await app.fetch(
+			new Request("http://localhost:3001/api/download?url=https://x.com/user/status/1", {
+				headers: { Origin: "https://snatch.example" },
+			}),
+		);
+
+		expect(res.headers.get("Access-Control-Allow-Origin")).toBe("https://snatch.example");
+
+		if (orig !== undefined) process.env.ALLOWED_ORIGINS = orig;
+		else delete process.env.ALLOWED_ORIGINS;
+	});
+
+	it("advertises exactly the methods each route registers on preflight", async () => {
+		const preflight = (path: string, method: string) =>
+			app.fetch(
+				new Request(`http://localhost:3001${path}`, {
+					method: "OPTIONS",
+					headers: {
+						Origin: "https://snatch.example",
+						"Access-Control-Request-Method": method,
+					},
+				}),
+			);
+		const allowed = async (path: string, method: string) =>
+			(await preflight(path, method)).headers.get("Access-Control-Allow-Methods");
+
+		expect(await allowed("/api/download", "POST")).toBe("GET,POST,OPTIONS");
+		expect(await allowed("/api/resolve", "POST")).toBe("POST,OPTIONS");
+		expect(await allowed("/api/download/status/abc", "GET")).toBe("GET,OPTIONS");
+		// No route deletes the cache yet, so a DELETE preflight is not granted it.
+		expect(await allowed("/api/cache", "DELETE")).toBe("OPTIONS");
+	});
 });
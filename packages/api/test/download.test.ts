@@ -1,13 +1,16 @@
 import { beforeEach, describe, expect, it } from "bun:test";
+import { readFile } from "node:fs/promises";
+import path from "node:path";
 import app from "../src/app";
 import { clearClients } from "../src/middleware/rate-limit";
+import { stubDownload, useStubYtDlp } from "./stub-ytdlp";
 
 describe("POST /api/resolve validation", () => {
 	beforeEach(() => {
 		clearClients();
 	});
 
-	it("returns 400 when body is missing or invalid JSON", async () => {
+	it("returns 422 when body is missing or invalid JSON", async () => {
 		const res = await app.fetch(
 			new Request("http://localhost:3001/api/resolve", {
 				method: "POST",
@@ -15,10 +18,10 @@ describe("POST /api/resolve validation", () => {
 				body: "{ invalid json",
 			}),
 		);
-		expect(res.status).toBe(400);
+		expect(res.status).toBe(422);
 	});
 
-	it("returns 400 when URL is missing", async () => {
+	it("returns 422 when URL is missing", async () => {
 		const res = await app.fetch(
 			new Request("http://localhost:3001/api/resolve", {
 				method: "POST",
@@ -26,7 +29,7 @@ describe("POST /api/resolve validation", () => {
 				body: JSON.stringify({}),
 			}),
 		);
-		expect(res.status).toBe(400);
+		expect(res.status).toBe(422);
 	});
 
 	it("returns 400 when host is unsupported", async () => {
@@ -38,5 +41,171 @@ describe("POST /api/resolve validation", () => {
 			}),
 		);
 		expect(res.status).toBe(400);
+		expect(await res.json()).toEqual({
+			success: false,
+			error: expect.stringMatching(/^Unsupported platform: 'unknown-host-12345\.com'\./),
+			code: "UNSUPPORTED_PLATFORM",
+		});
+	});
+});
+
+describe("GET /api/download with a stub yt-dlp", () => {
+	const stub = useStubYtDlp();
+	const INFO = {
+		id: "1",
+		title: "Clip",
+		formats: [{ format_id: "m720", vcodec: "avc1", acodec: "mp4a", height: 720, ext: "mp4" }],
+	};
+
+	beforeEach(() => {
+		clearClients();
+	});
+
+	/** Resolve the stubbed tweet and return its signed 720p download URL. */
+	async function signedVideoUrl(): Promise<string> {
+		const res = await app.fetch(
+			new Request("http://localhost:3001/api/resolve", {
+				method: "POST",
+				headers: { "Content-Type": "application/json" },
+				body: JSON.stringify({ url: "https://x.com/user/status/1" }),
+			}),
+		);
+		const data = (await res.json()) as { picker: { id: string; url: string }[] };
+		const video = data.picker.find((choice) => choice.id === "v-720p");
+		if (!video) throw new Error("resolve returned no 720p choice");
+		return video.url;
+	}
+
+	it("streams the file yt-dlp wrote under the resolved filename", async () => {
+		await stub.probeJson(INFO, stubDownload("video-bytes"));
+
+		const res = await app.fetch(new Request(await signedVideoUrl()));
+		expect(res.status).toBe(200);
+		expect(res.headers.get("Content-Type")).toBe("video/mp4");
+		expect(res.headers.get("Content-Disposition")).toBe('attachment; filename="Clip.mp4"');
+		expect(res.headers.get("Content-Length")).toBe("11");
+		expect(await res.text()).toBe("video-bytes");
+	});
+
+	it("refuses a live stream unless allowLive is passed", async () => {
+		await stub.probeJson({ ...INFO, is_live: true }, stubDownload("never"));
+
+		const res = await app.fetch(new Request(await signedVideoUrl()));
+		expect(res.status).toBe(409);
+		expect(await res.json()).toEqual({
+			success: false,
+			error: "This is a live stream; pass allowLive=true to record a capped capture",
+			code: "LIVE_CONTENT",
+		});
+	});
+
+	it("returns the classified error body when the download fails", async () => {
+		await stub.probeJson(INFO, 'echo "ERROR: [twitter] 1: Private video" >&2; exit 1');
+
+		const res = await app.fetch(new Request(await signedVideoUrl()));
+		expect(res.status).toBe(403);
+		expect(await res.json()).toEqual({
+			success: false,
+			error: "1: Private video",
+			code: "PRIVATE_CONTENT",
+		});
+	});
+});
+
+describe("POST /api/download with a stub yt-dlp", () => {
+	const stub = useStubYtDlp();
+	const INFO = {
+		id: "1",
+		title: "Clip",
+		formats: [
+			{ format_id: "m480", vcodec: "avc1", acodec: "mp4a", height: 480, ext: "mp4" },
+			{ format_id: "m720", vcodec: "avc1", acodec: "mp4a", height: 720, ext: "mp4" },
+		],
+	};
+
+	beforeEach(() => {
+		clearClients();
+	});
+
+	function download(body: object) {
+		return app.fetch(
+			new Request("http://localhost:3001/api/download", {
+				method: "POST",
+				headers: { "Content-Type": "application/json" },
+				body: JSON.stringify(body),
+			}),
+		);
+	}
+
+	/** Record each download's arguments, then write `bytes` like yt-dlp would. */
+	async function stubRecordingDownload(bytes: string): Promise<string> {
+		const argsFile = path.join(stub.dir, "args");
+		await stub.probeJson(INFO, `echo "$@" > '${argsFile}'\n${stubDownload(bytes)}`);
+		return argsFile;
+	}
+
+	it("streams the explicitly requested format under the given filename", async () => {
+		const argsFile = await stubRecordingDownload("small-video");
+
+		const res = await download({
+			url: "https://x.com/user/status/1",
+			format: "v-480p",
+			filename: "clip-480.mp4",
+		});
+		expect(res.status).toBe(200);
+		expect(res.headers.get("Content-Type")).toBe("video/mp4");
+		expect(res.headers.get("Content-Disposition")).toBe('attachment; filename="clip-480.mp4"');
+		expect(await res.text()).toBe("small-video");
+		const args = await readFile(argsFile, "utf-8");
+		expect(args).toContain("[height=480]");
+		expect(args).not.toContain("[height=720]");
+	});
+
+	it("defaults to the best video, or the audio choice with audioOnly", async () => {
+		const argsFile = await stubRecordingDownload("bytes");
+
+		expect((await download({ url: "https://x.com/user/status/1" })).status).toBe(200);
+		expect(await readFile(argsFile, "utf-8")).toContain("[height=720]");
+
+		const audio = await download({ url: "https://x.com/user/status/1", audioOnly: true });
+		expect(audio.status).toBe(200);
+		expect(audio.headers.get("Content-Type")).toBe("audio/mpeg");
+		expect(await readFile(argsFile, "utf-8")).toContain("--audio-format mp3");
+	});
+
+	it("rejects a format the media does not offer", async () => {
+		await stub.probeJson(INFO);
+
+		const res = await download({ url: "https://x.com/user/status/1", format: "v-1080p" });
+		expect(res.status).toBe(409);
+		expect(await res.json()).toEqual({
+			success: false,
+			error: 'Format "v-1080p" is not available',
+			code: "FORMAT_UNAVAILABLE",
+		});
+		const conflict = await download({
+			url: "https://x.com/user/status/1",
+			format: "v-720p",
+			audioOnly: true,
+		});
+		expect(conflict.status).toBe(400);
+	});
+
+	it("refuses a live stream, which needs a resolve and allowLive", async () => {
+		await stub.probeJson({ ...INFO, live_status: "is_live" }, stubDownload("never"));
+
+		const res = await download({ url: "https://x.com/user/status/1" });
+		expect(res.status).toBe(409);
+		expect(((await res.json()) as { code: string }).code).toBe("LIVE_CONTENT");
+	});
+
+	it("validates the body like /api/resolve", async () => {
+		const host = await download({ url: "https://unknown-host-12345.com/video" });
+		expect(host.status).toBe(400);
+		expect(((await host.json()) as { code: string }).code).toBe("UNSUPPORTED_PLATFORM");
+
+		const typo = await download({ url: "https://x.com/user/status/1", audio_only: true });
+		expect(typo.status).toBe(422);
+		expect(((await typo.json()) as { field: string }).field).toBe("audio_only");
 	});
 });
@@ -0,0 +1,66 @@
+import { afterEach, describe, expect, it, spyOn } from "bun:test";
+import { describeFailure } from "../src/lib/errors";
+import { logger } from "../src/lib/logger";
+import { YtDlpError } from "../src/lib/ytdlp";
+
+const STDERR = "WARNING: [youtube] using /home/app/.cookies\nERROR: [youtube] abc: Private video";
+
+describe("describeFailure", () => {
+	const prevDebug = process.env.DEBUG_ERRORS;
+
+	afterEach(() => {
+		if (prevDebug === undefined) delete process.env.DEBUG_ERRORS;
+		else process.env.DEBUG_ERRORS = prevDebug;
+	});
+
+	it("returns only the cleaned message by default", () => {
+		delete process.env.DEBUG_ERRORS;
+		expect(describeFailure(new YtDlpError(STDERR, "probe failed"), "x")).toEqual({
+			code: "PRIVATE_CONTENT",
+			message: "abc: Private video",
+		});
+	});
+
+	it("includes the raw stderr when DEBUG_ERRORS=1", () => {
+		process.env.DEBUG_ERRORS = "1";
+		expect(describeFailure(new YtDlpError(STDERR, "probe failed"), "x")).toEqual({
+			code: "PRIVATE_CONTENT",
+			message: "abc: Private video",
+			debug: STDERR,
+		});
+	});
+
+	it("never echoes stderr without an ERROR line as the message", () => {
+		const error = new YtDlpError("Traceback (most recent call last):", "boom");
+		expect(describeFailure(error, "x")).toEqual({ code: "YTDLP_FAILED", message: "boom" });
+	});
+
+	it("maps uncoded errors to EXTRACTION_FAILED", () => {
+		expect(describeFailure(new Error("bad json"), "x")).toEqual({
+			code: "EXTRACTION_FAILED",
+			message: "bad json",
+		});
+		expect(describeFailure("oops", "Resolution failed").message).toBe("Resolution failed");
+	});
+
+	it("warns the operator about an outdated yt-dlp when reporting, not when constructing", () => {
+		const error = spyOn(logger, "error");
+		try {
+			const outdated = new YtDlpError("ERROR: [x] 1: please update yt-dlp", "probe failed");
+			expect(error).not.toHaveBeenCalled();
+			expect(describeFailure(outdated, "x").code).toBe("YTDLP_OUTDATED");
+			expect(error).toHaveBeenCalledWith(
+				expect.objectContaining({ code: "YTDLP_OUTDATED" }),
+				expect.stringContaining("yt-dlp is outdated"),
+			);
+		} finally {
+			error.mockRestore();
+		}
+	});
+
+	it("truncates very long stderr from the front", () => {
+		const error = new YtDlpError(`${"x".repeat(10_000)}\nERROR: tail`, "failed");
+		expect(error.stderr.length).toBeLessThanOrEqual(4_001);
+		expect(error.stderr.endsWith("ERROR: tail")).toBe(true);
+	});
+});
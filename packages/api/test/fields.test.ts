@@ -0,0 +1,45 @@
+import { describe, expect, it } from "bun:test";
+import { parseFieldList, pickFields } from "../src/lib/fields";
+
+const response = {
+	status: "picker",
+	title: "Clip",
+	thumbnail: "https://cdn.example/t.jpg",
+	filename: "Clip.mp4",
+	picker: [{ type: "video", url: "https://api.example/api/download?x=1" }],
+};
+
+describe("parseFieldList", () => {
+	it("splits and trims a comma-separated list", () => {
+		expect(parseFieldList(" title, picker ,")).toEqual(["title", "picker"]);
+	});
+
+	it("treats absent or empty input as no filter", () => {
+		expect(parseFieldList(undefined)).toBeUndefined();
+		expect(parseFieldList("")).toBeUndefined();
+		expect(parseFieldList(" , ")).toBeUndefined();
+	});
+});
+
+describe("pickFields", () => {
+	it("returns the response untouched without a filter", () => {
+		expect(pickFields(response, undefined)).toEqual(response);
+	});
+
+	it("keeps only the requested keys plus status", () => {
+		expect(pickFields(response, ["picker"])).toEqual({
+			status: "picker",
+			picker: response.picker,
+		});
+		expect(pickFields(response, ["title", "filename"])).toEqual({
+			status: "picker",
+			title: "Clip",
+			filename: "Clip.mp4",
+		});
+	});
+
+	it("ignores unknown field names", () => {
+		expect(pickFields(response, ["title", "nope"])).toEqual({ status: "picker", title: "Clip" });
+		expect(pickFields(response, ["nope"])).toEqual({ status: "picker" });
+	});
+});
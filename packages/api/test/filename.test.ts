@@ -0,0 +1,56 @@
+import { afterEach, describe, expect, it, spyOn } from "bun:test";
+import { DEFAULT_FILENAME_TEMPLATE, filenameTemplate, renderFilename } from "../src/lib/filename";
+import { logger } from "../src/lib/logger";
+
+const CLIP = { title: "Launch day", uploader: "NASA", id: "123", ext: "mp4", platform: "x" };
+
+describe("FILENAME_TEMPLATE", () => {
+	const original = process.env.FILENAME_TEMPLATE;
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.FILENAME_TEMPLATE;
+		else process.env.FILENAME_TEMPLATE = original;
+	});
+
+	it("names files after the title by default", () => {
+		delete process.env.FILENAME_TEMPLATE;
+		expect(filenameTemplate()).toBe(DEFAULT_FILENAME_TEMPLATE);
+		expect(renderFilename(CLIP)).toBe("Launch day.mp4");
+	});
+
+	it("fills in every supported placeholder", () => {
+		process.env.FILENAME_TEMPLATE = "{uploader} - {title}.{ext}";
+		expect(renderFilename(CLIP)).toBe("NASA - Launch day.mp4");
+
+		process.env.FILENAME_TEMPLATE = "{platform}_{id}.{ext}";
+		expect(renderFilename(CLIP)).toBe("x_123.mp4");
+	});
+
+	it("strips separators and control characters from values", () => {
+		process.env.FILENAME_TEMPLATE = "{uploader} - {title}.{ext}";
+		const name = renderFilename({ ...CLIP, uploader: "x/../y", title: 'a/b\\c: "d"\r\n' });
+		expect(name).toBe("x..y - abc d.mp4");
+	});
+
+	it("renders a missing value as empty and never returns a bare extension", () => {
+		process.env.FILENAME_TEMPLATE = "{uploader} {title}.{ext}";
+		expect(renderFilename({ ...CLIP, uploader: undefined })).toBe("Launch day.mp4");
+
+		process.env.FILENAME_TEMPLATE = "{uploader}.{ext}";
+		expect(renderFilename({ ...CLIP, uploader: undefined })).toBe("media.mp4");
+	});
+
+	it("logs and falls back to the default for an unknown placeholder or stray brace", () => {
+		const warn = spyOn(logger, "warn");
+		try {
+			for (const template of ["{uploader} - {duration}.{ext}", "{title.{ext}", "{title}}.{ext}"]) {
+				process.env.FILENAME_TEMPLATE = template;
+				expect(filenameTemplate()).toBe(DEFAULT_FILENAME_TEMPLATE);
+				expect(renderFilename(CLIP)).toBe("Launch day.mp4");
+			}
+			expect(warn).toHaveBeenCalledTimes(3);
+		} finally {
+			warn.mockRestore();
+		}
+	});
+});
@@ -0,0 +1,64 @@
+import { afterAll, beforeAll, beforeEach, describe, expect, it } from "bun:test";
+import { chmod, mkdtemp, rm, writeFile } from "node:fs/promises";
+import os from "node:os";
+import path from "node:path";
+import app from "../src/app";
+import { clearReadinessCache } from "../src/routes/health";
+
+describe("GET /health, /health/ready and /ready", () => {
+	const prev = { path: process.env.PATH, dir: process.env.YTDLP_DIR };
+	let dir: string;
+
+	beforeAll(async () => {
+		dir = await mkdtemp(path.join(os.tmpdir(), "snatch-health-"));
+		// Hide any system yt-dlp so only the scratch directory is consulted.
+		process.env.PATH = dir;
+		process.env.YTDLP_DIR = dir;
+	});
+
+	afterAll(async () => {
+		process.env.PATH = prev.path;
+		if (prev.dir === undefined) delete process.env.YTDLP_DIR;
+		else process.env.YTDLP_DIR = prev.dir;
+		await rm(dir, { recursive: true, force: true });
+	});
+
+	beforeEach(() => {
+		clearReadinessCache();
+	});
+
+	it("returns 503 when yt-dlp cannot be invoked", async () => {
+		for (const route of ["/health/ready", "/ready"]) {
+			const res = await app.fetch(new Request(`http://localhost:3001${route}`));
+			expect(res.status).toBe(503);
+			expect(await res.json()).toEqual({ status: "unavailable", ytdlp: { available: false } });
+		}
+	});
+
+	it("keeps liveness at 200 while reporting yt-dlp as unavailable", async () => {
+		const res = await app.fetch(new Request("http://localhost:3001/health"));
+		expect(res.status).toBe(200);
+		expect(await res.json()).toMatchObject({ status: "ok", ytdlp: { available: false } });
+	});
+
+	it("returns 200 with the yt-dlp version when it runs", async () => {
+		const stub = path.join(dir, "yt-dlp");
+		await writeFile(stub, "#!/bin/sh\necho 2025.01.15\n");
+		await chmod(stub, 0o755);
+		for (const route of ["/health/ready", "/ready"]) {
+			const res = await app.fetch(new Request(`http://localhost:3001${route}`));
+			expect(res.status).toBe(200);
+			expect(await res.json()).toEqual({
+				status: "ready",
+				ytdlp: { available: true, version: "2025.01.15" },
+			});
+		}
+	});
+
+	it("reports the yt-dlp version on /health once it runs", async () => {
+		const res = await app.fetch(new Request("http://localhost:3001/health"));
+		expect(await res.json()).toMatchObject({
+			ytdlp: { available: true, version: "2025.01.15" },
+		});
+	});
+});
@@ -0,0 +1,209 @@
+import { afterEach, describe, expect, it } from "bun:test";
+import { mkdtemp, rm, writeFile } from "node:fs/promises";
+import { tmpdir } from "node:os";
+import path from "node:path";
+import app from "../src/app";
+import { type JobOutput, JobStore } from "../src/lib/jobs";
+import { YtDlpError } from "../src/lib/ytdlp";
+
+/** A runner the test finishes by hand, recording whether its output was cleaned up. */
+function deferredRunner() {
+	const { promise, resolve, reject } = Promise.withResolvers<JobOutput>();
+	const state = {
+		signal: undefined as AbortSignal | undefined,
+		progress: (_percent: number, _bytes?: number) => {},
+		cleanedUp: false,
+	};
+	const run = (signal: AbortSignal, onProgress: (percent: number, bytes?: number) => void) => {
+		state.signal = signal;
+		state.progress = onProgress;
+		return promise;
+	};
+	return { run, state, resolve, reject };
+}
+
+/** Wait for a background job to leave `running`. */
+async function settled(store: JobStore, id: string) {
+	for (let i = 0; i < 100 && store.get(id)?.status === "running"; i++) await Bun.sleep(1);
+	return store.get(id);
+}
+
+describe("JobStore", () => {
+	let dir: string | undefined;
+
+	afterEach(async () => {
+		if (dir) await rm(dir, { recursive: true, force: true });
+		dir = undefined;
+	});
+
+	async function spooledFile(size: number, state: { cleanedUp: boolean }): Promise<JobOutput> {
+		dir ??= await mkdtemp(path.join(tmpdir(), "snatch-jobs-"));
+		const filePath = path.join(dir, `clip-${size}.mp4`);
+		await writeFile(filePath, Buffer.alloc(size));
+		return {
+			filePath,
+			cleanup: async () => {
+				state.cleanedUp = true;
+			},
+		};
+	}
+
+	const meta = { filename: "", contentType: "video/mp4" };
+
+	it("moves running → done and hands the file over exactly once", async () => {
+		const store = new JobStore({ maxRunning: 2, maxBytes: 1024, ttlMs: 60_000 });
+		const runner = deferredRunner();
+		const job = store.start(meta, runner.run);
+		expect(job.status).toBe("running");
+
+		runner.state.progress(40);
+		expect(store.get(job.id)?.progress).toBe(40);
+		expect(store.take(job.id)).toBeUndefined();
+
+		runner.resolve(await spooledFile(10, runner.state));
+		expect(await settled(store, job.id)).toMatchObject({
+			status: "done",
+			progress: 100,
+			size: 10,
+			filename: "clip-10.mp4",
+		});
+
+		expect(store.take(job.id)?.output).toBeDefined();
+		expect(store.get(job.id)).toBeUndefined();
+	});
+
+	it("moves running → failed with a classified error", async () => {
+		const store = new JobStore({ maxRunning: 2, maxBytes: 1024, ttlMs: 60_000 });
+		const runner = deferredRunner();
+		const job = store.start(meta, runner.run);
+
+		runner.reject(new YtDlpError("ERROR: [x] 1: Private video", "Download failed"));
+		expect(await settled(store, job.id)).toMatchObject({
+			status: "failed",
+			error: { code: "PRIVATE_CONTENT" },
+		});
+		expect(store.take(job.id)).toBeUndefined();
+	});
+
+	it("hands each settled job to onSettled once", async () => {
+		const store = new JobStore({ maxRunning: 2, maxBytes: 1024, ttlMs: 60_000 });
+		const seen: string[] = [];
+		const runner = deferredRunner();
+		const job = store.start(meta, runner.run, (settledJob) => seen.push(settledJob.status));
+		expect(seen).toEqual([]);
+
+		runner.reject(new Error("boom"));
+		await settled(store, job.id);
+		await Bun.sleep(1);
+		expect(seen).toEqual(["failed"]);
+	});
+
+	it("refuses new jobs beyond the running and disk budgets", async () => {
+		const store = new JobStore({ maxRunning: 1, maxBytes: 8, ttlMs: 60_000 });
+		const first = deferredRunner();
+		const job = store.start(meta, first.run);
+		expect(() => store.start(meta, deferredRunner().run)).toThrow("Too many downloads");
+
+		first.resolve(await spooledFile(8, first.state));
+		await settled(store, job.id);
+		expect(() => store.start(meta, deferredRunner().run)).toThrow("storage is full");
+	});
+
+	it("stops a running job whose written bytes push usage over the disk budget", async () => {
+		const store = new JobStore({ maxRunning: 2, maxBytes: 100, ttlMs: 60_000 });
+		const finished = deferredRunner();
+		const finishedJob = store.start(meta, finished.run);
+		finished.resolve(await spooledFile(40, finished.state));
+		await settled(store, finishedJob.id);
+
+		const running = deferredRunner();
+		const job = store.start(meta, running.run);
+		running.state.progress(30, 60);
+		expect(running.state.signal?.aborted).toBe(false);
+		// A second job sees the running one's bytes: 40 finished + 60 written.
+		expect(() => store.start(meta, deferredRunner().run)).toThrow("storage is full");
+
+		running.state.progress(40, 61);
+		expect(running.state.signal?.aborted).toBe(true);
+		running.reject(new Error("The operation was aborted"));
+		expect(await settled(store, job.id)).toMatchObject({
+			status: "failed",
+			error: { code: "SERVER_BUSY", message: "Download storage is full; the download was stopped" },
+		});
+	});
+
+	it("cancels running jobs and deletes finished files once the TTL passes", async () => {
+		let now = 0;
+		const store = new JobStore({ maxRunning: 2, maxBytes: 1024, ttlMs: 1_000, now: () => now });
+		const running = deferredRunner();
+		const finished = deferredRunner();
+		const runningJob = store.start(meta, running.run);
+		const finishedJob = store.start(meta, finished.run);
+		finished.resolve(await spooledFile(4, finished.state));
+		await settled(store, finishedJob.id);
+
+		now = 1_000;
+		expect(store.get(runningJob.id)).toBeUndefined();
+		expect(store.get(finishedJob.id)).toBeUndefined();
+		expect(running.state.signal?.aborted).toBe(true);
+		expect(finished.state.cleanedUp).toBe(true);
+	});
+});
+
+describe("async download routes", () => {
+	it("rejects an unsigned job request", async () => {
+		const res = await app.fetch(
+			new Request("http://localhost:3001/api/download/async", {
+				method: "POST",
+				headers: { "Content-Type": "application/json" },
+				body: JSON.stringify({
+					url: "https://x.com/user/status/1",
+					choiceId: "a-mp3",
+					infoJson: "/etc/passwd",
+					sig: "deadbeef",
+				}),
+			}),
+		);
+		expect(res.status).toBe(403);
+	});
+
+	it("checks callbackUrl before anything else", async () => {
+		const prevSecret = process.env.WEBHOOK_SECRET;
+		const start = (callbackUrl: string) =>
+			app.fetch(
+				new Request("http://localhost:3001/api/download/async", {
+					method: "POST",
+					headers: { "Content-Type": "application/json" },
+					body: JSON.stringify({ url: "https://x.com/user/status/1", callbackUrl }),
+				}),
+			);
+		try {
+			delete process.env.WEBHOOK_SECRET;
+			const disabled = await start("https://hooks.example.com/snatch");
+			expect(disabled.status).toBe(400);
+			expect(await disabled.json()).toMatchObject({ field: "callbackUrl" });
+
+			process.env.WEBHOOK_SECRET = "s3cret";
+			const insecure = await start("http://hooks.example.com/snatch");
+			expect(insecure.status).toBe(400);
+			expect(await insecure.json()).toMatchObject({
+				error: "callbackUrl must be an absolute https URL",
+				field: "callbackUrl",
+			});
+		} finally {
+			if (prevSecret === undefined) delete process.env.WEBHOOK_SECRET;
+			else process.env.WEBHOOK_SECRET = prevSecret;
+		}
+	});
+
+	it("404s for an unknown job", async () => {
+		const status = await app.fetch(
+			new Request("http://localhost:3001/api/download/status/00000000-0000-0000-0000-000000000000"),
+		);
+		expect(status.status).toBe(404);
+		const result = await app.fetch(
+			new Request("http://localhost:3001/api/download/result/00000000-0000-0000-0000-000000000000"),
+		);
+		expect(result.status).toBe(404);
+	});
+});
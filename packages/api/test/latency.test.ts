@@ -0,0 +1,92 @@
+import { afterEach, beforeEach, describe, expect, it } from "bun:test";
+import {
+	adaptiveTimeoutMs,
+	clearLatencies,
+	MIN_LATENCY_SAMPLES,
+	nextEma,
+	recordLatency,
+} from "../src/lib/latency";
+import { probeTimeoutSecs } from "../src/lib/ytdlp";
+
+function warmUp(platform: string, durationMs: number) {
+	for (let i = 0; i < MIN_LATENCY_SAMPLES; i++) recordLatency(platform, durationMs);
+}
+
+describe("nextEma", () => {
+	it("seeds from the first sample and moves a fifth of the way after that", () => {
+		expect(nextEma(undefined, 2_000)).toBe(2_000);
+		expect(nextEma(2_000, 3_000)).toBe(2_200);
+		expect(nextEma(2_000, 1_500)).toBe(1_900);
+	});
+
+	it("counts a fast outlier as half the average at most", () => {
+		expect(nextEma(2_000, 10)).toBe(nextEma(2_000, 1_000));
+		expect(nextEma(2_000, 10)).toBe(1_800);
+	});
+});
+
+describe("adaptiveTimeoutMs", () => {
+	beforeEach(() => {
+		clearLatencies();
+	});
+
+	it("stays out of the way until a platform has enough samples", () => {
+		for (let i = 1; i < MIN_LATENCY_SAMPLES; i++) recordLatency("tiktok", 2_000);
+		expect(adaptiveTimeoutMs("tiktok", 5_000, 120_000)).toBeUndefined();
+		recordLatency("tiktok", 2_000);
+		expect(adaptiveTimeoutMs("tiktok", 5_000, 120_000)).toBe(8_000);
+	});
+
+	it("clamps four times the average to the given bounds", () => {
+		warmUp("twitter", 500);
+		warmUp("reddit", 60_000);
+		expect(adaptiveTimeoutMs("twitter", 5_000, 120_000)).toBe(5_000);
+		expect(adaptiveTimeoutMs("reddit", 5_000, 120_000)).toBe(120_000);
+	});
+
+	it("does not collapse after a single fast outlier", () => {
+		warmUp("instagram", 4_000);
+		recordLatency("instagram", 50);
+		expect(adaptiveTimeoutMs("instagram", 5_000, 120_000)).toBe(14_400);
+	});
+});
+
+describe("probeTimeoutSecs with latency samples", () => {
+	const prev = {
+		secs: process.env.EXTRACT_TIMEOUT_SECS,
+		max: process.env.EXTRACT_TIMEOUT_MAX_SECS,
+	};
+
+	beforeEach(() => {
+		clearLatencies();
+		delete process.env.EXTRACT_TIMEOUT_SECS;
+		delete process.env.EXTRACT_TIMEOUT_MAX_SECS;
+	});
+
+	afterEach(() => {
+		if (prev.secs === undefined) delete process.env.EXTRACT_TIMEOUT_SECS;
+		else process.env.EXTRACT_TIMEOUT_SECS = prev.secs;
+		if (prev.max === undefined) delete process.env.EXTRACT_TIMEOUT_MAX_SECS;
+		else process.env.EXTRACT_TIMEOUT_MAX_SECS = prev.max;
+	});
+
+	it("adapts the default per platform but never overrides the request", () => {
+		warmUp("tiktok", 2_500);
+		expect(probeTimeoutSecs(undefined, "tiktok")).toBe(10);
+		expect(probeTimeoutSecs(undefined, "twitter")).toBe(30);
+		expect(probeTimeoutSecs(20, "tiktok")).toBe(20);
+
+		process.env.EXTRACT_TIMEOUT_MAX_SECS = "8";
+		expect(probeTimeoutSecs(undefined, "tiktok")).toBe(8);
+	});
+
+	it("never lets the adaptive timeout exceed EXTRACT_TIMEOUT_SECS", () => {
+		warmUp("reddit", 10_000);
+		warmUp("tiktok", 2_500);
+		expect(probeTimeoutSecs(undefined, "reddit")).toBe(40);
+		process.env.EXTRACT_TIMEOUT_SECS = "20";
+		expect(probeTimeoutSecs(undefined, "reddit")).toBe(20);
+		expect(probeTimeoutSecs(undefined, "tiktok")).toBe(10);
+		expect(probeTimeoutSecs(60, "reddit")).toBe(60);
+	});
+});
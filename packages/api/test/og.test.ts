@@ -0,0 +1,93 @@
+import { afterEach, describe, expect, it } from "bun:test";
+import { ogFallback, ogFallbackEnabled, parseOgTags } from "../src/lib/og";
+import type { Fetcher } from "../src/lib/shortlink";
+
+const PAGE = `<html><head>
+<meta property="og:title" content="Reel by someone &amp; friends">
+<meta content="https://scontent.cdninstagram.com/v/thumb.jpg" property="og:image" />
+<meta property="og:video" content="http://scontent.cdninstagram.com/v/clip.mp4">
+<meta property="og:video:secure_url" content="https://scontent.cdninstagram.com/v/clip.mp4?a=1&amp;b=2">
+</head></html>`;
+
+/** Serves `body` for every request, recording the URLs it was asked for. */
+function page(body: string, status = 200): Fetcher & { seen: string[] } {
+	const seen: string[] = [];
+	const fetcher = async (url: string) => {
+		seen.push(url);
+		return new Response(body, { status });
+	};
+	return Object.assign(fetcher, { seen });
+}
+
+describe("parseOgTags", () => {
+	it("reads og tags in either attribute order and decodes entities", () => {
+		expect(parseOgTags(PAGE)).toMatchObject({
+			"og:title": "Reel by someone & friends",
+			"og:image": "https://scontent.cdninstagram.com/v/thumb.jpg",
+			"og:video:secure_url": "https://scontent.cdninstagram.com/v/clip.mp4?a=1&b=2",
+		});
+	});
+});
+
+describe("ogFallback", () => {
+	const original = process.env.OG_FALLBACK;
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.OG_FALLBACK;
+		else process.env.OG_FALLBACK = original;
+	});
+
+	it("prefers the secure video URL", async () => {
+		const media = await ogFallback("https://www.instagram.com/reel/Cabc1/", {
+			fetcher: page(PAGE),
+		});
+		expect(media).toEqual({
+			videoUrl: "https://scontent.cdninstagram.com/v/clip.mp4?a=1&b=2",
+			title: "Reel by someone & friends",
+			thumbnail: "https://scontent.cdninstagram.com/v/thumb.jpg",
+		});
+	});
+
+	it("never fetches pages outside the platform allowlist", async () => {
+		const fetcher = page(PAGE);
+		expect(await ogFallback("https://evil.example/reel/abc/", { fetcher })).toBeNull();
+		expect(fetcher.seen).toEqual([]);
+	});
+
+	it("returns null for error pages and pages without a video", async () => {
+		const url = "https://www.instagram.com/reel/Cabc1/";
+		expect(await ogFallback(url, { fetcher: page(PAGE, 404) })).toBeNull();
+		expect(await ogFallback(url, { fetcher: page("<html></html>") })).toBeNull();
+	});
+
+	it("stops reading a page at 512KB and cancels the rest", async () => {
+		const chunk = new TextEncoder().encode(`${" ".repeat(64 * 1024 - 1)}\n`);
+		let pulled = 0;
+		let cancelled = false;
+		const body = new ReadableStream<Uint8Array>({
+			start(controller) {
+				controller.enqueue(new TextEncoder().encode(PAGE));
+			},
+			// Endless filler: reading it whole would never finish.
+			pull(controller) {
+				pulled += chunk.byteLength;
+				controller.enqueue(chunk);
+			},
+			cancel() {
+				cancelled = true;
+			},
+		});
+		const fetcher: Fetcher = async () => new Response(body);
+		const media = await ogFallback("https://www.instagram.com/reel/Cabc1/", { fetcher });
+		expect(media?.videoUrl).toBe("https://scontent.cdninstagram.com/v/clip.mp4?a=1&b=2");
+		expect(cancelled).toBe(true);
+		expect(pulled).toBeLessThan(1024 * 1024);
+	});
+
+	it("can be switched off", () => {
+		process.env.OG_FALLBACK = "false";
+		expect(ogFallbackEnabled()).toBe(false);
+		delete process.env.OG_FALLBACK;
+		expect(ogFallbackEnabled()).toBe(true);
+	});
+});
@@ -0,0 +1,55 @@
+import { afterEach, describe, expect, it } from "bun:test";
+import { assertSafeOutbound, OutboundBlockedError, type Resolver } from "../src/lib/outbound";
+import { resolveShortLink } from "../src/lib/shortlink";
+
+/** Resolves every name to `addresses`. */
+const resolvesTo =
+	(...addresses: string[]): Resolver =>
+	async () =>
+		addresses;
+
+describe("assertSafeOutbound", () => {
+	const original = process.env.ALLOW_PRIVATE_OUTBOUND;
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.ALLOW_PRIVATE_OUTBOUND;
+		else process.env.ALLOW_PRIVATE_OUTBOUND = original;
+	});
+
+	it("lets public hosts through", async () => {
+		delete process.env.ALLOW_PRIVATE_OUTBOUND;
+		await assertSafeOutbound("https://t.co/abc", resolvesTo("104.244.42.1"));
+		await assertSafeOutbound("https://t.co/abc", async () => {
+			throw new Error("ENOTFOUND");
+		});
+	});
+
+	it("refuses internal names, private literals and names resolving privately", async () => {
+		delete process.env.ALLOW_PRIVATE_OUTBOUND;
+		const resolver = resolvesTo("104.244.42.1");
+		for (const url of [
+			"http://localhost:3001/",
+			"http://169.254.169.254/latest/meta-data/",
+			"http://[::1]/",
+			"http://redis.internal/",
+		]) {
+			await expect(assertSafeOutbound(url, resolver)).rejects.toBeInstanceOf(OutboundBlockedError);
+		}
+		await expect(
+			assertSafeOutbound("https://rebind.example.com/", resolvesTo("93.184.216.34", "10.0.0.5")),
+		).rejects.toThrow("resolves to a private address");
+	});
+
+	it("allows everything with ALLOW_PRIVATE_OUTBOUND=true", async () => {
+		process.env.ALLOW_PRIVATE_OUTBOUND = "true";
+		await assertSafeOutbound("http://localhost:3001/", resolvesTo("127.0.0.1"));
+	});
+
+	it("fails a short link instead of handing it on when a hop is refused", async () => {
+		const fetcher = async () => {
+			throw new OutboundBlockedError("Refusing to fetch internal host 'fb.watch'");
+		};
+		const pending = resolveShortLink("https://fb.watch/abc/", { fetcher });
+		await expect(pending).rejects.toBeInstanceOf(OutboundBlockedError);
+	});
+});
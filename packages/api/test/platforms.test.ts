@@ -0,0 +1,142 @@
+import { afterEach, describe, expect, it } from "bun:test";
+import {
+	ALLOWED_PLATFORM_DOMAINS,
+	OPT_IN_PLATFORMS,
+	type PlatformInfo,
+	SERVICES,
+} from "@snatch/shared";
+import app from "../src/app";
+import { platformDomains, validatePlatformUrl } from "../src/lib/platforms";
+import { validateMediaUrl } from "../src/schemas/media";
+
+describe("SUPPORTED_PLATFORMS", () => {
+	const original = process.env.SUPPORTED_PLATFORMS;
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.SUPPORTED_PLATFORMS;
+		else process.env.SUPPORTED_PLATFORMS = original;
+	});
+
+	it("defaults to the built-in hosts", () => {
+		delete process.env.SUPPORTED_PLATFORMS;
+		expect(platformDomains()).toEqual(ALLOWED_PLATFORM_DOMAINS);
+		expect(validatePlatformUrl("https://videos.example.com/v/1").valid).toBe(false);
+	});
+
+	it("extends the built-in hosts when the list includes default", () => {
+		process.env.SUPPORTED_PLATFORMS = "default, Videos.Example.com";
+		expect(validateMediaUrl("https://videos.example.com/v/1").valid).toBe(true);
+		expect(validateMediaUrl("https://x.com/user/status/1").valid).toBe(true);
+	});
+
+	it("replaces the built-in hosts otherwise", () => {
+		process.env.SUPPORTED_PLATFORMS = "videos.example.com";
+		expect(platformDomains()).toEqual(["videos.example.com"]);
+		expect(validateMediaUrl("https://x.com/user/status/1").code).toBe("UNSUPPORTED_PLATFORM");
+	});
+
+	it("rejects entries that are not domain names", () => {
+		for (const value of ["https://videos.example.com", "example", "*.example.com", ","]) {
+			process.env.SUPPORTED_PLATFORMS = value;
+			expect(() => platformDomains()).toThrow("SUPPORTED_PLATFORMS");
+		}
+	});
+});
+
+describe("GET /api/platforms", () => {
+	const original = process.env.SUPPORTED_PLATFORMS;
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.SUPPORTED_PLATFORMS;
+		else process.env.SUPPORTED_PLATFORMS = original;
+	});
+
+	async function platforms(): Promise<PlatformInfo[]> {
+		const res = await app.fetch(new Request("http://localhost:3001/api/platforms"));
+		expect(res.status).toBe(200);
+		return (await res.json()) as PlatformInfo[];
+	}
+
+	it("lists every built-in service with a valid example", async () => {
+		delete process.env.SUPPORTED_PLATFORMS;
+		const list = await platforms();
+		const builtIn = SERVICES.filter((service) => !OPT_IN_PLATFORMS.includes(service.id));
+		expect(list.map((platform) => platform.id)).toEqual(builtIn.map((service) => service.id));
+		for (const platform of list) {
+			expect(validatePlatformUrl(platform.exampleUrl)).toEqual({ valid: true });
+		}
+		expect(list.find((platform) => platform.id === "twitter")).toMatchObject({
+			name: "X / Twitter",
+			domains: ["x.com", "twitter.com"],
+			supportsProfiles: false,
+		});
+		expect(list.find((platform) => platform.id === "tiktok")?.supportsProfiles).toBe(true);
+	});
+
+	it("follows the deployment's SUPPORTED_PLATFORMS", async () => {
+		process.env.SUPPORTED_PLATFORMS = "x.com, videos.example.com";
+		const list = await platforms();
+		expect(list.map((platform) => [platform.id, platform.domains])).toEqual([
+			["twitter", ["x.com"]],
+		]);
+	});
+});
+
+describe("ENABLE_YOUTUBE_SHORTS", () => {
+	const original = process.env.ENABLE_YOUTUBE_SHORTS;
+	const shorts = "https://www.youtube.com/shorts/jNQXAC9IVRw";
+
+	afterEach(() => {
+		if (original === undefined) delete process.env.ENABLE_YOUTUBE_SHORTS;
+		else process.env.ENABLE_YOUTUBE_SHORTS = original;
+	});
+
+	function resolve(url: string): Promise<Response> {
+		return app.fetch(
+			new Request("http://localhost:3001/api/resolve", {
+				method: "POST",
+				headers: { "Content-Type": "application/json" },
+				body: JSON.stringify({ url }),
+			}),
+		);
+	}
+
+	it("keeps YouTube off unless enabled", async () => {
+		delete process.env.ENABLE_YOUTUBE_SHORTS;
+		expect(platformDomains()).toEqual(ALLOWED_PLATFORM_DOMAINS);
+		expect(validatePlatformUrl(shorts).code).toBe("UNSUPPORTED_PLATFORM");
+		const res = await app.fetch(new Request("http://localhost:3001/api/platforms"));
+		const list = (await res.json()) as PlatformInfo[];
+		expect(list.some((platform) => platform.id === "youtube")).toBe(false);
+	});
+
+	it("admits Shorts and youtu.be links once enabled", async () => {
+		process.env.ENABLE_YOUTUBE_SHORTS = "true";
+		expect(platformDomains()).toEqual([...ALLOWED_PLATFORM_DOMAINS, "youtube.com", "youtu.be"]);
+		expect(validatePlatformUrl(shorts)).toEqual({ valid: true });
+		expect(validatePlatformUrl("https://youtu.be/jNQXAC9IVRw")).toEqual({ valid: true });
+		const res = await app.fetch(new Request("http://localhost:3001/api/platforms"));
+		const list = (await res.json()) as PlatformInfo[];
+		expect(list.find((platform) => platform.id === "youtube")).toMatchObject({
+			name: "YouTube Shorts",
+			exampleUrl: shorts,
+		});
+	});
+
+	it("answers 400 for watch pages and channels whether enabled or not", async () => {
+		for (const flag of [undefined, "true"]) {
+			if (flag === undefined) delete process.env.ENABLE_YOUTUBE_SHORTS;
+			else process.env.ENABLE_YOUTUBE_SHORTS = flag;
+			for (const url of [
+				"https://www.youtube.com/watch?v=jNQXAC9IVRw",
+				"https://www.youtube.com/@channel",
+				"https://www.youtube.com/channel/UC123/videos",
+			]) {
+				const res = await resolve(url);
+				expect(res.status).toBe(400);
+				const body = (await res.json()) as { code: string };
+				expect(body.code).toBe(flag ? "INVALID_URL" : "UNSUPPORTED_PLATFORM");
+			}
+		}
+	});
+});
@@ -0,0 +1,91 @@
+import { afterEach, describe, expect, it } from "bun:test";
+import app from "../src/app";
+import { extractionQueue, QueueBusyError, WorkQueue } from "../src/lib/queue";
+
+/** A task that stays running until `finish()` is called. */
+function gate() {
+	let finish = () => {};
+	const done = new Promise<void>((resolve) => {
+		finish = resolve;
+	});
+	return { finish, task: () => done };
+}
+
+describe("WorkQueue", () => {
+	it("runs waiting tasks in arrival order as slots free up", async () => {
+		const queue = new WorkQueue({ concurrency: 1, maxDepth: 5, maxWaitMs: 1_000 });
+		const first = gate();
+		const order: number[] = [];
+		const running = queue.run(first.task);
+		const queued = [1, 2, 3].map((n) => queue.run(async () => order.push(n)));
+		expect(queue.running).toBe(1);
+		expect(queue.length).toBe(3);
+
+		first.finish();
+		await Promise.all([running, ...queued]);
+		expect(order).toEqual([1, 2, 3]);
+		expect(queue.running).toBe(0);
+		expect(queue.length).toBe(0);
+	});
+
+	it("refuses a task once the line is full", async () => {
+		const queue = new WorkQueue({ concurrency: 1, maxDepth: 1, maxWaitMs: 1_000 });
+		const first = gate();
+		const running = queue.run(first.task);
+		const waiting = queue.run(async () => "served");
+
+		await expect(queue.run(async () => "refused")).rejects.toBeInstanceOf(QueueBusyError);
+		first.finish();
+		expect(await waiting).toBe("served");
+		await running;
+	});
+
+	it("gives up on a task that waits longer than maxWaitMs", async () => {
+		const queue = new WorkQueue({ concurrency: 1, maxDepth: 5, maxWaitMs: 20 });
+		const first = gate();
+		const running = queue.run(first.task);
+
+		await expect(queue.run(async () => "late")).rejects.toMatchObject({
+			code: "SERVER_BUSY",
+			status: 503,
+		});
+		expect(queue.length).toBe(0);
+		first.finish();
+		await running;
+		expect(queue.running).toBe(0);
+	});
+
+	it("drops a waiter whose signal aborts", async () => {
+		const queue = new WorkQueue({ concurrency: 1, maxDepth: 5, maxWaitMs: 1_000 });
+		const first = gate();
+		const running = queue.run(first.task);
+		const controller = new AbortController();
+		const abandoned = queue.run(async () => "never", controller.signal);
+
+		controller.abort(new Error("client left"));
+		await expect(abandoned).rejects.toThrow("client left");
+		expect(queue.length).toBe(0);
+		first.finish();
+		await running;
+	});
+});
+
+describe("extraction queue on /health", () => {
+	const prev = process.env.EXTRACT_CONCURRENCY;
+
+	afterEach(() => {
+		if (prev === undefined) delete process.env.EXTRACT_CONCURRENCY;
+		else process.env.EXTRACT_CONCURRENCY = prev;
+	});
+
+	it("reports the queue's load only when queueing is on", async () => {
+		delete process.env.EXTRACT_CONCURRENCY;
+		expect(extractionQueue()).toBeUndefined();
+		const off = await app.fetch(new Request("http://localhost:3001/health"));
+		expect(await off.json()).not.toHaveProperty("queue");
+
+		process.env.EXTRACT_CONCURRENCY = "2";
+		const on = await app.fetch(new Request("http://localhost:3001/health"));
+		expect(await on.json()).toMatchObject({ queue: { running: 0, waiting: 0 } });
+	});
+});
@@ -0,0 +1,35 @@
+import { describe, expect, it } from "bun:test";
+import { parseRange } from "../src/lib/range";
+
+describe("parseRange", () => {
+	it("parses an explicit byte range", () => {
+		expect(parseRange("bytes=0-499", 1000)).toEqual({ start: 0, end: 499 });
+	});
+
+	it("parses an open-ended range to the end of the file", () => {
+		expect(parseRange("bytes=500-", 1000)).toEqual({ start: 500, end: 999 });
+	});
+
+	it("parses a suffix range", () => {
+		expect(parseRange("bytes=-200", 1000)).toEqual({ start: 800, end: 999 });
+		expect(parseRange("bytes=-5000", 1000)).toEqual({ start: 0, end: 999 });
+	});
+
+	it("clamps an end past the file size", () => {
+		expect(parseRange("bytes=900-5000", 1000)).toEqual({ start: 900, end: 999 });
+	});
+
+	it("reports ranges starting past the end as unsatisfiable", () => {
+		expect(parseRange("bytes=1000-", 1000)).toBe("unsatisfiable");
+		expect(parseRange("bytes=2000-3000", 1000)).toBe("unsatisfiable");
+		expect(parseRange("bytes=-0", 1000)).toBe("unsatisfiable");
+	});
+
+	it("ignores absent, malformed and multi-range headers", () => {
+		expect(parseRange(undefined, 1000)).toBeNull();
+		expect(parseRange("bytes=-", 1000)).toBeNull();
+		expect(parseRange("items=0-10", 1000)).toBeNull();
+		expect(parseRange("bytes=500-100", 1000)).toBeNull();
+		expect(parseRange("bytes=0-10,20-30", 1000)).toBeNull();
+	});
+});
@@ -0,0 +1,70 @@
+import { beforeEach, describe, expect, it } from "bun:test";
+import app from "../src/app";
+import { clearCooldowns } from "../src/lib/cooldown";
+import { clearClients } from "../src/middleware/rate-limit";
+import { useStubYtDlp } from "./stub-ytdlp";
+
+function refresh(url: string) {
+	return app.fetch(
+		new Request("http://localhost:3001/api/refresh", {
+			method: "POST",
+			headers: { "Content-Type": "application/json" },
+			body: JSON.stringify({ url }),
+		}),
+	);
+}
+
+describe("POST /api/refresh", () => {
+	const stub = useStubYtDlp();
+
+	beforeEach(() => {
+		clearClients();
+		clearCooldowns();
+	});
+
+	it("returns only the re-signed choices", async () => {
+		await stub.probeJson({
+			id: "1",
+			title: "Clip",
+			uploader: "User",
+			thumbnail: "https://pbs.twimg.com/1.jpg",
+			formats: [{ format_id: "hls-720", vcodec: "avc1", acodec: "mp4a", height: 720, ext: "mp4" }],
+		});
+
+		const res = await refresh("https://x.com/user/status/1");
+		expect(res.status).toBe(200);
+		const data = (await res.json()) as { status: string; picker: { id: string }[] };
+		expect(Object.keys(data).sort()).toEqual(["picker", "status"]);
+		expect(data.picker.map((choice) => choice.id)).toEqual(["v-720p", "a-mp3"]);
+		// Formats only: no thumbnails are carried over from the metadata.
+		expect(data.picker.every((choice) => !("thumb" in choice))).toBe(true);
+	});
+
+	it("refuses a second refresh of the same URL with 429 and Retry-After", async () => {
+		await stub.probeJson({ id: "2", title: "Clip" });
+		expect((await refresh("https://x.com/user/status/2")).status).toBe(200);
+
+		const again = await refresh("https://x.com/user/status/2");
+		expect(again.status).toBe(429);
+		const retryAfter = Number(again.headers.get("Retry-After"));
+		expect(retryAfter).toBeGreaterThan(0);
+		expect(await again.json()).toMatchObject({ code: "RATE_LIMITED", retryAfter });
+		// The cooldown follows the post ID, not the spelling of its URL.
+		expect((await refresh("https://x.com/renamed/status/2")).status).toBe(429);
+
+		expect((await refresh("https://x.com/user/status/3")).status).toBe(200);
+	});
+
+	it("lets a failed refresh be retried at once", async () => {
+		await stub.script('echo "ERROR: [twitter] 4: Private video" >&2; exit 1');
+		expect((await refresh("https://x.com/user/status/4")).status).toBe(403);
+
+		await stub.probeJson({ id: "4", title: "Clip" });
+		expect((await refresh("https://x.com/user/status/4")).status).toBe(200);
+	});
+
+	it("rejects profile URLs, which have no links to refresh", async () => {
+		const res = await refresh("https://www.tiktok.com/@someone");
+		expect(res.status).toBe(400);
+	});
+});
@@ -0,0 +1,295 @@
+import { beforeEach, describe, expect, it, spyOn } from "bun:test";
+import { readFile } from "node:fs/promises";
+import path from "node:path";
+import app from "../src/app";
+import { publicBaseUrl } from "../src/lib/env";
+import { logger } from "../src/lib/logger";
+import { clearClients } from "../src/middleware/rate-limit";
+import { useStubYtDlp } from "./stub-ytdlp";
+
+const TWEET = "https://x.com/user/status/1";
+
+/** POST /api/resolve against whatever stub `YTDLP_PATH` points at. */
+function resolve(url = TWEET, headers: Record<string, string> = {}) {
+	return app.fetch(
+		new Request("http://localhost:3001/api/resolve", {
+			method: "POST",
+			headers: { "Content-Type": "application/json", ...headers },
+			body: JSON.stringify({ url }),
+		}),
+	);
+}
+
+describe("POST /api/resolve with a stub yt-dlp", () => {
+	const stub = useStubYtDlp();
+
+	beforeEach(() => {
+		clearClients();
+	});
+
+	it("returns a signed picker built from the probe output", async () => {
+		const info = {
+			id: "1",
+			title: "Clip",
+			uploader: "User",
+			formats: [
+				{ format_id: "hls-720", vcodec: "avc1", acodec: "mp4a", height: 720, ext: "mp4" },
+				{ format_id: "audio", vcodec: "none", acodec: "mp4a", abr: 128, ext: "m4a" },
+			],
+		};
+		await stub.probeJson(info);
+
+		const res = await resolve();
+		expect(res.status).toBe(200);
+		const signed = expect.stringMatching(
+			/^http:\/\/localhost:3001\/api\/download\?url=.+&sig=[0-9a-f]{64}$/,
+		);
+		expect(await res.json()).toEqual({
+			status: "picker",
+			platform: "twitter",
+			videoId: "1",
+			extractedAt: expect.any(Number),
+			title: "Clip",
+			uploader: "User",
+			isLive: false,
+			filename: "Clip.mp4",
+			picker: [
+				{
+					formatId: "hls-720",
+					height: 720,
+					vcodec: "avc1",
+					acodec: "mp4a",
+					hasAudio: true,
+					id: "v-720p",
+					type: "video",
+					quality: "720p",
+					ext: "mp4",
+					label: "720p (mp4)",
+					url: signed,
+				},
+				{
+					formatId: "audio",
+					vcodec: "none",
+					acodec: "mp4a",
+					hasAudio: true,
+					id: "a-mp3",
+					type: "audio",
+					quality: "mp3",
+					ext: "mp3",
+					label: "Audio Only (mp3)",
+					url: signed,
+				},
+			],
+		});
+	});
+
+	it("builds download links on PUBLIC_BASE_URL when it is set", async () => {
+		await stub.probeJson({ id: "1", title: "Clip" });
+		const prevBase = process.env.PUBLIC_BASE_URL;
+		process.env.PUBLIC_BASE_URL = "https://api.example.com/snatch/";
+		try {
+			const res = await resolve();
+			const data = (await res.json()) as { picker: { url: string }[] };
+			expect(data.picker.length).toBeGreaterThan(0);
+			for (const choice of data.picker) {
+				expect(choice.url).toStartWith("https://api.example.com/snatch/api/download?url=");
+			}
+
+			process.env.PUBLIC_BASE_URL = "ftp://api.example.com";
+			expect(() => publicBaseUrl()).toThrow("PUBLIC_BASE_URL");
+		} finally {
+			if (prevBase === undefined) delete process.env.PUBLIC_BASE_URL;
+			else process.env.PUBLIC_BASE_URL = prevBase;
+		}
+	});
+
+	it("maps a classified yt-dlp failure to its status and code", async () => {
+		await stub.script('echo "ERROR: [twitter] 1: Private video" >&2; exit 1');
+
+		const res = await resolve();
+		expect(res.status).toBe(403);
+		expect(await res.json()).toEqual({
+			status: "error",
+			error: { code: "PRIVATE_CONTENT", message: "1: Private video" },
+		});
+	});
+
+	it("reports a successful run with empty stdout as NO_METADATA", async () => {
+		await stub.script("exit 0");
+
+		const res = await resolve();
+		expect(res.status).toBe(502);
+		expect(await res.json()).toEqual({
+			status: "error",
+			error: { code: "NO_METADATA", message: "No metadata returned" },
+		});
+	});
+
+	it("logs one structured event per extraction", async () => {
+		const info = spyOn(logger, "info");
+		try {
+			await stub.script('echo "ERROR: [twitter] 1: Private video" >&2; exit 1');
+			await resolve();
+			await stub.script(`echo '{"id":"1","title":"Clip"}'`);
+			await resolve();
+
+			const events = info.mock.calls
+				.filter(([, message]) => message === "extraction finished")
+				.map(([fields]) => fields);
+			expect(events).toEqual([
+				{
+					requestId: expect.any(String),
+					platform: "twitter",
+					attempts: 1,
+					cacheHit: false,
+					durationMs: expect.any(Number),
+					outcome: "error",
+					errorCode: "PRIVATE_CONTENT",
+				},
+				{
+					requestId: expect.any(String),
+					platform: "twitter",
+					attempts: 1,
+					cacheHit: false,
+					durationMs: expect.any(Number),
+					outcome: "success",
+					errorCode: undefined,
+				},
+			]);
+		} finally {
+			info.mockRestore();
+		}
+	});
+
+	it("tags every yt-dlp run with the caller's request id", async () => {
+		const debug = spyOn(logger, "debug");
+		try {
+			await stub.script('echo "ERROR: [twitter] 1: Private video" >&2; exit 1');
+			const res = await resolve(TWEET, { "X-Request-Id": "trace-123" });
+			expect(res.headers.get("X-Request-Id")).toBe("trace-123");
+
+			const spans = debug.mock.calls
+				.filter(([, message]) => message === "yt-dlp run finished")
+				.map(([fields]) => fields);
+			expect(spans).toEqual([
+				{
+					span: "yt_dlp.extract",
+					requestId: "trace-123",
+					platform: "twitter",
+					urlHash: expect.stringMatching(/^[0-9a-f]{12}$/),
+					attempt: 1,
+					durationMs: expect.any(Number),
+					exitCode: 1,
+					stderrBytes: expect.any(Number),
+				},
+			]);
+		} finally {
+			debug.mockRestore();
+		}
+	});
+
+	it("probes the URL without share tracking parameters", async () => {
+		const argsFile = path.join(stub.dir, "args");
+		await stub.script(`echo "$@" > '${argsFile}'\necho '{"id":"1","title":"Clip"}'`);
+		const debug = spyOn(logger, "debug");
+		try {
+			expect((await resolve(`${TWEET}?s=20&t=AbCdEf`)).status).toBe(200);
+			expect(await readFile(argsFile, "utf-8")).toEndWith(` ${TWEET}\n`);
+			const cleaned = debug.mock.calls.filter(([, message]) => message === "URL canonicalized");
+			expect(cleaned.map(([fields]) => fields)).toEqual([
+				{ original: `${TWEET}?s=20&t=AbCdEf`, canonical: TWEET },
+			]);
+		} finally {
+			debug.mockRestore();
+		}
+	});
+
+	it("probes a link pasted without a scheme as https", async () => {
+		const argsFile = path.join(stub.dir, "args");
+		await stub.script(`echo "$@" > '${argsFile}'\necho '{"id":"1","title":"Clip"}'`);
+		expect((await resolve(TWEET.replace("https://", ""))).status).toBe(200);
+		expect(await readFile(argsFile, "utf-8")).toEndWith(` ${TWEET}\n`);
+
+		const res = await resolve("example.com/video/1");
+		expect(res.status).toBe(400);
+		expect(((await res.json()) as { code: string }).code).toBe("INVALID_URL");
+	});
+
+	it("probes mobile and legacy hosts on the platform's canonical host", async () => {
+		const argsFile = path.join(stub.dir, "args");
+		await stub.script(`echo "$@" > '${argsFile}'\necho '{"id":"1","title":"Clip"}'`);
+		for (const host of ["mobile.twitter.com", "twitter.com", "www.x.com"]) {
+			expect((await resolve(TWEET.replace("x.com", host))).status).toBe(200);
+			expect(await readFile(argsFile, "utf-8")).toEndWith(` ${TWEET}\n`);
+		}
+	});
+
+	it("probes a trusted CDN link only when DIRECT_CDN_URLS is on", async () => {
+		await stub.probeJson({ id: "clip", title: "clip", formats: [{ format_id: "mp4" }] });
+		const cdn = "https://scontent-lax3-1.cdninstagram.com/v/t50/clip.mp4";
+		const prevDirect = process.env.DIRECT_CDN_URLS;
+		try {
+			delete process.env.DIRECT_CDN_URLS;
+			const off = await resolve(cdn);
+			expect(off.status).toBe(400);
+			expect(((await off.json()) as { code: string }).code).toBe("UNSUPPORTED_PLATFORM");
+
+			process.env.DIRECT_CDN_URLS = "true";
+			expect((await resolve(cdn)).status).toBe(200);
+			const untrusted = await resolve("https://cdn.example.com/clip.mp4");
+			expect(untrusted.status).toBe(400);
+		} finally {
+			if (prevDirect === undefined) delete process.env.DIRECT_CDN_URLS;
+			else process.env.DIRECT_CDN_URLS = prevDirect;
+		}
+	});
+
+	it("kills the probe when the client disconnects", async () => {
+		const pidFile = path.join(stub.dir, "probe.pid");
+		await stub.script(`echo $$ > '${pidFile}'\nexec sleep 30`);
+		const client = new AbortController();
+		const pending = app.fetch(
+			new Request("http://localhost:3001/api/resolve", {
+				method: "POST",
+				headers: { "Content-Type": "application/json" },
+				body: JSON.stringify({ url: TWEET }),
+				signal: client.signal,
+			}),
+		);
+		for (let i = 0; i < 200 && !(await Bun.file(pidFile).exists()); i++) await Bun.sleep(10);
+
+		client.abort();
+		await pending;
+		const pid = Number(await readFile(pidFile, "utf-8"));
+		expect(pid).toBeGreaterThan(0);
+		// The handler only settles once yt-dlp has exited; signal 0 checks it is gone.
+		expect(() => process.kill(pid, 0)).toThrow();
+	});
+
+	it("leaves out extractedAt when EXTRACTED_AT is false", async () => {
+		await stub.script(`echo '{"id":"1","title":"Clip"}'`);
+		const stamped = await resolve();
+		expect(((await stamped.json()) as { extractedAt: number }).extractedAt).toBeGreaterThan(0);
+
+		process.env.EXTRACTED_AT = "false";
+		try {
+			const res = await resolve();
+			expect(res.status).toBe(200);
+			expect(await res.json()).not.toHaveProperty("extractedAt");
+		} finally {
+			delete process.env.EXTRACTED_AT;
+		}
+	});
+
+	it("reports an unrunnable YTDLP_PATH as unavailable instead of downloading", async () => {
+		process.env.YTDLP_PATH = path.join(stub.dir, "missing");
+		try {
+			const res = await resolve();
+			expect(res.status).toBe(503);
+			const data = (await res.json()) as { error: { code: string } };
+			expect(data.error.code).toBe("YTDLP_UNAVAILABLE");
+		} finally {
+			process.env.YTDLP_PATH = stub.path;
+		}
+	});
+});
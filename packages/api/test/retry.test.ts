@@ -0,0 +1,246 @@
+import { afterEach, describe, expect, it } from "bun:test";
+import { backoffDelay, DEFAULT_BACKOFF, retryPolicy, retryWithBackoff } from "../src/lib/retry";
+import {
+	isRetryableError,
+	RATE_LIMIT_BACKOFF,
+	ytdlpBackoffFor,
+	YtDlpError,
+} from "../src/lib/ytdlp";
+
+/** A `sleep` that records the requested delays instead of waiting. */
+function recordingSleep() {
+	const delays: number[] = [];
+	const sleep = async (ms: number) => {
+		delays.push(ms);
+	};
+	return { delays, sleep };
+}
+
+describe("retryWithBackoff", () => {
+	it("returns the first successful result", async () => {
+		let calls = 0;
+		const result = await retryWithBackoff(
+			async (attempt) => {
+				calls++;
+				if (attempt < 3) throw new Error("transient");
+				return "done";
+			},
+			{ attempts: 3, initialDelayMs: 0 },
+		);
+		expect(result).toBe("done");
+		expect(calls).toBe(3);
+	});
+
+	it("rethrows the last error once attempts are exhausted", async () => {
+		let calls = 0;
+		const run = retryWithBackoff(
+			async (attempt) => {
+				calls++;
+				throw new Error(`failure ${attempt}`);
+			},
+			{ attempts: 2, initialDelayMs: 0 },
+		);
+		await expect(run).rejects.toThrow("failure 2");
+		expect(calls).toBe(2);
+	});
+
+	it("stops immediately when shouldRetry rejects the error", async () => {
+		let calls = 0;
+		const run = retryWithBackoff(
+			async () => {
+				calls++;
+				throw new Error("permanent");
+			},
+			{ attempts: 3, initialDelayMs: 0, shouldRetry: () => false },
+		);
+		await expect(run).rejects.toThrow("permanent");
+		expect(calls).toBe(1);
+	});
+
+	it("doubles the delay up to maxDelayMs", async () => {
+		const { delays, sleep } = recordingSleep();
+		const run = retryWithBackoff(
+			async () => {
+				throw new Error("transient");
+			},
+			{ attempts: 5, initialDelayMs: 100, maxDelayMs: 300, sleep },
+		);
+		await expect(run).rejects.toThrow("transient");
+		expect(delays).toEqual([100, 200, 300, 300]);
+	});
+
+	it("skips a retry that would start past deadlineMs", async () => {
+		let clock = 0;
+		const delays: number[] = [];
+		const sleep = async (ms: number) => {
+			delays.push(ms);
+			clock += ms;
+		};
+		let calls = 0;
+		const run = retryWithBackoff(
+			async () => {
+				calls++;
+				clock += 100;
+				throw new Error("transient");
+			},
+			{ attempts: 5, initialDelayMs: 200, deadlineMs: 1_000, sleep, now: () => clock },
+		);
+		await expect(run).rejects.toThrow("transient");
+		// Attempts end at 100, 400 and 900ms; an 800ms wait from there would pass the deadline.
+		expect(delays).toEqual([200, 400]);
+		expect(calls).toBe(3);
+	});
+});
+
+describe("backoffDelay", () => {
+	it("adds at most `jitter` of the base delay", () => {
+		for (let i = 0; i < 50; i++) {
+			const delay = backoffDelay({ attempts: 3, initialDelayMs: 1_000, jitter: 0.5 }, 2);
+			expect(delay).toBeGreaterThanOrEqual(2_000);
+			expect(delay).toBeLessThanOrEqual(3_000);
+		}
+	});
+
+	it("draws a fully jittered delay from anywhere below the base delay", () => {
+		const backoff = { attempts: 3, initialDelayMs: 1_000, jitter: "full" } as const;
+		expect(backoffDelay(backoff, 2, () => 0)).toBe(0);
+		expect(backoffDelay(backoff, 2, () => 0.25)).toBe(500);
+		const delays = Array.from({ length: 50 }, () => backoffDelay(backoff, 2));
+		for (const delay of delays) {
+			expect(delay).toBeGreaterThanOrEqual(0);
+			expect(delay).toBeLessThan(2_000);
+		}
+		expect(new Set(delays).size).toBeGreaterThan(1);
+	});
+
+	it("grows the delay by `multiplier`", () => {
+		const backoff = { attempts: 4, initialDelayMs: 100, multiplier: 3 };
+		expect([1, 2, 3].map((attempt) => backoffDelay(backoff, attempt))).toEqual([100, 300, 900]);
+	});
+});
+
+describe("DEFAULT_BACKOFF", () => {
+	it("jitters each delay below the 500ms, 1s schedule", async () => {
+		const { delays, sleep } = recordingSleep();
+		const run = retryWithBackoff(
+			async () => {
+				throw new Error("transient");
+			},
+			{ ...DEFAULT_BACKOFF, sleep, random: () => 0.5 },
+		);
+		await expect(run).rejects.toThrow("transient");
+		expect(delays).toEqual([250, 500]);
+	});
+});
+
+describe("retryPolicy", () => {
+	const VARS = [
+		"RETRY_MAX_ATTEMPTS",
+		"RETRY_INITIAL_MS",
+		"RETRY_MAX_DELAY_MS",
+		"RETRY_DEADLINE_MS",
+	] as const;
+	const prev = VARS.map((name) => [name, process.env[name]] as const);
+
+	afterEach(() => {
+		for (const [name, value] of prev) {
+			if (value === undefined) delete process.env[name];
+			else process.env[name] = value;
+		}
+	});
+
+	it("is DEFAULT_BACKOFF while no RETRY_* variable is set", () => {
+		for (const name of VARS) delete process.env[name];
+		expect(retryPolicy()).toEqual(DEFAULT_BACKOFF);
+	});
+
+	it("applies the RETRY_* variables over the default schedule", () => {
+		process.env.RETRY_MAX_ATTEMPTS = "5";
+		process.env.RETRY_INITIAL_MS = "2000";
+		process.env.RETRY_MAX_DELAY_MS = "10000";
+		process.env.RETRY_DEADLINE_MS = "30000";
+		expect(retryPolicy()).toEqual({
+			...DEFAULT_BACKOFF,
+			attempts: 5,
+			initialDelayMs: 2_000,
+			maxDelayMs: 10_000,
+			deadlineMs: 30_000,
+		});
+	});
+
+	it("rejects malformed or inconsistent values", () => {
+		process.env.RETRY_MAX_ATTEMPTS = "three";
+		expect(() => retryPolicy()).toThrow("RETRY_MAX_ATTEMPTS must be a positive integer");
+		process.env.RETRY_MAX_ATTEMPTS = "50";
+		expect(() => retryPolicy()).toThrow("RETRY_MAX_ATTEMPTS must be at most 10");
+		delete process.env.RETRY_MAX_ATTEMPTS;
+
+		process.env.RETRY_DEADLINE_MS = "0";
+		expect(() => retryPolicy()).toThrow("RETRY_DEADLINE_MS must be a positive integer");
+		delete process.env.RETRY_DEADLINE_MS;
+
+		process.env.RETRY_INITIAL_MS = "1000";
+		process.env.RETRY_MAX_DELAY_MS = "500";
+		expect(() => retryPolicy()).toThrow("RETRY_MAX_DELAY_MS must be at least 1000");
+	});
+});
+
+describe("retryWithBackoff with isRetryableError", () => {
+	it("gives up after one attempt on a permanent yt-dlp failure", async () => {
+		let calls = 0;
+		const run = retryWithBackoff(
+			async () => {
+				calls++;
+				throw new YtDlpError("ERROR: [youtube] abc: Private video", "probe failed");
+			},
+			{ attempts: 3, initialDelayMs: 0, shouldRetry: isRetryableError },
+		);
+		await expect(run).rejects.toThrow("Private video");
+		expect(calls).toBe(1);
+	});
+
+	it("retries transient network failures", async () => {
+		let calls = 0;
+		const run = retryWithBackoff(
+			async () => {
+				calls++;
+				throw new YtDlpError("ERROR: Unable to download webpage: timed out", "probe failed");
+			},
+			{ attempts: 3, initialDelayMs: 0, shouldRetry: isRetryableError },
+		);
+		await expect(run).rejects.toThrow("timed out");
+		expect(calls).toBe(3);
+	});
+
+	it("backs off longer and gives up sooner on a platform 429", async () => {
+		const { delays, sleep } = recordingSleep();
+		let calls = 0;
+		const run = retryWithBackoff(
+			async () => {
+				calls++;
+				throw new YtDlpError("ERROR: Unable to download: HTTP Error 429: Too Many Requests", "x");
+			},
+			{
+				attempts: 3,
+				initialDelayMs: 500,
+				shouldRetry: isRetryableError,
+				backoffFor: ytdlpBackoffFor,
+				sleep,
+			},
+		);
+		await expect(run).rejects.toMatchObject({ code: "RATE_LIMITED" });
+		expect(calls).toBe(RATE_LIMIT_BACKOFF.attempts);
+		expect(delays).toHaveLength(1);
+		expect(delays[0]).toBeGreaterThanOrEqual(5_000);
+		expect(delays[0]).toBeLessThanOrEqual(RATE_LIMIT_BACKOFF.maxDelayMs ?? 0);
+	});
+});
+
+describe("isRetryableError", () => {
+	it("treats unsupported URLs and spawn errors as permanent", () => {
+		expect(isRetryableError(new YtDlpError("ERROR: Unsupported URL: https://x.com/", "x"))).toBe(
+			false,
+		);
+		expect(isRetryableError(new Error("spawn yt-dlp ENOENT"))).toBe(false);
+	});
+});
@@ -14,11 +14,82 @@ describe("API Routes", () => {
 				}),
 			);
 			expect(res.status).toBe(400);
-			const data = (await res.json()) as { success: boolean; error: string };
+			const data = (await res.json()) as { success: boolean; error: string; code: string };
 			expect(data.success).toBe(false);
+			expect(data.code).toBe("INVALID_URL");
 		});
 
-		it("should reject unsupported media options with 400", async () => {
+		it("should point at an invalid character in the URL", async () => {
+			const res = await app.fetch(
+				new Request("http://localhost:3001/api/resolve", {
+					method: "POST",
+					headers: { "Content-Type": "application/json" },
+					body: JSON.stringify({ url: "https://x.com/\u00fcser/status/1\nX-Injected: 1" }),
+				}),
+			);
+			expect(res.status).toBe(400);
+			expect(await res.json()).toEqual({
+				success: false,
+				error: "URL contains an invalid character at position 27",
+				code: "INVALID_CHAR",
+				position: 27,
+				character: "\n",
+			});
+		});
+
+		it("should tag unsupported hosts and malformed JSON with distinct codes", async () => {
+			const post = (body: string) =>
+				app.fetch(
+					new Request("http://localhost:3001/api/resolve", {
+						method: "POST",
+						headers: { "Content-Type": "application/json" },
+						body,
+					}),
+				);
+			const unsupported = await post(JSON.stringify({ url: "https://example.com/v/1" }));
+			expect(unsupported.status).toBe(400);
+			expect(((await unsupported.json()) as { code: string }).code).toBe("UNSUPPORTED_PLATFORM");
+
+			const malformed = await post("{not json");
+			expect(malformed.status).toBe(422);
+			expect(((await malformed.json()) as { code: string }).code).toBe("BAD_REQUEST_BODY");
+		});
+
+		it("should reject unknown fields, wrong types and empty bodies with 422", async () => {
+			const post = (body: string) =>
+				app.fetch(
+					new Request("http://localhost:3001/api/resolve", {
+						method: "POST",
+						headers: { "Content-Type": "application/json" },
+						body,
+					}),
+				);
+			const unknown = await post(JSON.stringify({ uri: "https://x.com/user/status/1" }));
+			expect(unknown.status).toBe(422);
+			expect(await unknown.json()).toEqual({
+				success: false,
+				error: expect.stringContaining("uri"),
+				code: "BAD_REQUEST_BODY",
+				field: "uri",
+			});
+
+			const wrongType = await post(
+				JSON.stringify({ url: "https://x.com/user/status/1", formatsLimit: "3" }),
+			);
+			expect(wrongType.status).toBe(422);
+			expect(await wrongType.json()).toEqual({
+				success: false,
+				error: "formatsLimit must be a number",
+				code: "BAD_REQUEST_BODY",
+				field: "formatsLimit",
+			});
+
+			const empty = await post("");
+			expect(empty.status).toBe(422);
+			expect(((await empty.json()) as { code: string }).code).toBe("BAD_REQUEST_BODY");
+		});
+
+		it("should reject unsupported media options with 422", async () => {
 			const res = await app.fetch(
 				new Request("http://localhost:3001/api/resolve", {
 					method: "POST",
@@ -29,9 +100,37 @@ describe("API Routes", () => {
 					}),
 				}),
 			);
-			expect(res.status).toBe(400);
-			const data = (await res.json()) as { success: boolean };
+			expect(res.status).toBe(422);
+			const data = (await res.json()) as { success: boolean; field: string };
 			expect(data.success).toBe(false);
+			expect(data.field).toBe("videoQuality");
+		});
+
+		it("should reject an out-of-range formatsLimit with 422", async () => {
+			const res = await app.fetch(
+				new Request("http://localhost:3001/api/resolve", {
+					method: "POST",
+					headers: { "Content-Type": "application/json" },
+					body: JSON.stringify({ url: "https://x.com/user/status/1", formatsLimit: 21 }),
+				}),
+			);
+			expect(res.status).toBe(422);
+			const data = (await res.json()) as { success: boolean; error: string };
+			expect(data.error).toBe("formatsLimit must be at most 20");
+		});
+
+		it("should reject a lang that is not a language tag with 422", async () => {
+			for (const lang of ["en --exec rm", "en\nX-Evil: 1", "e", "english-language-x"]) {
+				const res = await app.fetch(
+					new Request("http://localhost:3001/api/resolve", {
+						method: "POST",
+						headers: { "Content-Type": "application/json" },
+						body: JSON.stringify({ url: "https://x.com/user/status/1", lang }),
+					}),
+				);
+				expect(res.status).toBe(422);
+				expect(((await res.json()) as { field: string }).field).toBe("lang");
+			}
 		});
 	});
 
@@ -61,15 +160,19 @@ describe("API Routes", () => {
 				),
 			);
 			expect(res.status).toBe(403);
+			const data = (await res.json()) as { code: string };
+			expect(data.code).toBe("INVALID_SIGNATURE");
 		});
 	});
 
 	describe("GET /health", () => {
-		it("should return health OK", async () => {
+		it("should return 200 with uptime and yt-dlp status", async () => {
 			const res = await app.fetch(new Request("http://localhost:3001/health"));
 			expect(res.status).toBe(200);
-			const text = await res.text();
-			expect(text).toBe("OK");
+			const data = (await res.json()) as { status: string; uptimeSecs: number; ytdlp: object };
+			expect(data.status).toBe("ok");
+			expect(data.uptimeSecs).toBeGreaterThanOrEqual(0);
+			expect(data.ytdlp).toHaveProperty("available");
 		});
 	});
 });
@@ -0,0 +1,95 @@
+import { describe, expect, it } from "bun:test";
+import { isShortLink } from "@snatch/shared";
+import { type Fetcher, resolveShortLink } from "../src/lib/shortlink";
+
+/** Serves canned redirects keyed by request URL; anything else is a 200. */
+function redirects(map: Record<string, string>): Fetcher & { seen: string[] } {
+	const seen: string[] = [];
+	const fetcher = async (url: string) => {
+		seen.push(url);
+		const location = map[url];
+		return location
+			? new Response(null, { status: 301, headers: { Location: location } })
+			: new Response(null, { status: 200 });
+	};
+	return Object.assign(fetcher, { seen });
+}
+
+describe("isShortLink", () => {
+	it("matches shortener hosts exactly", () => {
+		expect(isShortLink("https://vm.tiktok.com/ZMabc/")).toBe(true);
+		expect(isShortLink("https://vt.tiktok.com/ZSabc/")).toBe(true);
+		expect(isShortLink("https://t.co/AbC123")).toBe(true);
+		expect(isShortLink("https://v.redd.it/1a2b3c4d5e6f")).toBe(true);
+		expect(isShortLink("https://www.tiktok.com/@user/video/1")).toBe(false);
+		expect(isShortLink("not a url")).toBe(false);
+	});
+});
+
+describe("resolveShortLink", () => {
+	it("follows redirects to the canonical URL", async () => {
+		const fetcher = redirects({
+			"https://vm.tiktok.com/ZMabc/": "https://www.tiktok.com/@user/video/1?_r=1",
+		});
+		expect(await resolveShortLink("https://vm.tiktok.com/ZMabc/", { fetcher })).toBe(
+			"https://www.tiktok.com/@user/video/1",
+		);
+		// The canonical host is not a shortener, so it is never fetched.
+		expect(fetcher.seen).toEqual(["https://vm.tiktok.com/ZMabc/"]);
+	});
+
+	it("follows v.redd.it to the Reddit thread it belongs to", async () => {
+		const fetcher = redirects({
+			"https://v.redd.it/1a2b3c4d5e6f": "https://www.reddit.com/r/videos/comments/abc123/title/",
+		});
+		expect(await resolveShortLink("https://v.redd.it/1a2b3c4d5e6f", { fetcher })).toBe(
+			"https://www.reddit.com/r/videos/comments/abc123/title/",
+		);
+	});
+
+	it("rejects a redirect that leaves the platform allowlist", async () => {
+		const fetcher = redirects({ "https://vm.tiktok.com/abc/": "http://169.254.169.254/latest" });
+		await expect(resolveShortLink("https://vm.tiktok.com/abc/", { fetcher })).rejects.toThrow(
+			"outside the supported platforms",
+		);
+	});
+
+	it("enforces the redirect cap", async () => {
+		const fetcher = redirects({
+			"https://vm.tiktok.com/a/": "https://vt.tiktok.com/b/",
+			"https://vt.tiktok.com/b/": "https://vm.tiktok.com/a/",
+		});
+		const run = resolveShortLink("https://vm.tiktok.com/a/", { fetcher, maxRedirects: 3 });
+		await expect(run).rejects.toThrow("more than 3 times");
+		expect(fetcher.seen).toHaveLength(3);
+	});
+
+	it("resolves shorteners outside the platform allowlist", async () => {
+		const fetcher = redirects({
+			"https://t.co/AbC123": "https://x.com/user/status/1/video/1",
+			"https://instagr.am/p/Cxyz1/": "https://www.instagram.com/p/Cxyz1/",
+		});
+		expect(await resolveShortLink("https://t.co/AbC123", { fetcher })).toBe(
+			"https://x.com/user/status/1/video/1",
+		);
+		expect(await resolveShortLink("https://instagr.am/p/Cxyz1/", { fetcher })).toBe(
+			"https://www.instagram.com/p/Cxyz1/",
+		);
+	});
+
+	it("rejects a non-platform shortener that does not redirect", async () => {
+		const fetcher = redirects({});
+		await expect(resolveShortLink("https://t.co/dead", { fetcher })).rejects.toThrow(
+			"could not be resolved to a supported platform",
+		);
+	});
+
+	it("falls back to the original URL when the shortener is unreachable", async () => {
+		const fetcher: Fetcher = async () => {
+			throw new Error("ECONNREFUSED");
+		};
+		expect(await resolveShortLink("https://vm.tiktok.com/abc/", { fetcher })).toBe(
+			"https://vm.tiktok.com/abc/",
+		);
+	});
+});
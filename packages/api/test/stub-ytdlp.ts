@@ -0,0 +1,59 @@
+import { afterAll, beforeAll } from "bun:test";
+import { chmod, mkdtemp, rm, writeFile } from "node:fs/promises";
+import os from "node:os";
+import path from "node:path";
+
+/**
+ * Shell that plays yt-dlp's download side: writes `bytes` to the `-o`
+ * template with the title filled in, then prints the path the way
+ * `--print after_move:filepath` does.
+ */
+export function stubDownload(bytes: string): string {
+	return [
+		'out=""; prev=""',
+		'for arg in "$@"; do [ "$prev" = "-o" ] && out="$arg"; prev="$arg"; done',
+		`file=$(printf '%s' "$out" | sed 's/%(title).60s.%(ext)s/clip.mp4/')`,
+		`printf '%s' '${bytes}' > "$file"`,
+		'echo "$file"',
+	].join("\n");
+}
+
+/**
+ * Point `YTDLP_PATH` at a shell script for the enclosing `describe`, so route
+ * tests run the real handlers end to end with canned yt-dlp behaviour.
+ * `script(body)` sets what every invocation other than `--version` does;
+ * `probeJson(info)` answers `-J` probes with `info` and otherwise runs `rest`.
+ */
+export function useStubYtDlp() {
+	const prev = process.env.YTDLP_PATH;
+	const stub = {
+		dir: "",
+		path: "",
+		async script(body: string) {
+			await writeFile(
+				stub.path,
+				`#!/bin/sh\nif [ "$1" = "--version" ]; then echo 2025.01.15; exit 0; fi\n${body}\n`,
+			);
+			await chmod(stub.path, 0o755);
+		},
+		async probeJson(info: object, rest = "exit 1") {
+			const fixture = path.join(stub.dir, "info.json");
+			await writeFile(fixture, JSON.stringify(info));
+			await stub.script(`if [ "$1" = "-J" ]; then cat '${fixture}'; exit 0; fi\n${rest}`);
+		},
+	};
+
+	beforeAll(async () => {
+		stub.dir = await mkdtemp(path.join(os.tmpdir(), "snatch-stub-"));
+		stub.path = path.join(stub.dir, "yt-dlp");
+		process.env.YTDLP_PATH = stub.path;
+	});
+
+	afterAll(async () => {
+		if (prev === undefined) delete process.env.YTDLP_PATH;
+		else process.env.YTDLP_PATH = prev;
+		await rm(stub.dir, { recursive: true, force: true });
+	});
+
+	return stub;
+}
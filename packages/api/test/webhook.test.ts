@@ -0,0 +1,69 @@
+import { describe, expect, it } from "bun:test";
+import type { Fetcher } from "../src/lib/shortlink";
+import {
+	callbackUrlProblem,
+	deliverWebhook,
+	signWebhook,
+	WEBHOOK_SIGNATURE_HEADER,
+} from "../src/lib/webhook";
+
+const CALLBACK = "https://hooks.example.com/snatch";
+const PAYLOAD = { jobId: "job-1", status: "done", progress: 100 };
+
+/** Answers each delivery with the next status in `statuses`, recording the requests. */
+function receiver(...statuses: number[]): Fetcher & { calls: RequestInit[] } {
+	const calls: RequestInit[] = [];
+	const fetcher = async (_url: string, init: RequestInit) => {
+		calls.push(init);
+		return new Response(null, { status: statuses[calls.length - 1] ?? 200 });
+	};
+	return Object.assign(fetcher, { calls });
+}
+
+describe("deliverWebhook", () => {
+	it("POSTs the payload signed with the webhook secret", async () => {
+		const fetcher = receiver(204);
+		expect(await deliverWebhook(CALLBACK, PAYLOAD, { fetcher, secret: "s3cret" })).toBe(true);
+
+		expect(fetcher.calls).toHaveLength(1);
+		const [init] = fetcher.calls;
+		const body = String(init.body);
+		expect(init.method).toBe("POST");
+		expect(JSON.parse(body)).toEqual(PAYLOAD);
+		const headers = init.headers as Record<string, string>;
+		expect(headers[WEBHOOK_SIGNATURE_HEADER]).toBe(signWebhook(body, "s3cret"));
+		expect(headers[WEBHOOK_SIGNATURE_HEADER]).toMatch(/^sha256=[0-9a-f]{64}$/);
+	});
+
+	it("retries a failing receiver with backoff until it accepts", async () => {
+		const fetcher = receiver(503, 500, 200);
+		const delays: number[] = [];
+		const sleep = async (ms: number) => delays.push(ms);
+		expect(await deliverWebhook(CALLBACK, PAYLOAD, { fetcher, secret: "s", sleep })).toBe(true);
+		expect(fetcher.calls).toHaveLength(3);
+		expect(delays).toEqual([1_000, 2_000]);
+	});
+
+	it("gives up after the attempt budget or on a non-retryable answer", async () => {
+		const deliver = (fetcher: Fetcher) =>
+			deliverWebhook(CALLBACK, PAYLOAD, { fetcher, secret: "s", sleep: async () => {} });
+		const down = receiver(500, 500, 500, 500, 500);
+		expect(await deliver(down)).toBe(false);
+		expect(down.calls).toHaveLength(4);
+
+		const gone = receiver(410);
+		expect(await deliver(gone)).toBe(false);
+		expect(gone.calls).toHaveLength(1);
+	});
+});
+
+describe("callbackUrlProblem", () => {
+	it("accepts only public https URLs without credentials", () => {
+		expect(callbackUrlProblem(CALLBACK)).toBeUndefined();
+		expect(callbackUrlProblem("http://hooks.example.com/snatch")).toContain("https");
+		expect(callbackUrlProblem("not a url")).toContain("https");
+		expect(callbackUrlProblem("https://user:pw@hooks.example.com/")).toContain("credentials");
+		expect(callbackUrlProblem("https://169.254.169.254/")).toContain("public host");
+		expect(callbackUrlProblem("https://localhost:8080/")).toContain("public host");
+	});
+});
@@ -1,5 +1,40 @@
-import { describe, expect, it } from "bun:test";
-import { buildChoices, type VideoInfo } from "../src/lib/ytdlp";
+import { afterEach, describe, expect, it, spyOn } from "bun:test";
+import { mkdtemp, readFile, rm } from "node:fs/promises";
+import os from "node:os";
+import path from "node:path";
+import { adaptiveTimeoutMs, clearLatencies, MIN_LATENCY_SAMPLES } from "../src/lib/latency";
+import {
+	buildChoices,
+	buildPostItems,
+	type ChoiceOptions,
+	choiceWarnings,
+	classifyYtDlpError,
+	clearProfileCache,
+	type CommandResult,
+	type CommandRunner,
+	configArgs,
+	defaultVideoQuality,
+	describeMedia,
+	type ExtractionStats,
+	formatExpiry,
+	formatsLimit,
+	isLive,
+	languageArgs,
+	listProfile,
+	liveCaptureArgs,
+	liveCaptureSecs,
+	minVideoHeight,
+	parseVideoInfo,
+	probe,
+	probeTimeoutSecs,
+	profileCacheUsage,
+	signableChoices,
+	spawnCommand,
+	type VideoInfo,
+	withBinaryFallback,
+	ytDlpBinaries,
+	YtDlpError,
+} from "../src/lib/ytdlp";
 
 const FIXTURE: VideoInfo = {
 	id: "abc",
@@ -12,6 +47,13 @@ const FIXTURE: VideoInfo = {
 	],
 };
 
+type RawFormat = NonNullable<VideoInfo["formats"]>[number];
+
+/** A muxed H.264 mp4 format, the shape most platforms report for progressive streams. */
+function muxedMp4(formatId: string, height: number, extra?: Partial<RawFormat>): RawFormat {
+	return { format_id: formatId, ext: "mp4", vcodec: "avc1", acodec: "mp4a", height, ...extra };
+}
+
 describe("buildChoices", () => {
 	it("honors a non-mp3 audioFormat in id and args", () => {
 		const choices = buildChoices(FIXTURE, { audioFormat: "opus" });
@@ -35,6 +77,54 @@ describe("buildChoices", () => {
 		expect(heights).not.toContain("1080p");
 	});
 
+	it("describes the source formats behind each choice", () => {
+		const choices = buildChoices({
+			...FIXTURE,
+			formats: [
+				...(FIXTURE.formats ?? []),
+				{ format_id: "m720", ext: "mp4", vcodec: "avc1", acodec: "mp4a", height: 720, fps: 60 },
+			],
+		});
+		const hd = choices.find((c) => c.id === "v-1080p");
+		expect(hd?.details).toMatchObject({
+			formatId: "v1080+audio",
+			height: 1080,
+			vcodec: "avc1",
+			acodec: "opus",
+			tbr: 3000,
+		});
+		const muxed = choices.find((c) => c.id === "v-720p");
+		expect(muxed?.details).toMatchObject({ formatId: "m720", acodec: "mp4a", fps: 60 });
+		expect(choices.find((c) => c.kind === "audio")?.details?.formatId).toBe("audio");
+	});
+
+	it("collapses duplicate heights into one choice, keeping the best variant", () => {
+		const choices = buildChoices({
+			title: "Thread clip",
+			formats: [
+				muxedMp4("hls-720-a", 720),
+				muxedMp4("http-720", 720, { tbr: 2176 }),
+				muxedMp4("hls-720-b", 720, { tbr: 950 }),
+				muxedMp4("http-480", 480, { tbr: 832 }),
+				muxedMp4("http-360", 360, { tbr: 632 }),
+			],
+		});
+		const video = choices.filter((c) => c.kind === "video");
+		expect(video.map((c) => c.quality)).toEqual(["720p", "480p", "360p"]);
+		expect(video[0].details?.formatId).toBe("http-720");
+	});
+
+	it("breaks bitrate ties on filesize", () => {
+		const choices = buildChoices({
+			title: "No bitrates",
+			formats: [
+				muxedMp4("small", 720, { filesize: 1_000 }),
+				muxedMp4("large", 720, { filesize: 9_000 }),
+			],
+		});
+		expect(choices.find((c) => c.id === "v-720p")?.details?.formatId).toBe("large");
+	});
+
 	it("defaults to all heights and mp3 when no options given", () => {
 		const choices = buildChoices(FIXTURE);
 		const video = choices.filter((c) => c.kind === "video").map((c) => c.quality);
@@ -42,3 +132,1127 @@ describe("buildChoices", () => {
 		expect(choices.find((c) => c.kind === "audio")?.id).toBe("a-mp3");
 	});
 });
+
+describe("classifyYtDlpError", () => {
+	it("maps well-known stderr markers to status codes", () => {
+		expect(classifyYtDlpError("ERROR: [youtube] abc: Private video. Sign in")).toEqual({
+			code: "PRIVATE_CONTENT",
+			status: 403,
+		});
+		expect(
+			classifyYtDlpError("ERROR: [Instagram] abc: This post may not be comfortable for some"),
+		).toEqual({ code: "PRIVATE_CONTENT", status: 403 });
+		expect(classifyYtDlpError("ERROR: [vimeo] 1: Video unavailable")).toEqual({
+			code: "NOT_FOUND",
+			status: 404,
+		});
+		expect(
+			classifyYtDlpError("ERROR: [TikTok] 1: Requested content is not available, rate limit?"),
+		).toEqual({ code: "NOT_FOUND", status: 404 });
+		expect(classifyYtDlpError("ERROR: [twitter] 1: This video is georestricted")).toEqual({
+			code: "GEO_BLOCKED",
+			status: 451,
+		});
+		const rateLimited = "ERROR: Unable to download: HTTP Error 429: Too Many Requests";
+		expect(classifyYtDlpError(rateLimited)).toEqual({ code: "RATE_LIMITED", status: 429 });
+	});
+
+	it("reports an engine that asks to be updated as YTDLP_OUTDATED", () => {
+		const outdated = { code: "YTDLP_OUTDATED", status: 503 };
+		const stale = [
+			"ERROR: [TikTok] 1: Unable to extract webpage video data; please update yt-dlp",
+			"WARNING: You are using an outdated version of yt-dlp\nERROR: [x] 1: oops",
+			"WARNING: Your yt-dlp version 2023.03.04 is older than 90 days!",
+		];
+		for (const stderr of stale) expect(classifyYtDlpError(stderr)).toEqual(outdated);
+	});
+
+	it("keeps specific causes and the generic bug-report footer out of YTDLP_OUTDATED", () => {
+		const privateHint = "ERROR: [youtube] abc: Private video; please update yt-dlp if wrong";
+		expect(classifyYtDlpError(privateHint).code).toBe("PRIVATE_CONTENT");
+		const footer =
+			"ERROR: [vimeo] 1: something exploded; please report this issue. " +
+			"Confirm you are on the latest version using yt-dlp -U";
+		expect(classifyYtDlpError(footer).code).toBe("YTDLP_FAILED");
+	});
+
+	it("falls back to a 502 for unrecognized failures", () => {
+		expect(classifyYtDlpError("ERROR: something exploded")).toEqual({
+			code: "YTDLP_FAILED",
+			status: 502,
+		});
+		expect(classifyYtDlpError("")).toEqual({ code: "YTDLP_FAILED", status: 502 });
+	});
+
+	it("keeps only the last ERROR line as the client-facing message", () => {
+		const err = new YtDlpError(
+			"WARNING: noise\nERROR: [vimeo] 1: Video unavailable\n",
+			"yt-dlp probe failed",
+		);
+		expect(err.message).toBe("1: Video unavailable");
+		expect(err.status).toBe(404);
+	});
+});
+
+describe("probe failures", () => {
+	it("reports a binary that cannot be spawned as YTDLP_UNAVAILABLE", async () => {
+		const run = probe("/nonexistent/yt-dlp", "https://x.com/user/status/1");
+		await expect(run).rejects.toMatchObject({
+			code: "YTDLP_UNAVAILABLE",
+			status: 503,
+			message: "yt-dlp is unavailable",
+		});
+	});
+
+	it("reports a non-zero exit as YTDLP_FAILED", async () => {
+		const run = probe("false", "https://x.com/user/status/1");
+		await expect(run).rejects.toMatchObject({ code: "YTDLP_FAILED", status: 502 });
+	});
+});
+
+describe("probe with a stub runner", () => {
+	const URL = "https://x.com/user/status/1";
+
+	/** Replays canned results in order, recording how many times it ran. */
+	function stubRunner(...results: CommandResult[]) {
+		const runner = Object.assign(
+			async (): Promise<CommandResult> => {
+				runner.calls++;
+				return results[Math.min(runner.calls, results.length) - 1];
+			},
+			{ calls: 0 },
+		);
+		return runner;
+	}
+
+	it("parses metadata and caches the info-json on success", async () => {
+		const stdout = JSON.stringify({ id: "1", title: "Clip", formats: [] });
+		const run = stubRunner({ stdout, stderr: "", exitCode: 0 });
+		const { info, infoJsonPath } = await probe("yt-dlp", URL, { run });
+		try {
+			expect(info.title).toBe("Clip");
+			expect(await Bun.file(infoJsonPath).text()).toBe(stdout);
+		} finally {
+			await rm(infoJsonPath, { force: true });
+		}
+	});
+
+	it("classifies a non-zero exit without retrying permanent failures", async () => {
+		const run = stubRunner({ stdout: "", stderr: "ERROR: [x] 1: Private video", exitCode: 1 });
+		await expect(probe("yt-dlp", URL, { run })).rejects.toMatchObject({
+			code: "PRIVATE_CONTENT",
+			status: 403,
+		});
+		expect(run.calls).toBe(1);
+	});
+
+	it("retries a transient failure and then succeeds", async () => {
+		const run = stubRunner(
+			{ stdout: "", stderr: "ERROR: Unable to download webpage: timed out", exitCode: 1 },
+			{ stdout: JSON.stringify({ id: "1", title: "Clip" }), stderr: "", exitCode: 0 },
+		);
+		const { infoJsonPath } = await probe("yt-dlp", URL, { run });
+		await rm(infoJsonPath, { force: true });
+		expect(run.calls).toBe(2);
+	});
+
+	it("waits at least 5s before retrying a platform 429", async () => {
+		const delays: number[] = [];
+		const sleep = async (ms: number) => {
+			delays.push(ms);
+		};
+		const run = stubRunner(
+			{ stdout: "", stderr: "ERROR: [tiktok] 1: HTTP Error 429: Too Many Requests", exitCode: 1 },
+			{ stdout: JSON.stringify({ id: "1", title: "Clip" }), stderr: "", exitCode: 0 },
+		);
+		const { infoJsonPath } = await probe("yt-dlp", URL, { run, sleep });
+		await rm(infoJsonPath, { force: true });
+		expect(run.calls).toBe(2);
+		expect(delays).toHaveLength(1);
+		expect(delays[0]).toBeGreaterThanOrEqual(5_000);
+	});
+
+	it("reports a probe that outlives its timeout as TIMEOUT", async () => {
+		const hang: CommandRunner = (_command, _args, signal) =>
+			new Promise((_, reject) => {
+				signal?.addEventListener("abort", () => reject(signal?.reason));
+			});
+		const run = probe("yt-dlp", URL, { run: hang, timeoutMs: 50 });
+		await expect(run).rejects.toMatchObject({
+			code: "TIMEOUT",
+			status: 504,
+			message: "Extraction timed out after 0.1s",
+		});
+	});
+
+	it("gives up with TIMEOUT instead of sleeping past its budget", async () => {
+		const delays: number[] = [];
+		const sleep = async (ms: number) => {
+			delays.push(ms);
+		};
+		const run = stubRunner({
+			stdout: "",
+			stderr: "ERROR: [tiktok] 1: HTTP Error 429: Too Many Requests",
+			exitCode: 1,
+		});
+		// The 5s+ backoff after a 429 cannot fit in a 2s budget.
+		await expect(probe("yt-dlp", URL, { run, sleep, timeoutMs: 2_000 })).rejects.toMatchObject({
+			code: "TIMEOUT",
+			message: "Extraction timed out after 2s",
+		});
+		expect(run.calls).toBe(1);
+		expect(delays).toEqual([]);
+	});
+
+	it("records only the successful run's duration as the platform's latency", async () => {
+		clearLatencies();
+		let clock = 0;
+		const now = spyOn(performance, "now").mockImplementation(() => clock);
+		const stdout = JSON.stringify({ id: "1", title: "Clip" });
+		const stderr = "ERROR: Unable to download webpage: timed out";
+		const failed = { stdout: "", stderr, exitCode: 1 };
+		const succeeded = { stdout, stderr: "", exitCode: 0 };
+		// Each probe: a 20s failure, its backoff plus 5s, then a 1s success.
+		let calls = 0;
+		const run: CommandRunner = async () => {
+			clock += ++calls % 2 ? 20_000 : 1_000;
+			return calls % 2 ? failed : succeeded;
+		};
+		const sleep = async (ms: number) => {
+			clock += ms + 5_000;
+		};
+		try {
+			for (let i = 0; i < MIN_LATENCY_SAMPLES; i++) {
+				const { infoJsonPath } = await probe("yt-dlp", URL, { run, sleep });
+				await rm(infoJsonPath, { force: true });
+			}
+			expect(adaptiveTimeoutMs("twitter", 0, 600_000)).toBe(4_000);
+		} finally {
+			now.mockRestore();
+			clearLatencies();
+		}
+	});
+
+	it("rejects output that is not JSON", async () => {
+		const run = stubRunner({ stdout: "<html>", stderr: "", exitCode: 0 });
+		await expect(probe("yt-dlp", URL, { run })).rejects.toThrow(
+			"Could not parse video metadata from yt-dlp.",
+		);
+	});
+});
+
+describe("spawnCommand", () => {
+	it("reaps a timed-out child even when it ignores SIGTERM", async () => {
+		const dir = await mkdtemp(path.join(os.tmpdir(), "snatch-spawn-"));
+		try {
+			const pidFile = path.join(dir, "pid");
+			const script = `echo $$ > '${pidFile}'; trap '' TERM; exec sleep 30`;
+			const run = spawnCommand("sh", ["-c", script], AbortSignal.timeout(300));
+			await expect(run).rejects.toThrow("The operation was aborted");
+
+			const pid = Number(await readFile(pidFile, "utf-8"));
+			expect(pid).toBeGreaterThan(0);
+			// Signal 0 only checks that the process exists.
+			expect(() => process.kill(pid, 0)).toThrow();
+		} finally {
+			await rm(dir, { recursive: true, force: true });
+		}
+	}, 10_000);
+
+	it("resolves with the exit code when nothing aborts it", async () => {
+		const result = await spawnCommand("sh", ["-c", "echo out; echo err >&2; exit 3"]);
+		expect(result).toEqual({ stdout: "out\n", stderr: "err\n", exitCode: 3 });
+	});
+});
+
+describe("withBinaryFallback", () => {
+	const unavailable = () => new YtDlpError("", "yt-dlp is unavailable", "YTDLP_UNAVAILABLE");
+
+	it("falls through to the next binary when one fails to spawn", async () => {
+		const tried: string[] = [];
+		const run: CommandRunner = async (command) => {
+			tried.push(command);
+			if (command === "yt-dlp") throw unavailable();
+			return { stdout: "{}", stderr: "", exitCode: 0 };
+		};
+		const result = await withBinaryFallback(run, ["yt-dlp", "youtube-dl"])("yt-dlp", ["-J"]);
+		expect(result.exitCode).toBe(0);
+		expect(tried).toEqual(["yt-dlp", "youtube-dl"]);
+	});
+
+	it("keeps a failed run's result and rethrows once every binary fails to spawn", async () => {
+		const tried: string[] = [];
+		const failing: CommandRunner = async (command) => {
+			tried.push(command);
+			return { stdout: "", stderr: "ERROR: Private video", exitCode: 1 };
+		};
+		const fallback = withBinaryFallback(failing, ["youtube-dl"]);
+		expect((await fallback("yt-dlp", ["-J"])).exitCode).toBe(1);
+		expect(tried).toEqual(["yt-dlp"]);
+
+		const missing: CommandRunner = async () => {
+			throw unavailable();
+		};
+		const none = withBinaryFallback(missing, ["yt-dlp", "youtube-dl"])("yt-dlp", ["-J"]);
+		await expect(none).rejects.toMatchObject({ code: "YTDLP_UNAVAILABLE" });
+	});
+
+	it("reads the binaries from YTDLP_BINARIES", () => {
+		const prev = process.env.YTDLP_BINARIES;
+		try {
+			process.env.YTDLP_BINARIES = " yt-dlp , youtube-dl,, ";
+			expect(ytDlpBinaries()).toEqual(["yt-dlp", "youtube-dl"]);
+			process.env.YTDLP_BINARIES = "";
+			expect(ytDlpBinaries()).toBeUndefined();
+		} finally {
+			if (prev === undefined) delete process.env.YTDLP_BINARIES;
+			else process.env.YTDLP_BINARIES = prev;
+		}
+	});
+});
+
+describe("languageArgs", () => {
+	it("asks for the language through a header and YouTube's extractor argument", () => {
+		expect(languageArgs("pt-BR")).toEqual([
+			"--add-header",
+			"Accept-Language:pt-BR",
+			"--extractor-args",
+			"youtube:lang=pt-BR",
+		]);
+		expect(languageArgs("zh-Hant-TW")).toHaveLength(4);
+	});
+
+	it("adds nothing for a missing or malformed tag", () => {
+		for (const lang of [undefined, "", "en us", "en;--exec=id", "-en", "en--US", "x"]) {
+			expect(languageArgs(lang)).toEqual([]);
+		}
+	});
+
+	it("is passed to the probe only when valid", async () => {
+		const seen: string[][] = [];
+		const run: CommandRunner = async (_command, args) => {
+			seen.push(args);
+			return { stdout: JSON.stringify({ id: "1", title: "Clip" }), stderr: "", exitCode: 0 };
+		};
+		for (const lang of ["de", "de;rm"]) {
+			const { infoJsonPath } = await probe("yt-dlp", "https://x.com/user/status/1", { run, lang });
+			await rm(infoJsonPath, { force: true });
+		}
+		expect(seen[0]).toContain("Accept-Language:de");
+		expect(seen[0].at(-1)).toBe("https://x.com/user/status/1");
+		expect(seen[1]).not.toContain("--add-header");
+	});
+});
+
+describe("configArgs", () => {
+	const VARS = [
+		"YTDLP_USER_AGENT",
+		"YTDLP_USER_AGENT_TIKTOK",
+		"YTDLP_EXTRA_HEADERS",
+		"YTDLP_EXTRA_FLAGS",
+		"YTDLP_EXTRA_FLAGS_TIKTOK",
+		"YTDLP_EXTRACTOR_ARGS_TWITTER",
+	];
+	const prev = Object.fromEntries(VARS.map((name) => [name, process.env[name]]));
+
+	afterEach(() => {
+		for (const name of VARS) {
+			if (prev[name] === undefined) delete process.env[name];
+			else process.env[name] = prev[name];
+		}
+	});
+
+	it("adds nothing when nothing is configured", () => {
+		for (const name of VARS) delete process.env[name];
+		expect(configArgs()).toEqual([]);
+	});
+
+	it("passes the configured user agent through as a single argv entry", () => {
+		process.env.YTDLP_USER_AGENT = "Mozilla/5.0 (X11; Linux x86_64) $(whoami); `id`";
+		expect(configArgs()).toEqual([
+			"--user-agent",
+			"Mozilla/5.0 (X11; Linux x86_64) $(whoami); `id`",
+		]);
+	});
+
+	it("rejects a user agent containing line breaks", () => {
+		process.env.YTDLP_USER_AGENT = "Mozilla/5.0\r\nX-Injected: 1";
+		expect(() => configArgs()).toThrow("YTDLP_USER_AGENT");
+	});
+
+	it("turns YTDLP_EXTRA_HEADERS into repeated --add-header flags", () => {
+		delete process.env.YTDLP_USER_AGENT;
+		process.env.YTDLP_EXTRA_HEADERS = "Accept-Language: en-US, X-Forwarded-For: 203.0.113.7";
+		expect(configArgs()).toEqual([
+			"--add-header",
+			"Accept-Language:en-US",
+			"--add-header",
+			"X-Forwarded-For:203.0.113.7",
+		]);
+	});
+
+	it("rejects malformed extra headers", () => {
+		process.env.YTDLP_EXTRA_HEADERS = "Referer: https://a.example\rX-Injected: 1";
+		expect(() => configArgs()).toThrow("YTDLP_EXTRA_HEADERS");
+		process.env.YTDLP_EXTRA_HEADERS = "no separator";
+		expect(() => configArgs()).toThrow("YTDLP_EXTRA_HEADERS");
+	});
+
+	it("appends allowlisted YTDLP_EXTRA_FLAGS once each", () => {
+		for (const name of VARS) delete process.env[name];
+		process.env.YTDLP_EXTRA_FLAGS = "--force-ipv4, --geo-bypass,--force-ipv4";
+		expect(configArgs("https://x.com/user/status/1")).toEqual(["--force-ipv4", "--geo-bypass"]);
+	});
+
+	it("rejects extra flags that are unknown or take a value", () => {
+		for (const flags of ["--exec", "--proxy=http://evil.example", "--force-ipv4 --exec id", "-4"]) {
+			process.env.YTDLP_EXTRA_FLAGS = flags;
+			expect(() => configArgs()).toThrow("YTDLP_EXTRA_FLAGS");
+		}
+	});
+
+	it("applies a per-platform user agent only to that platform", () => {
+		process.env.YTDLP_USER_AGENT = "Default/1.0";
+		process.env.YTDLP_USER_AGENT_TIKTOK = "TikTok/2.0";
+		expect(configArgs("https://www.tiktok.com/@user/video/1")).toEqual([
+			"--user-agent",
+			"TikTok/2.0",
+		]);
+		expect(configArgs("https://x.com/user/status/1")).toEqual(["--user-agent", "Default/1.0"]);
+	});
+
+	it("validates per-platform overrides at startup", () => {
+		process.env.YTDLP_USER_AGENT_TIKTOK = "Tik\nTok";
+		expect(() => configArgs()).toThrow("YTDLP_USER_AGENT_TIKTOK");
+	});
+
+	it("appends each platform's argument profile only to its own URLs", () => {
+		for (const name of VARS) delete process.env[name];
+		process.env.YTDLP_EXTRA_FLAGS = "--force-ipv4";
+		process.env.YTDLP_EXTRA_FLAGS_TIKTOK = "--geo-bypass,--force-ipv4";
+		process.env.YTDLP_EXTRACTOR_ARGS_TWITTER = "twitter:api=syndication";
+
+		expect(configArgs("https://www.tiktok.com/@user/video/1")).toEqual([
+			"--force-ipv4",
+			"--geo-bypass",
+		]);
+		expect(configArgs("https://x.com/user/status/1")).toEqual([
+			"--force-ipv4",
+			"--extractor-args",
+			"twitter:api=syndication",
+		]);
+		expect(configArgs("https://vimeo.com/1")).toEqual(["--force-ipv4"]);
+	});
+
+	it("rejects a malformed platform profile at startup", () => {
+		for (const name of VARS) delete process.env[name];
+		process.env.YTDLP_EXTRACTOR_ARGS_TWITTER = "twitter:api=x --exec id";
+		expect(() => configArgs()).toThrow("YTDLP_EXTRACTOR_ARGS_TWITTER");
+		process.env.YTDLP_EXTRACTOR_ARGS_TWITTER = "--exec";
+		expect(() => configArgs()).toThrow("YTDLP_EXTRACTOR_ARGS_TWITTER");
+
+		delete process.env.YTDLP_EXTRACTOR_ARGS_TWITTER;
+		process.env.YTDLP_EXTRA_FLAGS_TIKTOK = "--exec";
+		expect(() => configArgs()).toThrow("YTDLP_EXTRA_FLAGS_TIKTOK");
+	});
+});
+
+describe("parseVideoInfo", () => {
+	it("reports empty stdout as NO_METADATA instead of a parse error", () => {
+		for (const stdout of ["", "\ufeff  \n"]) {
+			expect(() => parseVideoInfo(stdout)).toThrow("No metadata returned");
+			expect(() => parseVideoInfo(stdout)).toThrow(YtDlpError);
+		}
+	});
+
+	it("ignores a leading byte order mark", () => {
+		const info = parseVideoInfo(`\ufeff${JSON.stringify({ id: "abc", title: "Sample" })}`);
+		expect(info.title).toBe("Sample");
+	});
+});
+
+describe("choiceWarnings", () => {
+	it("warns when malformed formats were dropped", () => {
+		const info = parseVideoInfo(
+			JSON.stringify({
+				id: "abc",
+				title: "Sample",
+				formats: [
+					{ format_id: "v720", vcodec: "avc1", acodec: "none", height: 720 },
+					{ url: "https://cdn.example/no-id.mp4", vcodec: "avc1", height: 480 },
+				],
+			}),
+		);
+		expect(info.formats).toHaveLength(1);
+		expect(choiceWarnings(info, buildChoices(info))).toEqual([
+			"Skipped 1 malformed format(s) reported by yt-dlp",
+		]);
+	});
+
+	it("warns when only the best-quality fallback could be offered", () => {
+		const info = parseVideoInfo(JSON.stringify({ id: "abc", title: "Sample" }));
+		expect(choiceWarnings(info, buildChoices(info))).toEqual([
+			"No per-resolution formats found; offering the best available quality only",
+		]);
+	});
+
+	it("is empty for a well-formed format list", () => {
+		expect(choiceWarnings(FIXTURE, buildChoices(FIXTURE))).toEqual([]);
+	});
+});
+
+describe("describeMedia", () => {
+	it("maps TikTok metadata", () => {
+		const info = parseVideoInfo(
+			JSON.stringify({
+				id: "7300000000000000000",
+				title: "dance",
+				uploader: "creator",
+				uploader_id: "6800000000000000000",
+				upload_date: "20240105",
+				description: "dance #fyp",
+				duration: 15,
+				view_count: 120000,
+				like_count: 9000,
+			}),
+		);
+		expect(describeMedia(info)).toMatchObject({
+			title: "dance",
+			uploader: "creator",
+			uploaderId: "6800000000000000000",
+			uploadDate: "20240105",
+			description: "dance #fyp",
+			duration: 15,
+			viewCount: 120000,
+			likeCount: 9000,
+		});
+	});
+
+	it("lists thumbnails largest first and picks the largest as thumbnail", () => {
+		const info = parseVideoInfo(
+			JSON.stringify({
+				id: "abc",
+				title: "Clip",
+				thumbnail: "https://i.ytimg.com/vi/abc/hqdefault.jpg",
+				thumbnails: [
+					{ url: "https://i.ytimg.com/vi/abc/default.jpg", width: 120, height: 90 },
+					{ url: "https://i.ytimg.com/vi/abc/unsized.webp" },
+					{ url: "https://i.ytimg.com/vi/abc/maxres.jpg", width: 1280, height: 720 },
+					{ width: 640, height: 480 },
+					{ url: "https://i.ytimg.com/vi/abc/hq.jpg", width: 480, height: "360" },
+				],
+			}),
+		);
+		const meta = describeMedia(info);
+		expect(meta.thumbnail).toBe("https://i.ytimg.com/vi/abc/maxres.jpg");
+		expect(meta.thumbnails).toEqual([
+			{ url: "https://i.ytimg.com/vi/abc/maxres.jpg", width: 1280, height: 720 },
+			{ url: "https://i.ytimg.com/vi/abc/default.jpg", width: 120, height: 90 },
+			{ url: "https://i.ytimg.com/vi/abc/unsized.webp" },
+			{ url: "https://i.ytimg.com/vi/abc/hq.jpg" },
+		]);
+	});
+
+	it("keeps the platform's thumbnail when no entry has dimensions", () => {
+		const info = parseVideoInfo(
+			JSON.stringify({
+				id: "1",
+				title: "Clip",
+				thumbnail: "https://pbs.twimg.com/media/b.jpg",
+				thumbnails: [{ url: "https://pbs.twimg.com/media/a.jpg" }],
+			}),
+		);
+		expect(describeMedia(info)).toMatchObject({
+			thumbnail: "https://pbs.twimg.com/media/b.jpg",
+			thumbnails: [{ url: "https://pbs.twimg.com/media/a.jpg" }],
+		});
+	});
+
+	it("keeps Twitter's fractional duration", () => {
+		const info = parseVideoInfo(
+			JSON.stringify({
+				id: "1780000000000000000",
+				title: "User - clip",
+				uploader: "User",
+				uploader_id: "user",
+				upload_date: "20240410",
+				duration: 12.345,
+				like_count: 42,
+			}),
+		);
+		const meta = describeMedia(info);
+		expect(meta.duration).toBe(12.345);
+		expect(meta.uploaderId).toBe("user");
+		expect(meta.viewCount).toBeUndefined();
+	});
+
+	it("leaves fields the platform omits undefined", () => {
+		const info = parseVideoInfo(
+			JSON.stringify({
+				id: "C1a2b3c4d5",
+				title: "Video by someone",
+				uploader: "someone",
+				like_count: null,
+				view_count: "lots",
+			}),
+		);
+		const meta = describeMedia(info);
+		expect(meta.uploader).toBe("someone");
+		expect(meta.likeCount).toBeUndefined();
+		expect(meta.viewCount).toBeUndefined();
+		expect(meta.uploadDate).toBeUndefined();
+		expect(JSON.parse(JSON.stringify(meta))).toEqual({
+			title: "Video by someone",
+			uploader: "someone",
+			isLive: false,
+		});
+	});
+});
+
+describe("buildChoices audio sizing", () => {
+	const MIXED: VideoInfo = {
+		id: "mixed",
+		title: "Mixed",
+		formats: [
+			{ format_id: "a-low", acodec: "mp4a", vcodec: "none", abr: 48, filesize: 300_000 },
+			{ format_id: "a-high", acodec: "opus", vcodec: "none", abr: 160, filesize: 1_048_576 },
+			{ format_id: "a-mid", acodec: "mp4a", vcodec: "none", abr: 128, filesize: 800_000 },
+			{ format_id: "v480", vcodec: "avc1", acodec: "none", height: 480, filesize: 2_097_152 },
+			{ format_id: "m360", vcodec: "avc1", acodec: "mp4a", height: 360, filesize: 1_000_000 },
+		],
+	};
+
+	it("sizes the audio choice from the highest-bitrate audio-only stream", () => {
+		const audio = buildChoices(MIXED).find((c) => c.kind === "audio");
+		expect(audio?.sizeLabel).toBe("1.0 MB");
+	});
+
+	it("leaves video choices to video-bearing formats only", () => {
+		const video = buildChoices(MIXED).filter((c) => c.kind === "video");
+		expect(video.map((c) => c.quality)).toEqual(["480p", "360p"]);
+		// Video-only 480p is merged with the best audio, muxed 360p is not.
+		expect(video[0].sizeLabel).toBe("3.0 MB");
+		expect(video[1].sizeLabel).toBe("976.6 KB");
+	});
+});
+
+describe("storyboard and preview formats", () => {
+	/** Trimmed from a Twitter `yt-dlp -J` dump, plus a storyboard: `http-95` is a preview variant. */
+	const TWEET: VideoInfo = {
+		id: "1",
+		title: "Tweet",
+		formats: [
+			{ format_id: "hls-audio-128000-Audio", vcodec: "none", acodec: "mp4a.40.2" },
+			{ format_id: "hls-256", vcodec: "avc1.4D401E", acodec: "none", height: 270 },
+			{ format_id: "http-95", ext: "mp4", vcodec: "avc1", height: 120 },
+			{ format_id: "hls-832", vcodec: "avc1.4D401F", acodec: "none", height: 360 },
+			{ format_id: "http-2176", ext: "mp4", vcodec: "avc1", height: 720 },
+			{ format_id: "sb0", ext: "mhtml", protocol: "mhtml", vcodec: "images", height: 720 },
+		],
+	};
+	const prevFloor = process.env.MIN_VIDEO_HEIGHT;
+	const prevQuality = process.env.DEFAULT_QUALITY;
+
+	afterEach(() => {
+		if (prevFloor === undefined) delete process.env.MIN_VIDEO_HEIGHT;
+		else process.env.MIN_VIDEO_HEIGHT = prevFloor;
+		if (prevQuality === undefined) delete process.env.DEFAULT_QUALITY;
+		else process.env.DEFAULT_QUALITY = prevQuality;
+	});
+
+	it("drops storyboards and renditions below the height floor", () => {
+		delete process.env.MIN_VIDEO_HEIGHT;
+		const video = buildChoices(TWEET).filter((c) => c.kind === "video");
+		expect(video.map((c) => c.quality)).toEqual(["720p", "360p", "270p"]);
+		expect(video[0].details?.formatId).toBe("http-2176+hls-audio-128000-Audio");
+	});
+
+	it("reads the floor from MIN_VIDEO_HEIGHT", () => {
+		process.env.MIN_VIDEO_HEIGHT = "360";
+		expect(minVideoHeight()).toBe(360);
+		const video = buildChoices(TWEET).filter((c) => c.kind === "video");
+		expect(video.map((c) => c.quality)).toEqual(["720p", "360p"]);
+
+		process.env.MIN_VIDEO_HEIGHT = "1";
+		expect(buildChoices(TWEET).filter((c) => c.kind === "video")).toHaveLength(4);
+	});
+
+	it("caps choices at DEFAULT_QUALITY unless the request sets videoQuality", () => {
+		delete process.env.MIN_VIDEO_HEIGHT;
+		const qualities = (options?: ChoiceOptions) =>
+			buildChoices(TWEET, options)
+				.filter((c) => c.kind === "video")
+				.map((c) => c.quality);
+
+		process.env.DEFAULT_QUALITY = "360p";
+		expect(defaultVideoQuality()).toBe("360");
+		expect(qualities()).toEqual(["360p", "270p"]);
+		expect(qualities({ videoQuality: "max" })).toEqual(["720p", "360p", "270p"]);
+
+		process.env.DEFAULT_QUALITY = "best";
+		expect(defaultVideoQuality()).toBe("max");
+		expect(qualities()).toEqual(["720p", "360p", "270p"]);
+	});
+
+	it("refuses a DEFAULT_QUALITY outside the videoQuality allowlist", () => {
+		for (const value of ["721", "bestvideo+bestaudio", "720p; rm"]) {
+			process.env.DEFAULT_QUALITY = value;
+			expect(() => defaultVideoQuality()).toThrow("DEFAULT_QUALITY must be one of");
+		}
+	});
+
+	it("falls back to best when only storyboards carry a height", () => {
+		const choices = buildChoices({
+			id: "2",
+			title: "Sparse",
+			formats: [
+				{ format_id: "sb0", ext: "mhtml", protocol: "mhtml", vcodec: "images", height: 90 },
+			],
+		});
+		expect(choices.map((c) => c.id)).toEqual(["v-best", "a-mp3"]);
+	});
+});
+
+describe("Facebook DASH formats", () => {
+	/** Trimmed from a reel's `yt-dlp -J`: codec-less progressive `sd`/`hd`, then DASH streams. */
+	const REEL = parseVideoInfo(
+		JSON.stringify({
+			id: "1234567890123456",
+			title: "Reel by NASA",
+			formats: [
+				{ format_id: "sd", ext: "mp4", protocol: "https" },
+				{ format_id: "hd", ext: "mp4", protocol: "https" },
+				{ format_id: "880a", ext: "m4a", vcodec: "none", acodec: "mp4a.40.5", abr: 48 },
+				{ format_id: "881a", ext: "m4a", vcodec: "none", acodec: "mp4a.40.2", abr: 96 },
+				{ format_id: "770v", ext: "mp4", vcodec: "avc1.4d401e", acodec: "none", height: 854 },
+				{ format_id: "771v", ext: "mp4", vcodec: "avc1.64001f", acodec: "none", height: 1280 },
+			],
+		}),
+	);
+
+	it("merges each video-only stream with the best audio", () => {
+		const video = buildChoices(REEL).filter((c) => c.kind === "video");
+		expect(video.map((c) => [c.quality, c.details?.formatId, c.details?.acodec])).toEqual([
+			["1280p", "771v+881a", "mp4a.40.2"],
+			["854p", "770v+881a", "mp4a.40.2"],
+		]);
+		for (const choice of video) expect(choice.args[1]).toContain("+ba");
+	});
+
+	it("extracts audio from the best audio-only stream", () => {
+		const audio = buildChoices(REEL).find((c) => c.kind === "audio");
+		expect(audio?.details?.formatId).toBe("881a");
+	});
+});
+
+describe("Twitch clip formats", () => {
+	const CDN = "https://production.assets.clips.twitchcdn.net/v2/media/AwkwardHelpless";
+	/** Trimmed from a clip's `yt-dlp -J`: muxed MP4s labelled `<height>p<fps>`, no numeric height. */
+	const CLIP = parseVideoInfo(
+		JSON.stringify({
+			id: "AwkwardHelplessSalamanderSwiftRage",
+			title: "what a play",
+			uploader: "streamer",
+			extractor_key: "TwitchClips",
+			duration: 28.4,
+			formats: [
+				{ format_id: "360p30", url: `${CDN}/360.mp4`, ext: "mp4", vcodec: "avc1", acodec: "mp4a" },
+				{ format_id: "720p60", url: `${CDN}/720.mp4`, ext: "mp4", vcodec: "avc1", acodec: "mp4a" },
+				{
+					format_id: "1080",
+					format_note: "1080p60",
+					url: `${CDN}/1080.mp4`,
+					ext: "mp4",
+					vcodec: "avc1",
+					acodec: "mp4a",
+				},
+			],
+		}),
+	);
+
+	it("reads heights and frame rates from the quality labels", () => {
+		const video = buildChoices(CLIP).filter((c) => c.kind === "video");
+		expect(video.map((c) => [c.quality, c.details?.fps, c.details?.hasAudio])).toEqual([
+			["1080p", 60, true],
+			["720p", 60, true],
+			["360p", 30, true],
+		]);
+	});
+
+	it("selects a label-derived height by format id, which yt-dlp can match", () => {
+		const video = buildChoices(CLIP).filter((c) => c.kind === "video");
+		expect(video.map((c) => c.args)).toEqual([["-f", "1080"], ["-f", "720p60"], ["-f", "360p30"]]);
+	});
+
+	it("parses a label in the height field and drops one it cannot read", () => {
+		const info = parseVideoInfo(
+			JSON.stringify({
+				id: "1",
+				title: "Clip",
+				formats: [
+					{ format_id: "a", vcodec: "avc1", acodec: "mp4a", height: "480p" },
+					{ format_id: "b", vcodec: "avc1", acodec: "mp4a", height: "source" },
+				],
+			}),
+		);
+		expect(info.formats?.map((f) => f.height)).toEqual([480, undefined]);
+	});
+});
+
+describe("split audio without ffmpeg", () => {
+	/** A v.redd.it post: DASH video-only renditions and one audio track, nothing muxed. */
+	const REDDIT = parseVideoInfo(
+		JSON.stringify({
+			id: "abc123",
+			title: "Reddit clip",
+			formats: [
+				{ format_id: "dash-audio", ext: "m4a", vcodec: "none", acodec: "mp4a.40.2", abr: 128 },
+				{ format_id: "dash-480", ext: "mp4", vcodec: "avc1.4d401e", acodec: "none", height: 480 },
+				{ format_id: "dash-720", ext: "mp4", vcodec: "avc1.4d401f", acodec: "none", height: 720 },
+			],
+		}),
+	);
+
+	it("merges the audio track and flags every choice as having sound", () => {
+		const choices = buildChoices(REDDIT);
+		expect(choices.map((c) => c.details?.hasAudio)).toEqual([true, true, true]);
+		expect(choices[0].args).toContain("--merge-output-format");
+		expect(choiceWarnings(REDDIT, choices)).toEqual([]);
+	});
+
+	it("degrades to video-only choices with a warning when it cannot merge", () => {
+		const choices = buildChoices(REDDIT, { canMerge: false });
+		const video = choices.filter((c) => c.kind === "video");
+		expect(video.map((c) => [c.details?.formatId, c.details?.hasAudio])).toEqual([
+			["dash-720", false],
+			["dash-480", false],
+		]);
+		expect(video[0].args).toEqual(["-f", "b[height=720]/bv*[height=720]/b[height<=720]/bv*/b"]);
+		expect(choiceWarnings(REDDIT, choices)).toEqual([
+			"ffmpeg is unavailable, so videos with a separate audio track are video-only",
+		]);
+	});
+});
+
+describe("media URL expiry", () => {
+	it("reads each platform CDN's signed-expiry param", () => {
+		const signed: [string, number][] = [
+			["https://v16-webapp.tiktok.com/video/tos/a/?x-expires=1760000000&x-signature=x", 1760000000],
+			["https://scontent-lax3-1.cdninstagram.com/o1/v/t16/a.mp4?oh=00_Ab&oe=68F1A2B3", 0x68f1a2b3],
+			["https://video.xx.fbcdn.net/v/t42.1790-2/a.mp4?_nc_cat=1&oh=00_A&oe=68F00000", 0x68f00000],
+			["https://rr3---sn-abc.googlevideo.com/videoplayback?expire=1760003600&itag=18", 1760003600],
+			["https://production.assets.clips.twitchcdn.net/v2/media/a.mp4?exp=1760007200", 1760007200],
+		];
+		for (const [url, expiresAt] of signed) expect(formatExpiry(url)).toBe(expiresAt);
+	});
+
+	it("is undefined for unsigned, malformed or missing URLs", () => {
+		expect(formatExpiry("https://video.twimg.com/ext_tw_video/1/pu/a.mp4?tag=12")).toBeUndefined();
+		expect(formatExpiry("https://example.com/a.mp4?expire=soon")).toBeUndefined();
+		expect(formatExpiry("https://example.com/a.mp4?exp=-1")).toBeUndefined();
+		expect(formatExpiry("not a url")).toBeUndefined();
+		expect(formatExpiry(undefined)).toBeUndefined();
+	});
+
+	it("gives a merged choice the earlier of its two expiries", () => {
+		const info = parseVideoInfo(
+			JSON.stringify({
+				id: "1",
+				title: "clip",
+				formats: [
+					{
+						format_id: "v720",
+						url: "https://v16.tiktokcdn.com/v.mp4?x-expires=1760000600",
+						vcodec: "avc1",
+						acodec: "none",
+						height: 720,
+					},
+					{
+						format_id: "a",
+						url: "https://v16.tiktokcdn.com/a.m4a?x-expires=1760000300",
+						vcodec: "none",
+						acodec: "mp4a",
+					},
+				],
+			}),
+		);
+		const [video, audio] = buildChoices(info);
+		expect(video.details?.expiresAt).toBe(1760000300);
+		expect(audio.details?.expiresAt).toBe(1760000300);
+	});
+});
+
+describe("video choice limit", () => {
+	const LADDER: VideoInfo = {
+		title: "Ladder",
+		formats: [144, 240, 360, 480, 720, 1080].map((height) => ({
+			format_id: `v${height}`,
+			vcodec: "avc1",
+			acodec: "mp4a",
+			height,
+		})),
+	};
+	const prevLimit = process.env.FORMATS_LIMIT;
+
+	afterEach(() => {
+		if (prevLimit === undefined) delete process.env.FORMATS_LIMIT;
+		else process.env.FORMATS_LIMIT = prevLimit;
+	});
+
+	it("returns the whole ladder without a limit", () => {
+		expect(buildChoices(LADDER).filter((c) => c.kind === "video")).toHaveLength(6);
+	});
+
+	it("keeps the top heights plus the lowest when truncating", () => {
+		const video = buildChoices(LADDER, undefined, 3).filter((c) => c.kind === "video");
+		expect(video.map((c) => c.quality)).toEqual(["1080p", "720p", "144p"]);
+	});
+
+	it("keeps only the highest height at limit 1", () => {
+		const choices = buildChoices(LADDER, undefined, formatsLimit(1));
+		expect(choices.filter((c) => c.kind === "video").map((c) => c.quality)).toEqual(["1080p"]);
+	});
+
+	it("returns every height, highest first, when the limit exceeds the ladder", () => {
+		const choices = buildChoices(LADDER, undefined, formatsLimit(10));
+		expect(choices.filter((c) => c.kind === "video").map((c) => c.quality)).toEqual([
+			"1080p",
+			"720p",
+			"480p",
+			"360p",
+			"240p",
+			"144p",
+		]);
+	});
+
+	it("applies the default limit when the request sets none", () => {
+		delete process.env.FORMATS_LIMIT;
+		const ladder: VideoInfo = {
+			title: "Tall ladder",
+			formats: [144, 240, 360, 480, 540, 720, 1080, 1440, 2160, 4320].map((height) =>
+				muxedMp4(`v${height}`, height),
+			),
+		};
+		const video = buildChoices(ladder, undefined, formatsLimit()).filter((c) => c.kind === "video");
+		expect(video).toHaveLength(8);
+		expect(video[0].quality).toBe("4320p");
+		expect(video.at(-1)?.quality).toBe("144p");
+	});
+
+	it("prefers the request, then FORMATS_LIMIT, then the default", () => {
+		delete process.env.FORMATS_LIMIT;
+		expect(formatsLimit()).toBe(8);
+		expect(formatsLimit(0)).toBe(8);
+		process.env.FORMATS_LIMIT = "4";
+		expect(formatsLimit()).toBe(4);
+		expect(formatsLimit(12)).toBe(12);
+	});
+
+	it("clamps the configured default into range", () => {
+		process.env.FORMATS_LIMIT = "500";
+		expect(formatsLimit()).toBe(20);
+		process.env.FORMATS_LIMIT = "junk";
+		expect(formatsLimit()).toBe(8);
+	});
+});
+
+describe("multi-item posts", () => {
+	const CAROUSEL = parseVideoInfo(
+		JSON.stringify({
+			_type: "playlist",
+			id: "post",
+			title: "Carousel",
+			entries: [
+				{ id: "1", title: "Photo", ext: "jpg", thumbnail: "https://cdn.example/1.jpg" },
+				{ id: "2", title: "Clip", formats: [muxedMp4("m720", 720)] },
+				{ id: "3", title: "Clip 2", formats: [muxedMp4("m480", 480)] },
+			],
+		}),
+	);
+
+	it("parses playlist entries", () => {
+		expect(CAROUSEL.entries?.map((e) => e.title)).toEqual(["Photo", "Clip", "Clip 2"]);
+	});
+
+	it("builds the top-level picker from the first video entry", () => {
+		const video = buildChoices(CAROUSEL).filter((c) => c.kind === "video");
+		expect(video.map((c) => c.id)).toEqual(["v-720p"]);
+		expect(video[0].args.slice(-2)).toEqual(["--playlist-items", "2"]);
+	});
+
+	it("marks images and pins each video entry's choices", () => {
+		const items = buildPostItems(CAROUSEL);
+		expect(items.map((i) => i.mediaType)).toEqual(["image", "video", "video"]);
+		expect(items[0].choices).toEqual([]);
+		const clip = items[2].choices.find((c) => c.kind === "video");
+		expect(clip?.id).toBe("i3-v-480p");
+		expect(clip?.args.slice(-2)).toEqual(["--playlist-items", "3"]);
+	});
+
+	it("lets the download route find any signed choice", () => {
+		const ids = signableChoices(CAROUSEL).map((c) => c.id);
+		expect(ids).toContain("v-720p");
+		expect(ids).toContain("i2-v-720p");
+		expect(ids).toContain("i3-a-mp3");
+	});
+
+	it("leaves single-item media without items", () => {
+		expect(buildPostItems(FIXTURE)).toEqual([]);
+	});
+});
+
+describe("probeTimeoutSecs", () => {
+	const prev = {
+		secs: process.env.EXTRACT_TIMEOUT_SECS,
+		max: process.env.EXTRACT_TIMEOUT_MAX_SECS,
+	};
+
+	afterEach(() => {
+		if (prev.secs === undefined) delete process.env.EXTRACT_TIMEOUT_SECS;
+		else process.env.EXTRACT_TIMEOUT_SECS = prev.secs;
+		if (prev.max === undefined) delete process.env.EXTRACT_TIMEOUT_MAX_SECS;
+		else process.env.EXTRACT_TIMEOUT_MAX_SECS = prev.max;
+	});
+
+	it("uses the request value, then EXTRACT_TIMEOUT_SECS, then 30", () => {
+		delete process.env.EXTRACT_TIMEOUT_SECS;
+		delete process.env.EXTRACT_TIMEOUT_MAX_SECS;
+		expect(probeTimeoutSecs()).toBe(30);
+		expect(probeTimeoutSecs(10)).toBe(10);
+		process.env.EXTRACT_TIMEOUT_SECS = "45";
+		expect(probeTimeoutSecs()).toBe(45);
+	});
+
+	it("clamps between 5 seconds and EXTRACT_TIMEOUT_MAX_SECS", () => {
+		process.env.EXTRACT_TIMEOUT_MAX_SECS = "60";
+		expect(probeTimeoutSecs(1)).toBe(5);
+		expect(probeTimeoutSecs(600)).toBe(60);
+	});
+});
+
+describe("live streams", () => {
+	it("detects live media from either yt-dlp field", () => {
+		expect(isLive(parseVideoInfo(JSON.stringify({ id: "a", is_live: true })))).toBe(true);
+		expect(isLive(parseVideoInfo(JSON.stringify({ id: "b", live_status: "is_live" })))).toBe(true);
+		expect(isLive(parseVideoInfo(JSON.stringify({ id: "c", live_status: "was_live" })))).toBe(
+			false,
+		);
+		expect(describeMedia(parseVideoInfo(JSON.stringify({ id: "d", is_live: "yes" }))).isLive).toBe(
+			false,
+		);
+	});
+
+	it("caps the capture duration", () => {
+		expect(liveCaptureSecs()).toBe(300);
+		expect(liveCaptureSecs(60)).toBe(60);
+		expect(liveCaptureSecs(86400)).toBe(1800);
+	});
+
+	it("records from now with an ffmpeg time limit", () => {
+		const args = liveCaptureArgs(120);
+		expect(args).toContain("--no-live-from-start");
+		expect(args.slice(-2)).toEqual(["--downloader-args", "ffmpeg_i:-t 120"]);
+	});
+});
+
+describe("listProfile", () => {
+	const PROFILE = "https://www.tiktok.com/@creator";
+
+	afterEach(() => clearProfileCache());
+
+	function listingRunner(lines: unknown[]) {
+		const runner = Object.assign(
+			async (_command: string, args: string[]): Promise<CommandResult> => {
+				runner.args = args;
+				runner.calls++;
+				return { stdout: lines.map((l) => JSON.stringify(l)).join("\n"), stderr: "", exitCode: 0 };
+			},
+			{ calls: 0, args: [] as string[] },
+		);
+		return runner;
+	}
+
+	it("lists flat entries and drops ones that fail URL validation", async () => {
+		const run = listingRunner([
+			{
+				id: "1",
+				title: "first",
+				url: "https://www.tiktok.com/@creator/video/1",
+				thumbnails: [
+					{ url: "https://p16.tiktokcdn.com/small.jpg" },
+					{ url: "https://p16.tiktokcdn.com/big.jpg" },
+				],
+			},
+			{ id: "2", url: "https://evil.example/2" },
+		]);
+		const entries = await listProfile("yt-dlp", PROFILE, { limit: 5, run });
+		expect(entries).toEqual([
+			{
+				id: "1",
+				url: "https://www.tiktok.com/@creator/video/1",
+				title: "first",
+				thumbnail: "https://p16.tiktokcdn.com/big.jpg",
+			},
+		]);
+		expect(run.args).toContain("--flat-playlist");
+		expect(run.args[run.args.indexOf("--playlist-end") + 1]).toBe("5");
+	});
+
+	it("caps the listing and reuses a recent result", async () => {
+		const run = listingRunner([]);
+		await listProfile("yt-dlp", PROFILE, { limit: 50, run });
+		await listProfile("yt-dlp", PROFILE, { limit: 50, run });
+		expect(run.args[run.args.indexOf("--playlist-end") + 1]).toBe("10");
+		expect(run.calls).toBe(1);
+	});
+
+	it("keeps the original extraction time on a cache hit", async () => {
+		const now = spyOn(Date, "now").mockReturnValue(1_700_000_000_000);
+		try {
+			const run = listingRunner([]);
+			const first: ExtractionStats = { attempts: 0, cacheHit: false };
+			await listProfile("yt-dlp", PROFILE, { run, stats: first });
+			expect(first.extractedAt).toBe(1_700_000_000);
+
+			now.mockReturnValue(1_700_000_030_000);
+			const second: ExtractionStats = { attempts: 0, cacheHit: false };
+			await listProfile("yt-dlp", PROFILE, { run, stats: second });
+			expect(second).toEqual({ attempts: 0, cacheHit: true, extractedAt: 1_700_000_000 });
+		} finally {
+			now.mockRestore();
+		}
+	});
+
+	it("evicts the oldest listings to stay within PROFILE_CACHE_MAX_BYTES", async () => {
+		const prev = process.env.PROFILE_CACHE_MAX_BYTES;
+		process.env.PROFILE_CACHE_MAX_BYTES = "1200";
+		try {
+			const title = "x".repeat(400);
+			const listing = (n: number) =>
+				listingRunner([{ id: "1", title, url: `https://www.tiktok.com/@c${n}/video/1` }]);
+			const runs = [1, 2, 3].map(listing);
+			for (const [n, run] of runs.entries()) {
+				await listProfile("yt-dlp", `https://www.tiktok.com/@c${n + 1}`, { run });
+				expect(profileCacheUsage().bytes).toBeLessThanOrEqual(1200);
+			}
+			expect(profileCacheUsage().entries).toBe(2);
+
+			// The first listing was evicted; the last two are still served from the cache.
+			await listProfile("yt-dlp", "https://www.tiktok.com/@c1", { run: runs[0] });
+			await listProfile("yt-dlp", "https://www.tiktok.com/@c3", { run: runs[2] });
+			expect(runs.map((run) => run.calls)).toEqual([2, 1, 1]);
+
+			// A listing larger than the whole budget is never cached.
+			const huge = listingRunner([
+				{ id: "1", title: "x".repeat(2_000), url: "https://www.tiktok.com/@big/video/1" },
+			]);
+			await listProfile("yt-dlp", "https://www.tiktok.com/@big", { run: huge });
+			await listProfile("yt-dlp", "https://www.tiktok.com/@big", { run: huge });
+			expect(huge.calls).toBe(2);
+			expect(profileCacheUsage().bytes).toBeLessThanOrEqual(1200);
+		} finally {
+			if (prev === undefined) delete process.env.PROFILE_CACHE_MAX_BYTES;
+			else process.env.PROFILE_CACHE_MAX_BYTES = prev;
+		}
+	});
+});
@@ -25,11 +25,19 @@ export const SERVICES = [
 	{ id: "twitter", label: "X / Twitter", hosts: ["x.com", "twitter.com"] },
 	{ id: "vimeo", label: "Vimeo", hosts: ["vimeo.com"] },
 	{ id: "vk", label: "VK", hosts: ["vk.com", "vkvideo.ru"] },
-	{ id: "youtube", label: "YouTube", hosts: ["youtube.com", "youtu.be"] },
+	{ id: "youtube", label: "YouTube Shorts", hosts: ["youtube.com", "youtu.be"] },
 ] as const;
 
 export type SupportedPlatform = (typeof SERVICES)[number]["id"];
 
+/**
+ * Services off unless the operator turns them on, so their hosts are left out
+ * of {@link ALLOWED_PLATFORM_DOMAINS}. Full YouTube support is deliberately
+ * not offered: `ENABLE_YOUTUBE_SHORTS=true` admits Shorts and `youtu.be` links
+ * only, and `validateUrl` refuses watch pages and channels even then.
+ */
+export const OPT_IN_PLATFORMS: readonly SupportedPlatform[] = ["youtube"];
+
 export const PLATFORM_HOSTS = SERVICES.reduce(
 	(acc, s) => {
 		acc[s.id] = [...s.hosts];
@@ -38,8 +46,81 @@ export const PLATFORM_HOSTS = SERVICES.reduce(
 	{} as Record<SupportedPlatform, string[]>,
 );
 
-export const ALLOWED_PLATFORM_DOMAINS = SERVICES.flatMap((s) => [...s.hosts]);
+export const ALLOWED_PLATFORM_DOMAINS = SERVICES.filter(
+	(s) => !OPT_IN_PLATFORMS.includes(s.id),
+).flatMap((s) => [...s.hosts]);
+
+/** One media URL per service that `validateUrl` accepts, shown by `GET /api/platforms`. */
+export const PLATFORM_EXAMPLES: Record<SupportedPlatform, string> = {
+	bilibili: "https://www.bilibili.com/video/BV1GJ411x7h7",
+	bluesky: "https://bsky.app/profile/user.bsky.social/post/3l6oveex3ii2l",
+	dailymotion: "https://www.dailymotion.com/video/x8m8jmk",
+	facebook: "https://www.facebook.com/reel/1234567890",
+	instagram: "https://www.instagram.com/reel/C1a2B3c4D5e/",
+	loom: "https://www.loom.com/share/0281766fa2d04bb788eaf19e65135184",
+	ok: "https://ok.ru/video/1234567890",
+	pinterest: "https://www.pinterest.com/pin/1234567890/",
+	newgrounds: "https://www.newgrounds.com/portal/view/123456",
+	reddit: "https://www.reddit.com/r/videos/comments/abc123/title/",
+	rutube: "https://rutube.ru/video/0123456789abcdef0123456789abcdef/",
+	snapchat: "https://www.snapchat.com/spotlight/W7_EDlXWTBiXAEEniNoMPwAAY",
+	soundcloud: "https://soundcloud.com/artist/track",
+	streamable: "https://streamable.com/abc123",
+	tiktok: "https://www.tiktok.com/@user/video/7123456789012345678",
+	tumblr: "https://www.tumblr.com/user/123456789",
+	twitch: "https://clips.twitch.tv/FunnyClip-abc123_XYZ",
+	twitter: "https://x.com/user/status/1234567890",
+	vimeo: "https://vimeo.com/357274789",
+	vk: "https://vk.com/video-12345_67890",
+	youtube: "https://www.youtube.com/shorts/jNQXAC9IVRw",
+};
+
+/**
+ * Media CDNs that serve each platform's files. `validateUrl` never accepts
+ * these; the API only probes a direct CDN link when the operator opts in, and
+ * matches hosts by suffix exactly like platform hosts, over HTTPS only.
+ */
+export const TRUSTED_CDN_HOSTS: Partial<Record<SupportedPlatform, readonly string[]>> = {
+	facebook: ["fbcdn.net"],
+	instagram: ["cdninstagram.com"],
+	tiktok: ["tiktokcdn.com", "tiktokcdn-us.com"],
+	twitter: ["video.twimg.com"],
+};
+
+// Real share URLs never contain whitespace or control characters. `new URL()`
+// parsing + the host allowlist do the actual security work, and yt-dlp is
+// spawned without a shell, so this only rejects malformed input and anything
+// that could smuggle a line break into a header or log line. Percent-encoded
+// octets are left alone — they are ordinary URL characters.
+export const INVALID_URL_CHAR_REGEX = /[\s\p{Cc}]/u;
 
-// Real share URLs never contain whitespace. `new URL()` parsing + the host
-// allowlist do the actual security work; this just rejects malformed input.
-export const WHITESPACE_ONLY_REGEX = /\s/;
+/** Upper bound on URLs accepted by one `POST /api/resolve/batch` call. */
+export const MAX_BATCH_URLS = 10;
+
+/** Upper bound for the `formatsLimit` resolve option and the `FORMATS_LIMIT` default. */
+export const MAX_FORMATS_LIMIT = 20;
+
+/**
+ * The `lang` resolve option: a BCP 47-style tag such as `en`, `pt-BR` or
+ * `zh-Hant-TW`. Only letters, digits and single hyphens, so it can go into a
+ * header value or an extractor argument as-is.
+ */
+export const LANGUAGE_TAG_REGEX = /^[A-Za-z]{2,3}(?:-[A-Za-z0-9]{2,8})*$/;
+
+/** Upper bound on entries listed for a creator/profile URL (`entriesLimit`). */
+export const MAX_PROFILE_ENTRIES = 10;
+
+/**
+ * Share-link redirectors the API follows to a canonical URL before probing.
+ * Matched exactly rather than by suffix. Some (`t.co`, `instagr.am`) are not
+ * platform hosts themselves, so the URL they land on must pass `validateUrl`.
+ */
+export const SHORTLINK_HOSTS: readonly string[] = [
+	"vm.tiktok.com",
+	"vt.tiktok.com",
+	"redd.it",
+	"v.redd.it",
+	"t.co",
+	"instagr.am",
+	"fb.watch",
+];
@@ -2,9 +2,56 @@
  * Core type definitions shared between API and web
  */
 
+import type { SupportedPlatform } from "./constants";
+
+/**
+ * Machine-readable failure classes. Clients branch on these, never on the
+ * human-readable `error` text, which may be reworded at any time.
+ */
+export const ERROR_CODES = [
+	"BAD_REQUEST",
+	"BAD_REQUEST_BODY",
+	"INVALID_URL",
+	"INVALID_CHAR",
+	"UNSUPPORTED_PLATFORM",
+	"INVALID_SIGNATURE",
+	"BLOCKED",
+	"PRIVATE_CONTENT",
+	"NOT_FOUND",
+	"FORMAT_UNAVAILABLE",
+	"LIVE_CONTENT",
+	"JOB_NOT_READY",
+	"PAYLOAD_TOO_LARGE",
+	"RANGE_NOT_SATISFIABLE",
+	"RATE_LIMITED",
+	"GEO_BLOCKED",
+	"BLOCKED_CONTENT",
+	"EXTRACTION_FAILED",
+	"INTERNAL",
+	"YTDLP_FAILED",
+	"NO_METADATA",
+	"YTDLP_UNAVAILABLE",
+	"YTDLP_OUTDATED",
+	"SERVER_BUSY",
+	"TIMEOUT",
+] as const;
+
+export type ErrorCode = (typeof ERROR_CODES)[number];
+
 export interface ErrorResponse {
 	success: boolean;
 	error: string;
+	code?: ErrorCode;
+	/** Dotted path of the body field a `BAD_REQUEST_BODY` is about, e.g. `urls.2`. */
+	field?: string;
+	/** Raw yt-dlp stderr; only present when the server runs with `DEBUG_ERRORS=1`. */
+	debug?: string;
+	/** For `INVALID_CHAR`: code point index of the offending character in the trimmed URL. */
+	position?: number;
+	/** For `INVALID_CHAR`: the offending character. */
+	character?: string;
+	/** For a per-URL `RATE_LIMITED`: seconds until a retry is accepted, as in `Retry-After`. */
+	retryAfter?: number;
 }
 
 export const AUDIO_FORMATS = ["mp3", "ogg", "wav", "opus"] as const;
@@ -29,7 +76,34 @@ export interface MediaOptions {
 	downloadMode?: (typeof DOWNLOAD_MODES)[number];
 }
 
-export interface MediaChoiceItem {
+/**
+ * The yt-dlp source format(s) behind a choice. `formatId` uses yt-dlp's
+ * `video+audio` notation when a video-only stream is merged with audio.
+ */
+export interface FormatDetails {
+	formatId?: string;
+	width?: number;
+	height?: number;
+	fps?: number;
+	vcodec?: string;
+	acodec?: string;
+	/** Total bitrate in kbit/s. */
+	tbr?: number;
+	/**
+	 * Whether the downloaded file will carry sound: false for a video-only
+	 * stream the server cannot merge with a separate audio track.
+	 */
+	hasAudio?: boolean;
+	/** Approximate bytes, merged audio included, when the platform reports sizes. */
+	filesize?: number;
+	/**
+	 * Unix seconds at which the platform's signed media URL expires, when the
+	 * CDN says. The download link stops working then; resolve again to renew it.
+	 */
+	expiresAt?: number;
+}
+
+export interface MediaChoiceItem extends FormatDetails {
 	id?: string;
 	type: "video" | "audio";
 	quality?: string;
@@ -39,12 +113,140 @@ export interface MediaChoiceItem {
 	thumb?: string;
 }
 
-export interface ResolveResponse {
-	status: "picker" | "error";
-	filename?: string;
+/** A preview image; dimensions are absent when the platform does not report them. */
+export interface Thumbnail {
+	url: string;
+	width?: number;
+	height?: number;
+}
+
+/** Descriptive metadata for a resolved item; every field is optional. */
+export interface MediaMetadata {
 	title?: string;
+	/** The largest of `thumbnails`, or the platform's own pick when none has dimensions. */
 	thumbnail?: string;
+	/** Every preview the platform offers, largest area first; unsized ones last. */
+	thumbnails?: Thumbnail[];
+	/** Seconds; may be fractional. */
 	duration?: number;
+	uploader?: string;
+	uploaderId?: string;
+	/** `YYYYMMDD`, as reported by the platform. */
+	uploadDate?: string;
+	description?: string;
+	viewCount?: number;
+	likeCount?: number;
+	/** The URL points at a stream that is still broadcasting. */
+	isLive?: boolean;
+}
+
+/** One entry of a multi-item post such as an Instagram carousel. */
+export interface MediaItem {
+	/** 1-based position within the post. */
+	index: number;
+	mediaType: "video" | "image";
+	title?: string;
+	thumbnail?: string;
+	/** Download choices for this entry; absent for images. */
 	picker?: MediaChoiceItem[];
-	error?: { code?: string; message?: string; context?: Record<string, unknown> };
+}
+
+/** One recent upload on a creator/profile page; resolve its `url` to get a picker. */
+export interface ProfileEntry {
+	id: string;
+	url: string;
+	title?: string;
+	thumbnail?: string;
+}
+
+export interface ResolveResponse extends MediaMetadata {
+	/** `list` answers a profile URL with `entries` instead of a picker. */
+	status: "picker" | "list" | "error";
+	/** Where the URL (short links expanded) points, as a `SERVICES` id such as `tiktok`. */
+	platform?: SupportedPlatform;
+	/** The post's ID on `platform` (see `extractVideoId`), when its URL carries one. */
+	videoId?: string;
+	/**
+	 * Unix seconds the metadata was fetched from the platform. A listing served
+	 * from cache keeps its original time, so clients can tell its age. Omitted
+	 * when the server runs with `EXTRACTED_AT=false`.
+	 */
+	extractedAt?: number;
+	filename?: string;
+	/** For multi-item posts, the first video entry's choices. */
+	picker?: MediaChoiceItem[];
+	/** Every entry of a multi-item post; omitted for single-item media. */
+	items?: MediaItem[];
+	/** Recent uploads, newest first, when `status` is `list`. */
+	entries?: ProfileEntry[];
+	/**
+	 * `og-fallback` when yt-dlp failed and the single choice links straight to
+	 * the page's Open Graph video; omitted for normal extraction.
+	 */
+	source?: "og-fallback";
+	/** Why the picker may be missing qualities; omitted when empty. */
+	warnings?: string[];
+	error?: {
+		code?: ErrorCode;
+		message?: string;
+		/** Raw yt-dlp stderr; only present when the server runs with `DEBUG_ERRORS=1`. */
+		debug?: string;
+		context?: Record<string, unknown>;
+	};
+}
+
+export interface BatchResolveItem extends ResolveResponse {
+	url: string;
+}
+
+export interface BatchResolveResponse {
+	results: BatchResolveItem[];
+}
+
+/** One entry of `GET /api/platforms`: a service this deployment accepts links from. */
+export interface PlatformInfo {
+	/** `SERVICES` id, as in a resolve's `platform`. */
+	id: SupportedPlatform;
+	name: string;
+	/** The service's hosts the deployment's allowlist admits; subdomains match too. */
+	domains: string[];
+	exampleUrl: string;
+	/** Whether audio-only choices work here (they need ffmpeg on the server). */
+	supportsAudioOnly: boolean;
+	/** Whether creator pages resolve to a list of recent uploads. */
+	supportsProfiles: boolean;
+}
+
+/** Body of `POST /api/best`: the top video choice of the picker, nothing else. */
+export interface BestFormatResponse {
+	/** Signed `/api/download` link, or the page's video on an Open Graph fallback. */
+	url: string;
+	ext?: string;
+	quality?: string;
+	filesize?: number;
+}
+
+export type DownloadJobStatus = "running" | "done" | "failed";
+
+/** Body of `POST /api/download/async` and `GET /api/download/status/:jobId`. */
+export interface DownloadJobResponse {
+	jobId: string;
+	status: DownloadJobStatus;
+	/** 0-100; reaches 100 when the file is ready at `GET /api/download/result/:jobId`. */
+	progress: number;
+	/**
+	 * The requested name, else the file yt-dlp wrote. Absent while a job with
+	 * no requested name is running, and on a job that failed before writing.
+	 */
+	filename?: string;
+	/** Bytes, once `done`. */
+	size?: number;
+	/** Why the job failed, when `failed`. */
+	error?: {
+		code: ErrorCode;
+		message: string;
+		debug?: string;
+	};
+	/** Epoch milliseconds after which the job and its file are discarded. */
+	expiresAt: number;
 }
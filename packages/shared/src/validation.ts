@@ -1,9 +1,13 @@
 import {
 	ALLOWED_PLATFORM_DOMAINS,
+	INVALID_URL_CHAR_REGEX,
 	PLATFORM_HOSTS,
+	SERVICES,
+	SHORTLINK_HOSTS,
 	type SupportedPlatform,
-	WHITESPACE_ONLY_REGEX,
+	TRUSTED_CDN_HOSTS,
 } from "./constants";
+import type { ErrorCode } from "./types";
 
 function parseHttpUrl(url: string): URL | null {
 	try {
@@ -14,8 +18,63 @@ function parseHttpUrl(url: string): URL | null {
 	}
 }
 
+/** A percent-encoded octet; still present after one decode, it means double encoding. */
+const PERCENT_OCTET_REGEX = /%[0-9a-f]{2}/i;
+/** RFC 3986 unreserved characters, which percent-encoding never needs to protect. */
+const UNRESERVED_CHAR_REGEX = /^[A-Za-z0-9._~-]$/;
+/**
+ * Control characters (`%00`, `%0A`, `%0D`, …). Encoded spaces are ordinary in
+ * share URLs, so only the raw URL is checked for whitespace.
+ */
+const CONTROL_CHAR_REGEX = /\p{Cc}/u;
+/** A `.` or `..` path segment, which only survives parsing when its slashes were encoded. */
+const DOT_SEGMENT_REGEX = /(?:^|\/)\.\.?(?:\/|$)/;
+
+/**
+ * Why `parsed`'s path and query are unsafe once percent-decoded, if they are.
+ * Hosts need no such check: the URL parser already decodes them before
+ * `validateUrl` compares them against the allowlist.
+ */
+function encodingProblem(parsed: URL): string | undefined {
+	let path: string;
+	let query: string;
+	try {
+		path = decodeURIComponent(parsed.pathname);
+		query = decodeURIComponent(parsed.search);
+	} catch {
+		return "URL contains malformed percent-encoding";
+	}
+	if (PERCENT_OCTET_REGEX.test(path + query)) return "URL is percent-encoded more than once";
+	if (CONTROL_CHAR_REGEX.test(path + query)) return "URL contains encoded control characters";
+	if (DOT_SEGMENT_REGEX.test(path)) return "URL path contains encoded dot segments";
+	return undefined;
+}
+
+/** Uppercase every `%xx` escape and decode the ones that stand for unreserved characters. */
+function normalizePercent(text: string): string {
+	return text.replace(/%[0-9a-f]{2}/gi, (escape) => {
+		const char = String.fromCharCode(Number.parseInt(escape.slice(1), 16));
+		return UNRESERVED_CHAR_REGEX.test(char) ? char : escape.toUpperCase();
+	});
+}
+
+/** A punycode (`xn--`) label, i.e. an internationalized one once parsed. */
+const PUNYCODE_LABEL_REGEX = /(?:^|\.)xn--/;
+
+/**
+ * Whether `host` is `domain` or a subdomain of it, matched on whole labels.
+ * Punycode labels only match where `domain` itself spells them out, so an
+ * IDN lookalike can never ride on an allowlisted suffix.
+ */
 function hostMatchesDomain(host: string, domain: string): boolean {
-	return host === domain || host.endsWith(`.${domain}`);
+	if (host === domain) return true;
+	if (!host.endsWith(`.${domain}`)) return false;
+	return !PUNYCODE_LABEL_REGEX.test(host.slice(0, -domain.length - 1));
+}
+
+/** Whether `host` is one of `domains` or a subdomain of one, as `validateUrl` matches hosts. */
+export function isAllowedHost(host: string, domains: readonly string[]): boolean {
+	return domains.some((domain) => hostMatchesDomain(host.toLowerCase(), domain));
 }
 
 function platformFromHost(host: string): SupportedPlatform | null {
@@ -28,47 +87,425 @@ function platformFromHost(host: string): SupportedPlatform | null {
 }
 
 /**
- * Validate a URL for safe processing and supported platform check
+ * Shapes of media post URLs, as `host/path` (query dropped), for platforms
+ * whose hosts also serve plenty of pages yt-dlp cannot or should not download
+ * (feeds, groups, VODs). Their URLs must match to pass `validateUrl`; share
+ * redirectors on `SHORTLINK_HOSTS` are exempt and checked once resolved.
  */
-export function validateUrl(url: string): { valid: boolean; error?: string } {
+const MEDIA_PATH_PATTERNS: Partial<Record<SupportedPlatform, RegExp>> = {
+	// /reel/ID, /watch/?v=ID, /watch/live/?v=ID, /videos/ID, /PAGE/videos/[SLUG/]ID,
+	// /share/v/TOKEN, /share/r/TOKEN and mobile /story.php?story_fbid=ID.
+	facebook:
+		/^[^/]+\/(?:reel\/\d+|watch(?:\/live)?|videos\/\d+|[^/]+\/videos\/(?:[^/]+\/)?\d+|share\/[rv]\/[\w-]+|story\.php)\/?$/,
+	// /r/SUB/comments/ID[/SLUG[/COMMENT]], /user/NAME/comments/ID and /r/SUB/s/TOKEN share links.
+	reddit: /^[^/]+\/(?:(?:r|u|user)\/[^/]+\/)?(?:comments\/\w+(?:\/[^/]+){0,2}|s\/\w+)\/?$/,
+	// clips.twitch.tv/SLUG and twitch.tv/CHANNEL/clip/SLUG; never VODs or live channels.
+	twitch: /^(?:clips\.twitch\.tv\/[\w-]+|(?:[^/]+\.)?twitch\.tv\/\w+\/clip\/[\w-]+)\/?$/,
+	// /shorts/ID and youtu.be/ID; never watch pages, channels or playlists.
+	youtube: /^(?:(?:[^/]+\.)?youtu\.be\/[\w-]+|[^/]+\/shorts\/[\w-]+)\/?$/,
+};
+
+/**
+ * Platforms supported only at their {@link MEDIA_PATH_PATTERNS}: any other URL
+ * on their hosts belongs to no platform as far as `detectPlatform` is concerned.
+ */
+const PATH_SCOPED_PLATFORMS: readonly SupportedPlatform[] = ["youtube"];
+
+/** Rejections more specific than "This <label> URL does not point at a video". */
+const MEDIA_PATH_ERRORS: Partial<Record<SupportedPlatform, string>> = {
+	twitch: "Unsupported Twitch content: only clips can be downloaded, not VODs or live channels",
+	youtube: "Unsupported YouTube content: only Shorts can be downloaded, not videos or channels",
+};
+
+/**
+ * Where each platform's URLs carry the post ID, as `host/path?query`, and the
+ * shape a real one has. A URL whose ID is there but misshapen is a broken
+ * link, refused by `validateUrl` before it costs a yt-dlp call.
+ */
+const VIDEO_ID_PATTERNS: Partial<Record<SupportedPlatform, { segment: RegExp; id: RegExp }>> = {
+	// /p/CODE, /reel/CODE, /reels/CODE and /tv/CODE, optionally under /USER/.
+	instagram: { segment: /^[^/]+\/(?:[^/]+\/)?(?:p|reels?|tv)\/([^/?]+)/, id: /^[\w-]{5,}$/ },
+	reddit: { segment: /^[^/]+\/(?:(?:r|u|user)\/[^/]+\/)?comments\/([^/?]+)/, id: /^[a-z0-9]+$/ },
+	tiktok: { segment: /^[^/]+\/@[^/]+\/video\/([^/?]+)/, id: /^\d+$/ },
+	// Clip slugs: clips.twitch.tv/SLUG and twitch.tv/CHANNEL/clip/SLUG.
+	twitch: { segment: /^(?:clips\.twitch\.tv|[^/]+\/\w+\/clip)\/([^/?]+)/, id: /^[\w-]+$/ },
+	twitter: { segment: /^[^/]+\/(?:i\/web|[^/]+)\/status(?:es)?\/([^/?]+)/, id: /^\d+$/ },
+	// /shorts/ID and youtu.be/ID.
+	youtube: { segment: /^(?:(?:[^/]+\.)?youtu\.be|[^/]+\/shorts)\/([^/?]*)/, id: /^[\w-]{11}$/ },
+};
+
+/** A post as its platform knows it; see {@link extractVideoId}. */
+export interface VideoId {
+	platform: SupportedPlatform;
+	id: string;
+}
+
+/** The ID segment of `parsed` per {@link VIDEO_ID_PATTERNS}, well formed or not. */
+function videoIdSegment(parsed: URL): (VideoId & { wellFormed: boolean }) | undefined {
+	const host = parsed.hostname.toLowerCase();
+	const platform = platformFromHost(host);
+	const pattern = platform ? VIDEO_ID_PATTERNS[platform] : undefined;
+	const id = pattern?.segment.exec(host + parsed.pathname + parsed.search)?.[1];
+	if (!platform || !pattern || id === undefined) return undefined;
+	return { platform, id, wellFormed: pattern.id.test(id) };
+}
+
+/**
+ * The platform and post ID `url` points at, when the platform's URLs carry
+ * one and it is well formed: `x.com/user/status/123` and
+ * `mobile.twitter.com/i/web/status/123?s=20` are both `twitter` `123`.
+ */
+export function extractVideoId(url: string): VideoId | undefined {
+	const parsed = parseHttpUrl(url);
+	const found = parsed ? videoIdSegment(parsed) : undefined;
+	return found?.wellFormed ? { platform: found.platform, id: found.id } : undefined;
+}
+
+export interface UrlValidation {
+	valid: boolean;
+	error?: string;
+	/** `BLOCKED` and `BLOCKED_CONTENT` only come from the API's operator blocklists. */
+	code?: Extract<
+		ErrorCode,
+		"INVALID_URL" | "INVALID_CHAR" | "UNSUPPORTED_PLATFORM" | "BLOCKED" | "BLOCKED_CONTENT"
+	>;
+	/** For `INVALID_CHAR`: code point index of the offending character in the trimmed URL. */
+	position?: number;
+	/** For `INVALID_CHAR`: the offending character. */
+	character?: string;
+}
+
+/**
+ * The first character `INVALID_URL_CHAR_REGEX` rejects and its index in code
+ * points, not UTF-16 units, so an emoji or accented letter before it counts once.
+ */
+function invalidCharAt(url: string): { position: number; character: string } | undefined {
+	const chars = Array.from(url);
+	const position = chars.findIndex((char) => INVALID_URL_CHAR_REGEX.test(char));
+	return position === -1 ? undefined : { position, character: chars[position] };
+}
+
+/**
+ * Validate a URL for safe processing and supported platform check. `domains`
+ * replaces the built-in host allowlist, e.g. with an operator-configured one.
+ */
+export function validateUrl(
+	url: string,
+	domains: readonly string[] = ALLOWED_PLATFORM_DOMAINS,
+): UrlValidation {
 	if (!url || typeof url !== "string") {
-		return { valid: false, error: "URL is required" };
+		return { valid: false, error: "URL is required", code: "INVALID_URL" };
 	}
 
 	const trimmed = url.trim();
 
-	if (WHITESPACE_ONLY_REGEX.test(trimmed)) {
+	const invalidChar = invalidCharAt(trimmed);
+	if (invalidChar) {
 		return {
 			valid: false,
-			error: "URL contains invalid characters. Only standard URL characters are allowed.",
+			error: `URL contains an invalid character at position ${invalidChar.position}`,
+			code: "INVALID_CHAR",
+			...invalidChar,
 		};
 	}
 
 	const parsed = parseHttpUrl(trimmed);
 	if (!parsed) {
-		return { valid: false, error: "Invalid URL format" };
+		return { valid: false, error: "Invalid URL format", code: "INVALID_URL" };
 	}
 
+	// Credentials would reach yt-dlp's argv and our logs; never echo them back.
+	if (parsed.username || parsed.password) {
+		return {
+			valid: false,
+			error: "URLs with embedded credentials are not allowed",
+			code: "INVALID_URL",
+		};
+	}
+
+	const encodingError = encodingProblem(parsed);
+	if (encodingError) return { valid: false, error: encodingError, code: "INVALID_URL" };
+
+	// With the default list this is the same lookup as detectPlatform(), so
+	// "valid" and "has a platform" never disagree.
 	const host = parsed.hostname.toLowerCase();
-	const isAllowed = ALLOWED_PLATFORM_DOMAINS.some(
-		(domain) => host === domain || host.endsWith(`.${domain}`),
-	);
+	if (host.includes("..") || host.endsWith(".")) {
+		return { valid: false, error: "Invalid host name", code: "INVALID_URL" };
+	}
+	const allowed = domains.some((domain) => hostMatchesDomain(host, domain));
+	if (!allowed && PUNYCODE_LABEL_REGEX.test(host)) {
+		return {
+			valid: false,
+			error: `Internationalized host names are not supported: '${host}'`,
+			code: "INVALID_URL",
+		};
+	}
+	if (!allowed) {
+		return {
+			valid: false,
+			error: `Unsupported platform: '${host}'. Supported: ${domains.join(", ")}`,
+			code: "UNSUPPORTED_PLATFORM",
+		};
+	}
 
-	if (!isAllowed) {
+	const platform = platformFromHost(host);
+	const label = SERVICES.find((service) => service.id === platform)?.label ?? platform;
+	if (!SHORTLINK_HOSTS.includes(host) && hostPolicyOf(host)?.allowed === false) {
+		return { valid: false, error: `Unsupported ${label} host: '${host}'`, code: "INVALID_URL" };
+	}
+	const mediaPath = platform ? MEDIA_PATH_PATTERNS[platform] : undefined;
+	const hostPath = host + parsed.pathname;
+	if (platform && mediaPath && !SHORTLINK_HOSTS.includes(host) && !mediaPath.test(hostPath)) {
+		return {
+			valid: false,
+			error: MEDIA_PATH_ERRORS[platform] ?? `This ${label} URL does not point at a video`,
+			code: "INVALID_URL",
+		};
+	}
+	const videoId = videoIdSegment(parsed);
+	if (videoId && !videoId.wellFormed && !SHORTLINK_HOSTS.includes(host)) {
 		return {
 			valid: false,
-			error: `Unsupported platform: '${host}'. Supported: ${ALLOWED_PLATFORM_DOMAINS.join(", ")}`,
+			error: `Malformed ${label} post ID: '${videoId.id}'`,
+			code: "INVALID_URL",
 		};
 	}
 
 	return { valid: true };
 }
 
+/**
+ * Whether `url` is a known share-link redirector. These are accepted ahead of
+ * `validateUrl` and must be resolved to an allowlisted URL before use. A link
+ * carrying credentials never counts, so `validateUrl` gets to reject it.
+ */
+export function isShortLink(url: string): boolean {
+	const parsed = parseHttpUrl(url);
+	if (!parsed || parsed.username || parsed.password) return false;
+	return SHORTLINK_HOSTS.includes(parsed.hostname.toLowerCase());
+}
+
+/** `url` trimmed and without its `#fragment`, which no platform needs and yt-dlp never sees. */
+export function stripFragment(url: string): string {
+	const trimmed = url.trim();
+	const hash = trimmed.indexOf("#");
+	return hash === -1 ? trimmed : trimmed.slice(0, hash);
+}
+
+/** An absolute URL's `scheme://` prefix. */
+const SCHEME_PREFIX_REGEX = /^[a-z][a-z0-9+.-]*:\/\//i;
+
+/**
+ * `url` trimmed, with `https://` in front when it was pasted without a scheme
+ * (`instagram.com/reel/ABC`) and its host starts with `www.`, is a short-link
+ * host or matches one of `domains`. Anything else comes back as it was, so it
+ * fails `validateUrl` with the usual error.
+ */
+export function withDefaultScheme(
+	url: string,
+	domains: readonly string[] = ALLOWED_PLATFORM_DOMAINS,
+): string {
+	const trimmed = url.trim();
+	// A rejected character's reported position must match what the user typed.
+	if (SCHEME_PREFIX_REGEX.test(trimmed) || invalidCharAt(trimmed)) return trimmed;
+	const host = trimmed.split(/[/?#:]/, 1)[0].toLowerCase();
+	const known =
+		host.startsWith("www.") ||
+		SHORTLINK_HOSTS.includes(host) ||
+		domains.some((domain) => hostMatchesDomain(host, domain));
+	return known ? `https://${trimmed}` : trimmed;
+}
+
+/** `localhost` and the suffixes reserved for internal or link-local names. */
+const INTERNAL_HOST_REGEX = /(?:^|\.)(?:localhost|localdomain|local|internal)$/;
+
+function ipv4Octets(address: string): number[] | null {
+	const match = address.match(/^(\d{1,3})\.(\d{1,3})\.(\d{1,3})\.(\d{1,3})$/);
+	const octets = match?.slice(1).map(Number);
+	return octets?.every((octet) => octet <= 255) ? octets : null;
+}
+
+/** This-network, private, CGNAT, loopback, link-local, multicast and reserved ranges. */
+function isPrivateIpv4([a, b]: number[]): boolean {
+	return (
+		a === 0 ||
+		a === 10 ||
+		a === 127 ||
+		a >= 224 ||
+		(a === 100 && b >= 64 && b <= 127) ||
+		(a === 169 && b === 254) ||
+		(a === 172 && b >= 16 && b <= 31) ||
+		(a === 192 && b === 168)
+	);
+}
+
+/**
+ * Whether `address`, an IPv4 or (optionally bracketed) IPv6 literal, is one
+ * the server must never fetch: loopback, private, link-local, unique-local,
+ * multicast or unspecified, IPv4-mapped forms included. False for names.
+ */
+export function isPrivateAddress(address: string): boolean {
+	const v4 = ipv4Octets(address);
+	if (v4) return isPrivateIpv4(v4);
+
+	const v6 = address.toLowerCase().replace(/^\[|\]$/g, "");
+	if (!v6.includes(":")) return false;
+	const mapped = v6.match(/^::ffff:(?:(\d[\d.]*)|([0-9a-f]{1,4}):([0-9a-f]{1,4}))$/);
+	if (mapped) {
+		const [, dotted, high, low] = mapped;
+		const word = (hex: string) => Number.parseInt(hex, 16);
+		const octets = dotted
+			? ipv4Octets(dotted)
+			: [word(high) >> 8, word(high) & 255, word(low) >> 8, word(low) & 255];
+		return !octets || isPrivateIpv4(octets);
+	}
+	return v6 === "::" || v6 === "::1" || /^(?:f[cd]|fe[89ab]|ff)/.test(v6);
+}
+
+/**
+ * Whether the server may make requests to `host` as written: not `localhost`,
+ * not under `.local`/`.internal`, not a private IP literal. Names can still
+ * resolve somewhere private, so fetchers check resolved addresses too.
+ */
+export function isSafeOutboundHost(host: string): boolean {
+	const name = host.toLowerCase().replace(/\.$/, "");
+	return !INTERNAL_HOST_REGEX.test(name) && !isPrivateAddress(name);
+}
+
+/**
+ * Query parameters that select content, per platform; every other one is a
+ * share or tracking tag (`_t`, `_r`, `igsh`, `s`, `si`, `utm_*`, ...) and is
+ * dropped. Platforms not listed keep their whole query.
+ */
+const KEPT_QUERY_PARAMS: Partial<Record<SupportedPlatform, readonly string[]>> = {
+	facebook: ["v", "story_fbid", "id"],
+	instagram: ["img_index"],
+	reddit: [],
+	tiktok: [],
+	twitter: [],
+	youtube: [],
+};
+
+interface HostPolicy {
+	/** The host every allowed spelling is rewritten to. */
+	canonical: string;
+	/** Registrable domains the policy covers; the platform's other hosts are left alone. */
+	domains: readonly string[];
+	/** Subdomain labels allowed under each domain; `""` is the domain itself. */
+	prefixes: readonly string[];
+}
+
+/**
+ * Subdomain policy of platforms whose mobile, regional and bare hosts all
+ * mirror one site: the allowed spellings are rewritten to `canonical`, so a
+ * post shares one cache entry however it was linked, and any other subdomain
+ * is refused. Short-link hosts (`vm.tiktok.com`) are exempt.
+ */
+const HOST_POLICIES: readonly HostPolicy[] = [
+	{
+		canonical: "www.facebook.com",
+		domains: ["facebook.com"],
+		prefixes: ["", "www", "m", "mbasic", "web"],
+	},
+	{ canonical: "www.instagram.com", domains: ["instagram.com"], prefixes: ["", "www", "m"] },
+	{
+		canonical: "www.reddit.com",
+		domains: ["reddit.com"],
+		prefixes: ["", "www", "old", "new", "m", "np"],
+	},
+	{ canonical: "www.tiktok.com", domains: ["tiktok.com"], prefixes: ["", "www", "m"] },
+	{ canonical: "x.com", domains: ["x.com", "twitter.com"], prefixes: ["", "www", "m", "mobile"] },
+	{ canonical: "www.youtube.com", domains: ["youtube.com"], prefixes: ["", "www", "m", "music"] },
+];
+
+/** The {@link HOST_POLICIES} entry covering `host`, and whether its subdomain is allowed. */
+function hostPolicyOf(host: string): { policy: HostPolicy; allowed: boolean } | undefined {
+	for (const policy of HOST_POLICIES) {
+		const domain = policy.domains.find((candidate) => hostMatchesDomain(host, candidate));
+		if (!domain) continue;
+		const prefix = host.slice(0, Math.max(host.length - domain.length - 1, 0));
+		return { policy, allowed: policy.prefixes.includes(prefix) };
+	}
+	return undefined;
+}
+
+/**
+ * The one spelling of `url` used as a cache key and as yt-dlp's argument:
+ * reserialized on the platform's canonical host (see {@link HOST_POLICIES})
+ * without its fragment or the platform's tracking parameters (see
+ * {@link KEPT_QUERY_PARAMS}), with unreserved characters decoded and every
+ * other escape in uppercase hex (RFC 3986 §6.2.2). Call it on a URL that
+ * already passed `validateUrl`; anything unparseable comes back
+ * {@link stripFragment}ed.
+ */
+export function canonicalUrl(url: string): string {
+	const parsed = parseHttpUrl(url);
+	if (!parsed) return stripFragment(url);
+	parsed.hash = "";
+	const hostPolicy = hostPolicyOf(parsed.hostname);
+	if (hostPolicy?.allowed) parsed.hostname = hostPolicy.policy.canonical;
+	const platform = platformFromHost(parsed.hostname);
+	const kept = platform ? KEPT_QUERY_PARAMS[platform] : undefined;
+	if (kept) {
+		for (const name of [...parsed.searchParams.keys()]) {
+			if (!kept.includes(name)) parsed.searchParams.delete(name);
+		}
+	}
+	parsed.pathname = normalizePercent(parsed.pathname);
+	parsed.search = normalizePercent(parsed.search);
+	return parsed.toString();
+}
+
+/**
+ * Path shapes of creator pages. Only platforms whose yt-dlp extractor can list
+ * a profile are here; every other URL is treated as a single post.
+ */
+const PROFILE_PATH_PATTERNS: Partial<Record<SupportedPlatform, RegExp>> = {
+	tiktok: /^\/@[^/]+\/?$/,
+	instagram: /^\/(?!(?:p|reels?|tv|stories|explore|accounts)\/?$)[^/]+\/?$/,
+};
+
+/** Whether `platform` has creator pages that resolve to a list of uploads. */
+export function supportsProfiles(platform: SupportedPlatform): boolean {
+	return PROFILE_PATH_PATTERNS[platform] !== undefined;
+}
+
+/** Whether `url` points at a creator's profile rather than one post. */
+export function isProfileUrl(url: string): boolean {
+	const parsed = parseHttpUrl(url);
+	const platform = parsed && platformFromHost(parsed.hostname.toLowerCase());
+	const pattern = platform ? PROFILE_PATH_PATTERNS[platform] : undefined;
+	return !!parsed && !!pattern && pattern.test(parsed.pathname);
+}
+
 /**
  * Detect platform from URL
  */
 export function detectPlatform(url: string): SupportedPlatform | null {
 	const parsed = parseHttpUrl(url);
 	if (!parsed) return null;
-	return platformFromHost(parsed.hostname.toLowerCase());
+	const host = parsed.hostname.toLowerCase();
+	const platform = platformFromHost(host);
+	if (platform && PATH_SCOPED_PLATFORMS.includes(platform)) {
+		return MEDIA_PATH_PATTERNS[platform]?.test(host + parsed.pathname) ? platform : null;
+	}
+	return platform;
+}
+
+/** The platform whose trusted media CDN serves `url`, or null; HTTPS links only. */
+export function detectCdnPlatform(url: string): SupportedPlatform | null {
+	const parsed = parseHttpUrl(url);
+	if (parsed?.protocol !== "https:") return null;
+	const host = parsed.hostname.toLowerCase();
+	for (const [platform, domains] of Object.entries(TRUSTED_CDN_HOSTS)) {
+		if (domains.some((domain) => hostMatchesDomain(host, domain))) {
+			return platform as SupportedPlatform;
+		}
+	}
+	return null;
+}
+
+/** The platform behind `url`: the one whose host it is on, else whose media CDN serves it. */
+export function platformOf(url: string): SupportedPlatform | null {
+	return detectPlatform(url) ?? detectCdnPlatform(url);
 }
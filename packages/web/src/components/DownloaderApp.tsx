@@ -1,6 +1,13 @@
-import { detectPlatform, type ResolveResponse, SERVICES } from "@snatch/shared";
+import {
+	detectPlatform,
+	OPT_IN_PLATFORMS,
+	type PlatformInfo,
+	type ResolveResponse,
+	SERVICES,
+	withDefaultScheme,
+} from "@snatch/shared";
 import { CheckCircle, Download, Loader2, Settings, X, XCircle } from "lucide-react";
-import { useCallback, useState } from "react";
+import { useCallback, useEffect, useState } from "react";
 import { API_BASE_URL } from "../config";
 import { Sentry } from "../lib/sentry";
 import { DownloaderInput } from "./DownloaderInput";
@@ -20,6 +27,22 @@ export function DownloaderApp() {
 	});
 	const [isSettingsOpen, setIsSettingsOpen] = useState(false);
 
+	// What this deployment accepts; the built-in list until the API answers.
+	const [services, setServices] = useState<Pick<PlatformInfo, "id" | "name">[]>(() =>
+		SERVICES.filter((service) => !OPT_IN_PLATFORMS.includes(service.id)).map((service) => ({
+			id: service.id,
+			name: service.label,
+		})),
+	);
+	useEffect(() => {
+		fetch(`${API_BASE_URL}/api/platforms`)
+			.then((response) => (response.ok ? (response.json() as Promise<PlatformInfo[]>) : []))
+			.then((platforms) => {
+				if (platforms.length) setServices(platforms);
+			})
+			.catch(() => {});
+	}, []);
+
 	// Core State
 	const [loading, setLoading] = useState(false);
 	const [error, setError] = useState<string | null>(null);
@@ -55,7 +78,8 @@ export function DownloaderApp() {
 	}, []);
 
 	const handleDownload = async (rawUrl: string) => {
-		const url = rawUrl.trim();
+		// `instagram.com/reel/…` pasted without a scheme still resolves.
+		const url = withDefaultScheme(rawUrl);
 		if (!url) {
 			setError("Please enter a valid URL");
 			return;
@@ -91,12 +115,16 @@ export function DownloaderApp() {
 				| ResolveResponse
 				| { success?: boolean; error?: string };
 
-			// Transport/HTTP-level failures (rate-limit 429, gateway 5xx, a body
-			// we couldn't parse). Surface the server's structured message when it
-			// sent one, otherwise derive from the status so the user isn't left
-			// looking at a silent no-op.
+			// HTTP-level failures: validation 400s, rate-limit 429, classified
+			// engine failures (404 removed, 403 private, 451 geo-blocked), gateway
+			// 5xx, or a body we couldn't parse. Surface the server's structured
+			// message when it sent one, otherwise derive from the status so the
+			// user isn't left looking at a silent no-op.
 			if (!response.ok) {
-				const serverError = "success" in data && typeof data.error === "string" ? data.error : null;
+				const serverError =
+					"success" in data
+						? typeof data.error === "string" && data.error
+						: data.error?.message;
 				throw new Error(serverError || `Request failed (${response.status})`);
 			}
 
@@ -116,6 +144,11 @@ export function DownloaderApp() {
 				setPickerResponse(resolveData);
 				setResolvedUrl(url);
 			}
+
+			// A profile URL lists recent uploads; picking one resolves it in turn.
+			if (resolveData.status === "list") {
+				setPickerResponse(resolveData);
+			}
 		} catch (error) {
 			Sentry.captureException(error);
 			setError(
@@ -158,7 +191,7 @@ export function DownloaderApp() {
 								<span className="relative inline-flex rounded-full h-2 w-2 bg-green-500"></span>
 							</span>
 							<span className="text-sm font-medium text-gray-300">
-								{SERVICES.length}+ services supported
+								{services.length}+ services supported
 							</span>
 						</div>
 
@@ -284,6 +317,47 @@ export function DownloaderApp() {
 							</div>
 						</div>
 					)}
+
+					{/* Profile Entries Panel */}
+					{pickerResponse?.entries && (
+						<div className="max-w-2xl mx-auto space-y-4 text-left animate-in fade-in zoom-in duration-300 p-6 bg-zinc-900/40 border border-zinc-800 rounded-2xl">
+							<div className="flex items-start justify-between gap-4">
+								<div className="min-w-0">
+									<h3 className="text-lg font-bold text-white">Recent uploads</h3>
+									<p className="text-xs text-zinc-500 mt-1">Pick one to see its formats.</p>
+								</div>
+								<button
+									type="button"
+									onClick={() => setPickerResponse(null)}
+									className="shrink-0 p-1 px-3 bg-white/5 hover:bg-white/10 text-zinc-300 hover:text-white text-xs font-semibold rounded-lg transition-colors border border-white/5"
+								>
+									Clear
+								</button>
+							</div>
+
+							<div className="flex flex-col gap-2">
+								{pickerResponse.entries.map((entry) => (
+									<button
+										key={entry.id}
+										type="button"
+										onClick={() => {
+											void handleDownload(entry.url);
+										}}
+										className="w-full px-4 py-3 bg-zinc-900 hover:bg-zinc-800 border border-zinc-800 hover:border-purple-500/40 rounded-xl text-sm font-medium text-zinc-200 transition-colors flex items-center gap-3"
+									>
+										{entry.thumbnail && (
+											<img
+												src={entry.thumbnail}
+												alt=""
+												className="w-10 h-10 rounded object-cover shrink-0 bg-zinc-950"
+											/>
+										)}
+										<span className="truncate">{entry.title || entry.url}</span>
+									</button>
+								))}
+							</div>
+						</div>
+					)}
 				</div>
 
 				{/* Loading State */}
@@ -312,12 +386,12 @@ export function DownloaderApp() {
 					</div>
 
 					<div className="flex flex-wrap justify-center gap-2.5">
-						{SERVICES.map((service) => (
+						{services.map((service) => (
 							<span
 								key={service.id}
 								className="px-3.5 py-1.5 rounded-lg bg-white/5 border border-white/10 text-sm text-gray-300 hover:border-white/25 hover:text-white transition-colors animate-in fade-in duration-300"
 							>
-								{service.label}
+								{service.name}
 							</span>
 						))}
 					</div>
@@ -44,7 +44,9 @@ export function DownloaderInput({ onSubmit, onValueChange, loading }: Downloader
 							<div className="flex flex-col gap-4 sm:flex-row">
 								<div className="relative flex-1">
 									<input
-										type="url"
+										// Not type="url": the browser would block scheme-less links.
+										type="text"
+										inputMode="url"
 										value={field.state.value}
 										onBlur={field.handleBlur}
 										onChange={(event) => {